@@ -2,8 +2,86 @@ export const API_BASE = "/api";
 
 export const ENDPOINTS = {
   users: {
-    list: () => `${API_BASE}/users`,
+    list: (params?: Record<string, string>) =>
+      `${API_BASE}/users${params ? `?${new URLSearchParams(params)}` : ""}`,
     create: () => `${API_BASE}/users`,
+    import: (dryRun: boolean) => `${API_BASE}/users/import?dry_run=${dryRun}`,
+    /** Same filters as `list`, including `attr.<name>` */
+    export: (params?: Record<string, string>) =>
+      `${API_BASE}/users/export${params ? `?${new URLSearchParams(params)}` : ""}`,
     byId: (id: string) => `${API_BASE}/users/${id}`,
+    status: (id: string) => `${API_BASE}/users/${id}/status`,
+    profile: (id: string) => `${API_BASE}/users/${id}/profile`,
+    profileHistory: (id: string) => `${API_BASE}/users/${id}/profile/history`,
+    reports: (id: string, transitive = false) => `${API_BASE}/users/${id}/reports?transitive=${transitive}`,
+    chain: (id: string) => `${API_BASE}/users/${id}/chain`,
+    invite: (id: string) => `${API_BASE}/users/${id}/invite`,
+    attributes: (id: string) => `${API_BASE}/users/${id}/attributes`,
+    /** PUT multipart field `file` (JPEG, PNG or WebP, max 5 MB), DELETE to remove */
+    avatar: (id: string) => `${API_BASE}/users/${id}/avatar`,
+  },
+  me: {
+    profile: () => `${API_BASE}/me/profile`,
+    /** POST { new_email, current_password } to start, DELETE to cancel */
+    email: () => `${API_BASE}/me/email`,
+    /** POST { token } from the confirmation mail, while signed in */
+    verifyEmail: () => `${API_BASE}/me/email/verify`,
+    /** PUT multipart field `file`, DELETE to remove */
+    avatar: () => `${API_BASE}/me/avatar`,
+  },
+  userAttributes: {
+    list: () => `${API_BASE}/admin/user-attributes`,
+    byId: (id: string) => `${API_BASE}/admin/user-attributes/${id}`,
+  },
+  leaveRequests: {
+    /** POST multipart field `file` (PDF, JPEG or PNG, max 10 MB) */
+    attachments: () => `${API_BASE}/leave-requests/attachments`,
+    attachment: (id: string) => `${API_BASE}/leave-requests/${id}/attachment`,
+    /** Days a request over these dates would take, skipping weekends and holidays */
+    workingDays: (startDate: string, endDate: string) =>
+      `${API_BASE}/leave-requests/working-days?${new URLSearchParams({ start_date: startDate, end_date: endDate })}`,
+    /** POST; pending requests, or approved ones that haven't started */
+    cancel: (id: string) => `${API_BASE}/leave-requests/${id}/cancel`,
+  },
+  leaveBalances: {
+    mine: () => `${API_BASE}/leave-balances`,
+    myLedger: (leaveType?: string) =>
+      `${API_BASE}/leave-balances/ledger${leaveType ? `?${new URLSearchParams({ leave_type: leaveType })}` : ""}`,
+    byUser: (id: string) => `${API_BASE}/users/${id}/leave-balances`,
+    ledger: (id: string, leaveType?: string) =>
+      `${API_BASE}/users/${id}/leave-balances/ledger${leaveType ? `?${new URLSearchParams({ leave_type: leaveType })}` : ""}`,
+    /** POST { leave_type, days, effective_date?, note } */
+    adjustments: (id: string) => `${API_BASE}/users/${id}/leave-balances/adjustments`,
+    /** POST { as_of? } */
+    runAccruals: () => `${API_BASE}/admin/leave-accruals/run`,
+  },
+  leaveTypes: {
+    /** Active types the signed-in user is eligible for */
+    requestable: () => `${API_BASE}/leave-types`,
+    list: () => `${API_BASE}/admin/leave-types`,
+    byId: (id: string) => `${API_BASE}/admin/leave-types/${id}`,
+  },
+  holidayCalendars: {
+    list: () => `${API_BASE}/holiday-calendars`,
+    holidays: (id: string, year?: number) =>
+      `${API_BASE}/holiday-calendars/${id}/holidays${year ? `?year=${year}` : ""}`,
+    create: () => `${API_BASE}/admin/holiday-calendars`,
+    byId: (id: string) => `${API_BASE}/admin/holiday-calendars/${id}`,
+    addHoliday: (id: string) => `${API_BASE}/admin/holiday-calendars/${id}/holidays`,
+    holiday: (id: string, holidayId: string) => `${API_BASE}/admin/holiday-calendars/${id}/holidays/${holidayId}`,
+    /** POST an .ics file as the body; `replace` also removes dates the file doesn't have */
+    import: (id: string, options: { dryRun?: boolean; replace?: boolean } = {}) =>
+      `${API_BASE}/admin/holiday-calendars/${id}/import?${new URLSearchParams({
+        dry_run: String(!!options.dryRun),
+        replace: String(!!options.replace),
+      })}`,
+  },
+  invites: {
+    byToken: (token: string) => `${API_BASE}/invites/${token}`,
+    accept: (token: string) => `${API_BASE}/invites/${token}/accept`,
+  },
+  org: {
+    chart: (params?: Record<string, string>) =>
+      `${API_BASE}/org/chart${params ? `?${new URLSearchParams(params)}` : ""}`,
   },
 } as const;
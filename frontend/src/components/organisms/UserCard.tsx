@@ -1,4 +1,5 @@
 import { Pencil, Trash2, Mail, User as UserIcon } from "lucide-react"
+import { Avatar, AvatarFallback, AvatarImage } from "@/components/ui/avatar"
 import { Button } from "@/components/ui/button"
 import {
     DropdownMenu,
@@ -20,9 +21,12 @@ export function UserCard({ user, onEdit, onDelete }: UserCardProps) {
             {/* Name & Email Column */}
             <div className="flex flex-col sm:flex-row sm:items-start gap-0.5 sm:gap-3 overflow-hidden">
                 <div className="flex items-center gap-3">
-                    <div className="h-6 w-6 rounded-md bg-primary/10 flex items-center justify-center text-primary/70 shrink-0">
-                        <UserIcon className="h-3.5 w-3.5" />
-                    </div>
+                    <Avatar className="h-6 w-6 rounded-md">
+                        <AvatarImage src={user.avatar_urls?.small} alt="" className="object-cover" />
+                        <AvatarFallback className="rounded-md bg-primary/10 text-primary/70">
+                            <UserIcon className="h-3.5 w-3.5" />
+                        </AvatarFallback>
+                    </Avatar>
                     <span className="text-[14px] font-semibold tracking-tight truncate leading-6">{user.username}</span>
                 </div>
                 <div className="flex sm:hidden items-center gap-1.5 pl-9">
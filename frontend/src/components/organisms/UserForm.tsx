@@ -20,7 +20,6 @@ export function UserForm({
   const [formData, setFormData] = React.useState<UserPayload>({
     username: initialData?.username ?? "",
     email: initialData?.email ?? "",
-    password_hash: initialData?.password_hash ?? "",
   })
 
   function handleChange<K extends keyof UserPayload>(
@@ -65,21 +64,11 @@ export function UserForm({
         />
       </div>
 
-      {/* Password (optional / future-ready) */}
-      <div className="space-y-2">
-        <Label htmlFor="password">Password</Label>
-        <Input
-          id="password"
-          type="password"
-          placeholder={
-            initialData ? "Leave unchanged" : "Set password"
-          }
-          value={formData.password_hash}
-          onChange={(e) =>
-            handleChange("password_hash", e.target.value)
-          }
-        />
-      </div>
+      {!initialData && (
+        <p className="text-sm text-muted-foreground">
+          An invite is emailed so the user can choose their own password.
+        </p>
+      )}
 
       {/* Actions */}
       <div className="flex flex-col-reverse gap-3 sm:flex-row sm:justify-end">
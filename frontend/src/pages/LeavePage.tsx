@@ -14,7 +14,7 @@ import LeaveRequest from "./employee/LeaveRequest"
 interface LeaveRequestAdmin {
     id: string
     employee: { name: string; avatar?: string }
-    leaveType: "Sick" | "Vacation" | "Personal" | "Maternity" | "Casual" | "Unpaid"
+    leaveType: string
     startDate: string
     endDate: string
     duration: string
@@ -91,7 +91,7 @@ const PermissionSimulator: React.FC = () => {
                                 >
                                     <option value="">Select User...</option>
                                     {users.map(u => (
-                                        <option key={u.id} value={u.id}>{u.username} ({u.email})</option>
+                                        <option key={u.id} value={u.id}>{u.username}{u.email ? ` (${u.email})` : ""}</option>
                                     ))}
                                 </select>
                             </div>
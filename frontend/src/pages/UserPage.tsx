@@ -42,7 +42,7 @@ function UserPage() {
   const filteredUsers = users.filter(
     (u) =>
       u.username.toLowerCase().includes(search.toLowerCase()) ||
-      u.email.toLowerCase().includes(search.toLowerCase())
+      (u.email ?? "").toLowerCase().includes(search.toLowerCase())
   )
 
   // ---------- Handlers ----------
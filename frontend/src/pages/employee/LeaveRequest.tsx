@@ -8,20 +8,24 @@ import { Badge } from "@/components/ui/badge"
 import { Dialog, DialogContent, DialogHeader, DialogTitle, DialogTrigger, DialogFooter } from "@/components/ui/dialog"
 import { Label } from "@/components/ui/label"
 import { Textarea } from "@/components/ui/textarea"
+import { Input } from "@/components/ui/input"
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select"
 import { toast } from "sonner"
 import { format } from "date-fns"
 import { Calendar } from "@/components/ui/calendar"
 import { Popover, PopoverContent, PopoverTrigger } from "@/components/ui/popover"
 import { cn } from "@/lib/utils"
+import { ENDPOINTS } from "@/api/endpoints"
+import type { LeaveAttachment, LeaveBalance, LeaveType } from "@/types/leave"
+import type { WorkingDays } from "@/types/holiday"
 
 interface LeaveRequest {
     id: string
-    leaveType: "Casual" | "Sick" | "Unpaid"
+    leaveType: string
     startDate: string
     endDate: string
     reason: string
-    status: "Pending" | "Approved" | "Rejected"
+    status: "Pending" | "Approved" | "Rejected" | "Cancelled"
     approver?: string
     createdAt: string
 }
@@ -70,7 +74,8 @@ const leaveColumns: Column<LeaveRequest>[] = [
             const colors: Record<string, string> = {
                 Pending: "bg-yellow-100 text-yellow-700 dark:bg-yellow-900/30 dark:text-yellow-400",
                 Approved: "bg-emerald-100 text-emerald-700 dark:bg-emerald-900/30 dark:text-emerald-400",
-                Rejected: "bg-rose-100 text-rose-700 dark:bg-rose-900/30 dark:text-rose-400"
+                Rejected: "bg-rose-100 text-rose-700 dark:bg-rose-900/30 dark:text-rose-400",
+                Cancelled: "bg-gray-100 text-gray-700 dark:bg-gray-800 dark:text-gray-400"
             }
             const colorClass = colors[item.status] || "bg-gray-100 text-gray-700"
             return <Badge variant="secondary" className={colorClass}>{item.status}</Badge>
@@ -90,17 +95,66 @@ export default function LeaveRequest() {
     const [isDialogOpen, setIsDialogOpen] = useState(false)
 
     // Form state
-    const [leaveType, setLeaveType] = useState<"Casual" | "Sick" | "Unpaid">("Casual")
+    const [leaveTypes, setLeaveTypes] = useState<LeaveType[]>([])
+    const [balances, setBalances] = useState<LeaveBalance[]>([])
+    const [leaveType, setLeaveType] = useState("")
+    const [attachment, setAttachment] = useState<File | null>(null)
     const [startDate, setStartDate] = useState<Date | undefined>(undefined)
     const [endDate, setEndDate] = useState<Date | undefined>(undefined)
+    const [workingDays, setWorkingDays] = useState<WorkingDays | null>(null)
     const [reason, setReason] = useState("")
     const [isSubmitting, setIsSubmitting] = useState(false)
 
     // Fetch Requests
     useEffect(() => {
         fetchRequests()
+        fetchLeaveTypes()
+        fetchBalances()
     }, [])
 
+    // Preview how many days the selected range takes
+    useEffect(() => {
+        setWorkingDays(null)
+        if (!startDate || !endDate || startDate > endDate) return
+
+        let cancelled = false
+        fetch(ENDPOINTS.leaveRequests.workingDays(format(startDate, 'yyyy-MM-dd'), format(endDate, 'yyyy-MM-dd')))
+            .then(res => (res.ok ? res.json() : null))
+            .then((preview: WorkingDays | null) => {
+                if (!cancelled) setWorkingDays(preview)
+            })
+            .catch(console.error)
+        return () => {
+            cancelled = true
+        }
+    }, [startDate, endDate])
+
+    const fetchBalances = async () => {
+        try {
+            const res = await fetch(ENDPOINTS.leaveBalances.mine())
+            if (res.ok) {
+                setBalances(await res.json())
+            }
+        } catch (error) {
+            console.error(error)
+        }
+    }
+
+    const fetchLeaveTypes = async () => {
+        try {
+            const res = await fetch(ENDPOINTS.leaveTypes.requestable())
+            if (res.ok) {
+                const types: LeaveType[] = await res.json()
+                setLeaveTypes(types)
+                setLeaveType(current => current || types[0]?.name || "")
+            }
+        } catch (error) {
+            console.error(error)
+        }
+    }
+
+    const selectedType = leaveTypes.find(t => t.name === leaveType)
+
     const fetchRequests = async () => {
         setLoading(true)
         try {
@@ -150,13 +204,31 @@ export default function LeaveRequest() {
             return
         }
 
+        if (selectedType?.requires_attachment && !attachment) {
+            toast.error(`${selectedType.name} leave requires a supporting document`)
+            return
+        }
+
         setIsSubmitting(true)
         try {
+            let attachmentId: string | undefined
+            if (attachment) {
+                const body = new FormData()
+                body.append("file", attachment)
+                const upload = await fetch(ENDPOINTS.leaveRequests.attachments(), { method: 'POST', body })
+                if (!upload.ok) {
+                    toast.error(upload.status === 415 ? "Attachments must be PDF, JPEG or PNG" : "Failed to upload attachment")
+                    return
+                }
+                attachmentId = ((await upload.json()) as LeaveAttachment).id
+            }
+
             const payload = {
                 leave_type: leaveType,
                 start_date: format(startDate, 'yyyy-MM-dd'),
                 end_date: format(endDate, 'yyyy-MM-dd'),
-                reason
+                reason,
+                attachment_id: attachmentId
             }
 
             const res = await fetch('/api/leave-requests', {
@@ -169,12 +241,14 @@ export default function LeaveRequest() {
                 toast.success("Leave request submitted successfully")
                 setIsDialogOpen(false)
                 // Reset form
-                setLeaveType("Casual")
+                setLeaveType(leaveTypes[0]?.name ?? "")
+                setAttachment(null)
                 setStartDate(undefined)
                 setEndDate(undefined)
                 setReason("")
                 // Refresh list
                 fetchRequests()
+                fetchBalances()
             } else {
                 toast.error("Failed to submit request")
             }
@@ -217,16 +291,24 @@ export default function LeaveRequest() {
                         <div className="grid gap-4 py-4">
                             <div className="grid gap-2">
                                 <Label>Leave Type</Label>
-                                <Select value={leaveType} onValueChange={(v) => setLeaveType(v as typeof leaveType)}>
+                                <Select value={leaveType} onValueChange={setLeaveType}>
                                     <SelectTrigger>
-                                        <SelectValue />
+                                        <SelectValue placeholder="Select a leave type" />
                                     </SelectTrigger>
                                     <SelectContent className="z-[9999]">
-                                        <SelectItem value="Casual">Casual Leave</SelectItem>
-                                        <SelectItem value="Sick">Sick Leave</SelectItem>
-                                        <SelectItem value="Unpaid">Unpaid Leave</SelectItem>
+                                        {leaveTypes.map(t => (
+                                            <SelectItem key={t.id} value={t.name}>
+                                                {t.name} Leave{t.paid ? "" : " (unpaid)"}
+                                            </SelectItem>
+                                        ))}
                                     </SelectContent>
                                 </Select>
+                                {selectedType && (selectedType.min_notice_days > 0 || selectedType.max_consecutive_days) && (
+                                    <span className="text-xs text-muted-foreground">
+                                        {selectedType.min_notice_days > 0 && `${selectedType.min_notice_days} days notice. `}
+                                        {selectedType.max_consecutive_days && `At most ${selectedType.max_consecutive_days} consecutive days.`}
+                                    </span>
+                                )}
                             </div>
                             <div className="grid grid-cols-2 gap-4">
                                 <div className="grid gap-2">
@@ -280,6 +362,14 @@ export default function LeaveRequest() {
                                     </Popover>
                                 </div>
                             </div>
+                            {workingDays && (
+                                <p className="text-xs text-muted-foreground -mt-2">
+                                    {workingDays.days} working {workingDays.days === 1 ? "day" : "days"}
+                                    {workingDays.weekend_days > 0 && `, ${workingDays.weekend_days} weekend`}
+                                    {workingDays.holidays.length > 0 &&
+                                        `, ${[...new Set(workingDays.holidays.map(h => h.name))].join(", ")} excluded`}
+                                </p>
+                            )}
                             <div className="grid gap-2">
                                 <Label>
                                     Reason <span className="text-muted-foreground">(min. 5 words)</span>
@@ -294,6 +384,19 @@ export default function LeaveRequest() {
                                     {countWords(reason)} / 5 words minimum
                                 </span>
                             </div>
+                            <div className="grid gap-2">
+                                <Label>
+                                    Supporting document{" "}
+                                    <span className="text-muted-foreground">
+                                        ({selectedType?.requires_attachment ? "required" : "optional"}; PDF, JPEG or PNG)
+                                    </span>
+                                </Label>
+                                <Input
+                                    type="file"
+                                    accept="application/pdf,image/jpeg,image/png"
+                                    onChange={(e) => setAttachment(e.target.files?.[0] ?? null)}
+                                />
+                            </div>
                         </div>
                         <DialogFooter>
                             <Button variant="outline" onClick={() => setIsDialogOpen(false)}>Cancel</Button>
@@ -305,35 +408,35 @@ export default function LeaveRequest() {
                 </Dialog>
             </div>
 
-            {/* Leave Balance - Static for now, can be fetched if API provided balance endpoint */}
+            {/* Leave Balance - types without a balance have no limit */}
             <div className="grid grid-cols-1 sm:grid-cols-3 gap-4">
-                <Card className="p-4 border-none bg-blue-500/10">
-                    <div className="flex items-center gap-3">
-                        <CalendarIcon className="h-5 w-5 text-blue-600" />
-                        <div>
-                            <div className="text-xl font-bold text-blue-600">8</div>
-                            <div className="text-xs type-secondary">Casual Available</div>
-                        </div>
-                    </div>
-                </Card>
-                <Card className="p-4 border-none bg-amber-500/10">
-                    <div className="flex items-center gap-3">
-                        <FileText className="h-5 w-5 text-amber-600" />
-                        <div>
-                            <div className="text-xl font-bold text-amber-600">5</div>
-                            <div className="text-xs type-secondary">Sick Available</div>
-                        </div>
-                    </div>
-                </Card>
-                <Card className="p-4 border-none bg-gray-500/10">
-                    <div className="flex items-center gap-3">
-                        <CalendarIcon className="h-5 w-5 text-gray-600" />
-                        <div>
-                            <div className="text-xl font-bold text-gray-600">∞</div>
-                            <div className="text-xs type-secondary">Unpaid (No Limit)</div>
-                        </div>
-                    </div>
-                </Card>
+                {leaveTypes.map((t, i) => {
+                    const balance = balances.find(b => b.leave_type_id === t.id)
+                    const colors = ["blue", "amber", "gray"] as const
+                    const color = {
+                        blue: { card: "bg-blue-500/10", text: "text-blue-600" },
+                        amber: { card: "bg-amber-500/10", text: "text-amber-600" },
+                        gray: { card: "bg-gray-500/10", text: "text-gray-600" },
+                    }[colors[i % colors.length]]
+                    const Icon = i % 2 === 0 ? CalendarIcon : FileText
+                    return (
+                        <Card key={t.id} className={cn("p-4 border-none", color.card)}>
+                            <div className="flex items-center gap-3">
+                                <Icon className={cn("h-5 w-5", color.text)} />
+                                <div>
+                                    <div className={cn("text-xl font-bold", color.text)}>
+                                        {balance ? balance.available : "∞"}
+                                    </div>
+                                    <div className="text-xs type-secondary">
+                                        {balance
+                                            ? `${t.name} Available${balance.pending_days > 0 ? ` (${balance.pending_days} pending)` : ""}`
+                                            : `${t.name} (No Limit)`}
+                                    </div>
+                                </div>
+                            </div>
+                        </Card>
+                    )
+                })}
             </div>
 
             {/* Requests Table */}
@@ -343,6 +446,7 @@ export default function LeaveRequest() {
                     <TabsTrigger value="pending">Pending</TabsTrigger>
                     <TabsTrigger value="approved">Approved</TabsTrigger>
                     <TabsTrigger value="rejected">Rejected</TabsTrigger>
+                    <TabsTrigger value="cancelled">Cancelled</TabsTrigger>
                 </TabsList>
                 <TabsContent value={activeTab} className="flex-1 mt-4">
                     <DataTable
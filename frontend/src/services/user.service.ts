@@ -1,10 +1,10 @@
 import { ENDPOINTS } from "@/api/endpoints";
 import { http } from "@/api/http";
-import type { User, UserPayload } from "@/types/user";
+import type { User, UserPage, UserPayload } from "@/types/user";
 
 export const userService = {
-  list(): Promise<User[]> {
-    return http<User[]>(ENDPOINTS.users.list());
+  list(params?: Record<string, string>): Promise<UserPage> {
+    return http<UserPage>(ENDPOINTS.users.list(params));
   },
 
   create(payload: UserPayload): Promise<User> {
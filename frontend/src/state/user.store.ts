@@ -20,8 +20,9 @@ export const useUserStore = create<UserState>((set, get) => ({
     if (get().loading) return;
     try {
       set({ loading: true });
-      const users = await userService.list();
-      set({ users, loading: false });
+      // Largest page the API serves; the table pages client-side
+      const page = await userService.list({ limit: "500" });
+      set({ users: page.items, loading: false });
     } catch {
       toast.error("Failed to load users");
       set({ loading: false });
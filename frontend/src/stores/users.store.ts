@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { api } from '@/lib/api';
-import type { User } from '@/types/user';
+import type { User, UserPage } from '@/types/user';
 import { toast } from 'sonner';
 
 interface UsersState {
@@ -19,8 +19,8 @@ export const useUsersStore = create<UsersState>((set) => ({
     fetchUsers: async () => {
         set({ isLoading: true, error: null });
         try {
-            const users = await api.get<User[]>('/api/users');
-            set({ users, isLoading: false });
+            const page = await api.get<UserPage>('/api/users?limit=500');
+            set({ users: page.items, isLoading: false });
         } catch (error) {
             set({ error: 'Failed to fetch users', isLoading: false });
             toast.error('Failed to fetch users');
@@ -0,0 +1,62 @@
+/** Weekend days and public holidays of one or more locations */
+export interface HolidayCalendar {
+    id: string;
+    name: string;
+    description?: string | null;
+    /** Matched case-insensitively against the user's location */
+    locations: string[];
+    /** ISO weekdays, 1 = Monday to 7 = Sunday */
+    weekend_days: number[];
+    /** Used by users whose location no calendar lists */
+    is_default: boolean;
+    holiday_count: number;
+    created_at: string;
+    updated_at: string;
+}
+
+export interface HolidayCalendarPayload {
+    name: string;
+    description?: string | null;
+    locations?: string[];
+    /** Defaults to [6, 7] */
+    weekend_days?: number[];
+    is_default?: boolean;
+}
+
+export interface Holiday {
+    id: string;
+    calendar_id: string;
+    /** "YYYY-MM-DD" */
+    holiday_date: string;
+    name: string;
+    /** UID of the iCalendar event it was imported from */
+    source_uid?: string | null;
+    created_at: string;
+}
+
+export interface HolidayImportReport {
+    dry_run: boolean;
+    committed: boolean;
+    events: number;
+    created: number;
+    updated: number;
+    unchanged: number;
+    removed: number;
+    holidays: { holiday_date: string; name: string; uid?: string | null }[];
+    skipped: { line: number; summary?: string | null; reason: string }[];
+}
+
+/** GET /api/leave-requests/working-days */
+export interface WorkingDays {
+    start_date: string;
+    end_date: string;
+    /** null when no calendar applies and only Saturday and Sunday are skipped */
+    calendar_id?: string | null;
+    calendar?: string | null;
+    calendar_days: number;
+    weekend_days: number;
+    /** Holidays in the range falling on working weekdays */
+    holidays: Holiday[];
+    /** What the request takes from a balance */
+    days: number;
+}
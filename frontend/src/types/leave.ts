@@ -0,0 +1,93 @@
+/** From /api/leave-types (what the caller may request) or /api/admin/leave-types */
+export interface LeaveType {
+    id: string;
+    name: string;
+    description?: string | null;
+    paid: boolean;
+    requires_attachment: boolean;
+    /** Days between submitting and the first day of leave */
+    min_notice_days: number;
+    max_consecutive_days?: number | null;
+    /** null: open to everyone */
+    eligible_role_ids?: string[] | null;
+    eligible_employment_types?: string[] | null;
+    active: boolean;
+    /** "none" keeps no balance */
+    accrual_method: AccrualMethod;
+    /** Yearly entitlement; set unless accrual_method is "none" */
+    annual_days?: number | null;
+    /** Most days carried into a new year; null: no limit */
+    carry_over_cap?: number | null;
+    /** Months into the new year before carried days expire; null: never */
+    carry_over_expiry_months?: number | null;
+    allow_negative: boolean;
+    /** "YYYY-MM-DD"; nothing accrues for earlier periods */
+    accrual_start: string;
+    created_at: string;
+    updated_at: string;
+}
+
+export type AccrualMethod = "none" | "monthly" | "annual";
+
+/** POST/PUT /api/admin/leave-types; a PUT replaces every setting */
+export interface LeaveTypePayload {
+    name: string;
+    description?: string;
+    paid: boolean;
+    requires_attachment?: boolean;
+    min_notice_days?: number;
+    max_consecutive_days?: number | null;
+    eligible_role_ids?: string[] | null;
+    eligible_employment_types?: string[] | null;
+    active?: boolean;
+    accrual_method?: AccrualMethod;
+    annual_days?: number | null;
+    carry_over_cap?: number | null;
+    carry_over_expiry_months?: number | null;
+    allow_negative?: boolean;
+    /** Kept on update when omitted */
+    accrual_start?: string;
+}
+
+/** Supporting document; submit its id as `attachment_id` with the request */
+export interface LeaveAttachment {
+    id: string;
+    user_id: string;
+    leave_request_id?: string | null;
+    file_name: string;
+    content_type: string;
+    size_bytes: number;
+    created_at: string;
+}
+
+/** GET /api/leave-balances, one per type that keeps a balance */
+export interface LeaveBalance {
+    leave_type_id: string;
+    leave_type: string;
+    accrual_method: AccrualMethod;
+    /** Ledger sum, including approved leave that hasn't started */
+    balance: number;
+    /** Days held by requests awaiting approval */
+    pending_days: number;
+    available: number;
+    allow_negative: boolean;
+    accrued_through?: string | null;
+}
+
+export type LedgerEntryType = "accrual" | "grant" | "debit" | "credit" | "forfeit" | "expiry" | "adjustment";
+
+export interface LeaveLedgerEntry {
+    id: string;
+    user_id: string;
+    leave_type_id: string;
+    leave_type: string;
+    entry_type: LedgerEntryType;
+    /** Positive adds to the balance */
+    days: number;
+    effective_date: string;
+    period?: string | null;
+    leave_request_id?: string | null;
+    note?: string | null;
+    created_by?: string | null;
+    created_at: string;
+}
@@ -1,18 +1,173 @@
 export interface User {
     id: string;
     username: string;
-    email: string;
-    password_hash?: string;
+    /** Omitted unless the caller is this user or may read private user fields */
+    email?: string;
     role_id?: string;
     role_name?: string;
+    display_name?: string | null;
+    status: UserStatus;
+    /** Custom attribute values by name; only on list and detail reads */
+    attributes?: Record<string, AttributeValue>;
+    /** Thumbnail URLs by size (small, medium, large); absent without an avatar */
+    avatar_urls?: Record<string, string>;
+    created_at: string;
+    updated_at: string;
+}
+
+/** Numbers for number attributes, "YYYY-MM-DD" for dates, strings otherwise */
+export type AttributeValue = string | number;
+
+export type AttributeDataType = 'string' | 'number' | 'date' | 'enum';
+
+/** Admin-defined user field, from /api/admin/user-attributes */
+export interface UserAttributeDefinition {
+    id: string;
+    name: string;
+    label: string;
+    data_type: AttributeDataType;
+    required: boolean;
+    max_length?: number | null;
+    min_value?: number | null;
+    max_value?: number | null;
+    options?: string[] | null;
     created_at: string;
     updated_at: string;
 }
 
+export type UserStatus = 'invited' | 'active' | 'suspended' | 'terminated';
+
+/** Entry of GET /api/users/{id}/status; future-dated entries are scheduled */
+export interface UserStatusChange {
+    id: string;
+    user_id: string;
+    status: UserStatus;
+    effective_at: string;
+    reason?: string;
+    changed_by?: string;
+    created_at: string;
+}
+
+/** Response envelope of GET /api/users */
+export interface UserPage {
+    items: User[];
+    total: number;
+    next_cursor?: string | null;
+}
+
+export interface EmergencyContact {
+    name: string;
+    relationship?: string | null;
+    phone: string;
+}
+
+/** GET /api/me/profile */
+export interface SelfProfile {
+    user_id: string;
+    username: string;
+    email: string;
+    display_name?: string | null;
+    phone?: string | null;
+    avatar_urls?: Record<string, string> | null;
+    emergency_contacts: EmergencyContact[];
+    /** Topic → enabled; missing topics are enabled */
+    notification_preferences: Record<string, boolean>;
+    /** Set while a new address waits for confirmation */
+    pending_email?: { new_email: string; expires_at: string; created_at: string } | null;
+    editable_fields: string[];
+}
+
+/** PATCH /api/me/profile; only the fields present change, "" clears text */
+export interface SelfProfileUpdate {
+    display_name?: string;
+    phone?: string;
+    emergency_contacts?: EmergencyContact[];
+    notification_preferences?: Record<string, boolean>;
+}
+
+export type EmploymentType = 'full_time' | 'part_time' | 'contractor' | 'intern';
+
+/** GET/PUT /api/users/{id}/profile; PUT replaces every field */
+export interface EmployeeProfile {
+    user_id: string;
+    department?: string | null;
+    location?: string | null;
+    job_title?: string | null;
+    manager_id?: string | null;
+    manager_name?: string | null;
+    hire_date?: string | null;
+    employment_type?: EmploymentType | null;
+    phone?: string | null;
+}
+
+export interface EmployeeProfileChange {
+    id: string;
+    user_id: string;
+    field: string;
+    old_value?: string | null;
+    new_value?: string | null;
+    changed_by?: string | null;
+    changed_at: string;
+}
+
+/** Entry of /api/users/{id}/reports and /chain; depth 1 is direct */
+export interface OrgMember {
+    id: string;
+    username: string;
+    job_title?: string | null;
+    department?: string | null;
+    manager_id?: string | null;
+    status: UserStatus;
+    depth: number;
+}
+
+export interface OrgChartNode {
+    id: string;
+    username: string;
+    job_title?: string | null;
+    department?: string | null;
+    status: UserStatus;
+    reports: OrgChartNode[];
+}
+
+/** Per-row outcome of POST /api/users/import */
+export interface ImportRowResult {
+    line: number;
+    username: string;
+    email: string;
+    invite: boolean;
+    errors: string[];
+}
+
+export interface UserImportReport {
+    dry_run: boolean;
+    committed: boolean;
+    total_rows: number;
+    invalid_rows: number;
+    rows: ImportRowResult[];
+}
+
 export interface UserPayload {
     username: string;
     email: string;
-    password_hash: string;
+}
+
+/** Entry of GET /api/users/{id}/invite; the token itself is only ever mailed */
+export interface UserInvite {
+    id: string;
+    user_id: string;
+    expires_at: string;
+    created_by?: string | null;
+    created_at: string;
+    accepted_at?: string | null;
+    revoked_at?: string | null;
+}
+
+/** GET /api/invites/{token}, shown on the accept page */
+export interface InviteDetails {
+    username: string;
+    email: string;
+    expires_at: string;
 }
 
 export interface Role {
@@ -0,0 +1,16 @@
+-- Migration: Policy test suites
+-- Each case asserts the decision a subject should get once the policy is active.
+CREATE TABLE IF NOT EXISTS policy_test_cases (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    policy_id UUID NOT NULL REFERENCES policies(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    subject_type VARCHAR(20) NOT NULL CHECK (subject_type IN ('role', 'user')),
+    subject_id UUID NOT NULL,
+    action VARCHAR(255) NOT NULL,
+    resource VARCHAR(255) NOT NULL,
+    context JSONB,
+    expected_effect VARCHAR(10) NOT NULL CHECK (expected_effect IN ('allow', 'deny')),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_policy_test_cases_policy ON policy_test_cases(policy_id);
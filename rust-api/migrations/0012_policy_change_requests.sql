@@ -0,0 +1,29 @@
+-- Migration: Four-eyes approval for policy changes
+-- Pending activations, archives and binding changes wait here until a second user approves them.
+CREATE TABLE IF NOT EXISTS policy_change_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    policy_id UUID NOT NULL REFERENCES policies(id) ON DELETE CASCADE,
+    change_type VARCHAR(20) NOT NULL CHECK (change_type IN ('activate', 'archive', 'bind', 'unbind')),
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected', 'failed')),
+    proposed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decision_comment TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    decided_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_policy_change_requests_status ON policy_change_requests(status);
+CREATE INDEX IF NOT EXISTS idx_policy_change_requests_policy ON policy_change_requests(policy_id);
+
+-- Every step taken on a change request, kept as decision history
+CREATE TABLE IF NOT EXISTS policy_change_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    change_request_id UUID NOT NULL REFERENCES policy_change_requests(id) ON DELETE CASCADE,
+    event VARCHAR(20) NOT NULL CHECK (event IN ('proposed', 'approved', 'rejected', 'applied', 'failed')),
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    comment TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_policy_change_events_request ON policy_change_events(change_request_id);
@@ -0,0 +1,42 @@
+-- Migration: Break-glass emergency access and audit trail
+
+-- Who may break glass, and which policy they receive while the window is open
+CREATE TABLE IF NOT EXISTS break_glass_grants (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    policy_id UUID NOT NULL REFERENCES policies(id) ON DELETE CASCADE,
+    max_duration_minutes INTEGER NOT NULL DEFAULT 60 CHECK (max_duration_minutes > 0),
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_break_glass_grants_user ON break_glass_grants(user_id);
+
+CREATE TABLE IF NOT EXISTS break_glass_activations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    grant_id UUID NOT NULL REFERENCES break_glass_grants(id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    justification TEXT NOT NULL CHECK (length(trim(justification)) > 0),
+    activated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    ended_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_break_glass_activations_user ON break_glass_activations(user_id, expires_at);
+
+-- Audit trail of authorization decisions made for protected actions
+CREATE TABLE IF NOT EXISTS audit_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(255) NOT NULL,
+    resource VARCHAR(255) NOT NULL,
+    allowed BOOLEAN NOT NULL,
+    reason TEXT NOT NULL,
+    policy_id UUID,
+    break_glass_activation_id UUID REFERENCES break_glass_activations(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_user ON audit_log(user_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_audit_log_break_glass ON audit_log(break_glass_activation_id) WHERE break_glass_activation_id IS NOT NULL;
@@ -0,0 +1,22 @@
+-- Migration: Temporary delegation of permissions between users
+-- One row per delegated (action, resource) pair, valid for an inclusive date range.
+CREATE TABLE IF NOT EXISTS permission_delegations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    delegator_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    delegate_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    action VARCHAR(255) NOT NULL,
+    resource VARCHAR(255) NOT NULL,
+    starts_on DATE NOT NULL,
+    ends_on DATE NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP,
+    CHECK (ends_on >= starts_on),
+    CHECK (delegator_id <> delegate_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_permission_delegations_delegate ON permission_delegations(delegate_id, starts_on, ends_on);
+CREATE INDEX IF NOT EXISTS idx_permission_delegations_delegator ON permission_delegations(delegator_id);
+
+-- Record who an action was taken on behalf of
+ALTER TABLE leave_requests ADD COLUMN IF NOT EXISTS approved_on_behalf_of UUID REFERENCES users(id) ON DELETE SET NULL;
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS on_behalf_of UUID REFERENCES users(id) ON DELETE SET NULL;
@@ -0,0 +1,8 @@
+-- Migration: Attributes used by rule conditions when filtering lists
+-- A rule such as {"owner": "reports"} or {"department": "mine"} is turned into a
+-- SQL predicate over the owner of each row.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS department VARCHAR(100);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS manager_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_users_department ON users(department);
+CREATE INDEX IF NOT EXISTS idx_users_manager_id ON users(manager_id);
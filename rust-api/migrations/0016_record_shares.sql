@@ -0,0 +1,17 @@
+-- Migration: Per-record sharing grants
+-- Lets the owner of a single record (or an admin) give another user access to it
+-- without a policy change. Grants can expire and be revoked.
+CREATE TABLE IF NOT EXISTS record_shares (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    resource VARCHAR(255) NOT NULL,
+    resource_id UUID NOT NULL,
+    grantee_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    action VARCHAR(255) NOT NULL,
+    expires_at TIMESTAMP,
+    granted_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_record_shares_record ON record_shares(resource, resource_id);
+CREATE INDEX IF NOT EXISTS idx_record_shares_grantee ON record_shares(grantee_id);
@@ -0,0 +1,37 @@
+-- Migration: Access review campaigns
+-- A campaign snapshots policy bindings into review items. Each item is assigned to
+-- a reviewer who decides to keep or revoke it; revocations are applied on close.
+CREATE TABLE IF NOT EXISTS access_review_campaigns (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    policy_id UUID REFERENCES policies(id) ON DELETE SET NULL,
+    role_id UUID REFERENCES roles(id) ON DELETE SET NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'closed')),
+    due_on DATE NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    closed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    closed_at TIMESTAMP
+);
+
+-- Policy and subject names are copied so the evidence survives later deletions.
+CREATE TABLE IF NOT EXISTS access_review_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    campaign_id UUID NOT NULL REFERENCES access_review_campaigns(id) ON DELETE CASCADE,
+    binding_id UUID NOT NULL,
+    policy_id UUID NOT NULL,
+    policy_name VARCHAR(255) NOT NULL,
+    subject_type VARCHAR(20) NOT NULL,
+    subject_id UUID NOT NULL,
+    subject_name VARCHAR(255) NOT NULL,
+    reviewer_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    decision VARCHAR(20) CHECK (decision IN ('keep', 'revoke')),
+    comment TEXT,
+    decided_at TIMESTAMP,
+    applied_at TIMESTAMP,
+    apply_error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_access_review_items_campaign ON access_review_items(campaign_id);
+CREATE INDEX IF NOT EXISTS idx_access_review_items_reviewer ON access_review_items(reviewer_id);
@@ -0,0 +1,31 @@
+-- Migration: User status with effective dates
+-- Users are no longer removed when they leave: their status moves to suspended or
+-- terminated, which keeps leave and payroll history. Changes may be dated in the
+-- past or the future; the effective status is the latest change already in force.
+CREATE TABLE IF NOT EXISTS user_status_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL CHECK (status IN ('active', 'suspended', 'terminated')),
+    effective_at TIMESTAMP NOT NULL,
+    reason TEXT,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_status_changes_user ON user_status_changes(user_id, effective_at DESC);
+
+-- Effective status right now. Users without any change in force are active.
+CREATE OR REPLACE FUNCTION user_status(p_user_id UUID)
+RETURNS VARCHAR(20)
+LANGUAGE SQL STABLE
+AS $$
+    SELECT COALESCE(
+        (
+            SELECT status FROM user_status_changes
+            WHERE user_id = p_user_id AND effective_at <= CURRENT_TIMESTAMP
+            ORDER BY effective_at DESC, created_at DESC
+            LIMIT 1
+        ),
+        'active'
+    )
+$$;
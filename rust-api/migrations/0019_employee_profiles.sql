@@ -0,0 +1,23 @@
+-- Migration: Employee profile
+-- Department and manager were added in 0015; the rest of the profile lives next to
+-- them on users. Every change to a profile field is kept in the history table.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS location VARCHAR(100);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS job_title VARCHAR(100);
+ALTER TABLE users ADD COLUMN IF NOT EXISTS hire_date DATE;
+ALTER TABLE users ADD COLUMN IF NOT EXISTS employment_type VARCHAR(20)
+    CHECK (employment_type IN ('full_time', 'part_time', 'contractor', 'intern'));
+ALTER TABLE users ADD COLUMN IF NOT EXISTS phone VARCHAR(30);
+
+CREATE INDEX IF NOT EXISTS idx_users_location ON users(location);
+
+CREATE TABLE IF NOT EXISTS employee_profile_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    field VARCHAR(50) NOT NULL,
+    old_value TEXT,
+    new_value TEXT,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_employee_profile_changes_user ON employee_profile_changes(user_id, changed_at DESC);
@@ -0,0 +1,28 @@
+-- Migration: Invitation-based onboarding
+-- New users start out 'invited' and activate themselves by setting a password
+-- through a single-use, expiring invite. Mail goes through a pluggable mailer;
+-- the default one only writes to the local outbox table.
+ALTER TABLE user_status_changes DROP CONSTRAINT IF EXISTS user_status_changes_status_check;
+ALTER TABLE user_status_changes ADD CONSTRAINT user_status_changes_status_check
+    CHECK (status IN ('invited', 'active', 'suspended', 'terminated'));
+
+CREATE TABLE IF NOT EXISTS user_invites (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token VARCHAR(255) NOT NULL UNIQUE,
+    expires_at TIMESTAMP NOT NULL,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    accepted_at TIMESTAMP,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_invites_user ON user_invites(user_id);
+
+CREATE TABLE IF NOT EXISTS mail_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    recipient VARCHAR(255) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    body TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,27 @@
+-- Migration: Custom user attributes
+-- Admins define extra user fields at runtime. Values live in users.attributes as
+-- a JSON object keyed by attribute name and are validated by the API against
+-- the definition before they are written.
+CREATE TABLE IF NOT EXISTS user_attribute_definitions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(40) NOT NULL UNIQUE,
+    label VARCHAR(100) NOT NULL,
+    data_type VARCHAR(10) NOT NULL CHECK (data_type IN ('string', 'number', 'date', 'enum')),
+    required BOOLEAN NOT NULL DEFAULT FALSE,
+    -- string: longest allowed value
+    max_length INTEGER CHECK (max_length > 0),
+    -- number: inclusive bounds
+    min_value DOUBLE PRECISION,
+    max_value DOUBLE PRECISION,
+    -- enum: allowed values
+    options TEXT[],
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CHECK ((data_type = 'enum') = (options IS NOT NULL)),
+    CHECK (data_type = 'string' OR max_length IS NULL),
+    CHECK (data_type = 'number' OR (min_value IS NULL AND max_value IS NULL))
+);
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS attributes JSONB NOT NULL DEFAULT '{}';
+
+CREATE INDEX IF NOT EXISTS idx_users_attributes ON users USING GIN (attributes jsonb_path_ops);
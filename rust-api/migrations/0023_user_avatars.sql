@@ -0,0 +1,4 @@
+-- Migration: User avatars
+-- Images live in the storage backend under avatars/<user id>/<version>/; the
+-- version changes on every upload so avatar URLs can be cached indefinitely.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS avatar_version VARCHAR(32);
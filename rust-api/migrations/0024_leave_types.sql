@@ -0,0 +1,56 @@
+-- Migration: Configurable leave types
+-- Replaces the hard-coded leave_type enum with a table admins manage through
+-- /api/admin/leave-types. Each type carries the rules checked when a request
+-- is submitted.
+CREATE TABLE IF NOT EXISTS leave_types (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- What requests submit and display, e.g. 'Casual'
+    name VARCHAR(50) NOT NULL UNIQUE,
+    description TEXT,
+    paid BOOLEAN NOT NULL DEFAULT TRUE,
+    requires_attachment BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Days between submitting and the first day of leave
+    min_notice_days INTEGER NOT NULL DEFAULT 0 CHECK (min_notice_days >= 0),
+    -- NULL: no limit
+    max_consecutive_days INTEGER CHECK (max_consecutive_days > 0),
+    -- NULL: every role / employment type may request it
+    eligible_role_ids UUID[],
+    eligible_employment_types TEXT[],
+    -- Inactive types can't be requested but stay on existing requests
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO leave_types (name, paid) VALUES
+    ('Casual', TRUE),
+    ('Sick', TRUE),
+    ('Unpaid', FALSE)
+ON CONFLICT (name) DO NOTHING;
+
+-- Point existing requests at the seeded types, then drop the enum
+ALTER TABLE leave_requests ADD COLUMN IF NOT EXISTS leave_type_id UUID REFERENCES leave_types(id) ON DELETE RESTRICT;
+
+UPDATE leave_requests l SET leave_type_id = t.id
+FROM leave_types t
+WHERE l.leave_type_id IS NULL AND t.name = l.leave_type::TEXT;
+
+ALTER TABLE leave_requests ALTER COLUMN leave_type_id SET NOT NULL;
+ALTER TABLE leave_requests DROP COLUMN IF EXISTS leave_type;
+DROP TYPE IF EXISTS leave_type;
+
+CREATE INDEX IF NOT EXISTS idx_leave_requests_type ON leave_requests(leave_type_id);
+
+-- Supporting documents (e.g. a medical certificate), uploaded before the
+-- request is submitted and linked to it on submission
+CREATE TABLE IF NOT EXISTS leave_attachments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    leave_request_id UUID UNIQUE REFERENCES leave_requests(id) ON DELETE CASCADE,
+    file_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_leave_attachments_user ON leave_attachments(user_id);
@@ -0,0 +1,59 @@
+-- Migration: Leave balances and accruals
+-- Types with an accrual method other than 'none' keep a balance per user. Every
+-- movement is a row in leave_ledger and the balance is their sum; nothing is
+-- ever updated in place. Accruals are posted lazily, up to the current day,
+-- whenever a balance is read or changed, and leave_balances records how far
+-- that has got.
+ALTER TABLE leave_types ADD COLUMN IF NOT EXISTS accrual_method VARCHAR(10) NOT NULL DEFAULT 'none'
+    CHECK (accrual_method IN ('none', 'monthly', 'annual'));
+-- Yearly entitlement: granted on 1 January ('annual') or a twelfth on the 1st of each month ('monthly')
+ALTER TABLE leave_types ADD COLUMN IF NOT EXISTS annual_days NUMERIC(5,2) CHECK (annual_days >= 0);
+-- Most days kept at year end (NULL: no limit), and months into the new year before carried days expire (NULL: never)
+ALTER TABLE leave_types ADD COLUMN IF NOT EXISTS carry_over_cap NUMERIC(5,2) CHECK (carry_over_cap >= 0);
+ALTER TABLE leave_types ADD COLUMN IF NOT EXISTS carry_over_expiry_months INTEGER CHECK (carry_over_expiry_months BETWEEN 1 AND 12);
+ALTER TABLE leave_types ADD COLUMN IF NOT EXISTS allow_negative BOOLEAN NOT NULL DEFAULT FALSE;
+-- No accrual is posted for periods before this date
+ALTER TABLE leave_types ADD COLUMN IF NOT EXISTS accrual_start DATE NOT NULL DEFAULT date_trunc('year', CURRENT_DATE)::DATE;
+ALTER TABLE leave_types ADD CONSTRAINT leave_types_accrual_days CHECK ((accrual_method = 'none') = (annual_days IS NULL));
+
+-- Approved requests cancelled by their owner before they start
+ALTER TYPE leave_status ADD VALUE IF NOT EXISTS 'Cancelled';
+
+-- Working days (Monday to Friday) a request takes from the balance
+ALTER TABLE leave_requests ADD COLUMN IF NOT EXISTS days NUMERIC(6,2);
+UPDATE leave_requests SET days = (
+    SELECT COUNT(*) FROM generate_series(start_date, end_date, INTERVAL '1 day') d
+    WHERE EXTRACT(ISODOW FROM d) < 6
+)
+WHERE days IS NULL;
+ALTER TABLE leave_requests ALTER COLUMN days SET NOT NULL;
+
+CREATE TABLE IF NOT EXISTS leave_ledger (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    leave_type_id UUID NOT NULL REFERENCES leave_types(id) ON DELETE RESTRICT,
+    entry_type VARCHAR(20) NOT NULL
+        CHECK (entry_type IN ('accrual', 'grant', 'debit', 'credit', 'forfeit', 'expiry', 'adjustment')),
+    -- Positive adds to the balance, negative takes from it
+    days NUMERIC(7,2) NOT NULL,
+    effective_date DATE NOT NULL,
+    -- Accrual period ('2026' or '2026-03') of engine-posted entries
+    period VARCHAR(7),
+    leave_request_id UUID REFERENCES leave_requests(id) ON DELETE SET NULL,
+    note TEXT,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_leave_ledger_balance ON leave_ledger(user_id, leave_type_id, effective_date);
+-- An accrual period is posted at most once
+CREATE UNIQUE INDEX IF NOT EXISTS idx_leave_ledger_period ON leave_ledger(user_id, leave_type_id, entry_type, period)
+    WHERE period IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS leave_balances (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    leave_type_id UUID NOT NULL REFERENCES leave_types(id) ON DELETE CASCADE,
+    -- Last day accruals have been posted for
+    accrued_through DATE,
+    PRIMARY KEY (user_id, leave_type_id)
+);
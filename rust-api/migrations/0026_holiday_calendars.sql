@@ -0,0 +1,35 @@
+-- Migration: Holiday calendars
+-- A request's day count skips the weekend days and public holidays of the
+-- requester's calendar: the one listing their location, or else the default
+-- one. The seeded default has a Saturday/Sunday weekend and no holidays, which
+-- is how days were counted before, so existing requests keep their counts.
+CREATE TABLE IF NOT EXISTS holiday_calendars (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL UNIQUE,
+    description TEXT,
+    -- Matched case-insensitively against users.location; a location belongs to one calendar at most
+    locations TEXT[] NOT NULL DEFAULT '{}',
+    -- ISO weekdays, 1 = Monday to 7 = Sunday
+    weekend_days INTEGER[] NOT NULL DEFAULT '{6,7}',
+    -- Used by users whose location no calendar lists
+    is_default BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_holiday_calendars_default ON holiday_calendars(is_default) WHERE is_default;
+
+INSERT INTO holiday_calendars (name, description, is_default) VALUES
+    ('Default', 'Used for every location without a calendar of its own', TRUE)
+ON CONFLICT (name) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS holidays (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    calendar_id UUID NOT NULL REFERENCES holiday_calendars(id) ON DELETE CASCADE,
+    holiday_date DATE NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    -- UID of the iCalendar event it was imported from; NULL when entered by hand
+    source_uid TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (calendar_id, holiday_date)
+);
@@ -0,0 +1,12 @@
+-- Migration: Policy deletion through four-eyes approval
+-- Deleting a policy can now wait for approval like archiving it. A change
+-- request outlives the policy it targeted so that its decision history stays;
+-- the payload still names the deleted policy.
+ALTER TABLE policy_change_requests DROP CONSTRAINT IF EXISTS policy_change_requests_change_type_check;
+ALTER TABLE policy_change_requests ADD CONSTRAINT policy_change_requests_change_type_check
+    CHECK (change_type IN ('activate', 'archive', 'delete', 'bind', 'unbind'));
+
+ALTER TABLE policy_change_requests ALTER COLUMN policy_id DROP NOT NULL;
+ALTER TABLE policy_change_requests DROP CONSTRAINT IF EXISTS policy_change_requests_policy_id_fkey;
+ALTER TABLE policy_change_requests ADD CONSTRAINT policy_change_requests_policy_id_fkey
+    FOREIGN KEY (policy_id) REFERENCES policies(id) ON DELETE SET NULL;
@@ -0,0 +1,7 @@
+-- Migration: Read-only record shares
+-- Shares now only give read access to reports. Revoke the ones granting
+-- anything else so they stop showing up as active.
+UPDATE record_shares
+SET revoked_at = CURRENT_TIMESTAMP
+WHERE revoked_at IS NULL
+  AND (resource <> 'report' OR action <> 'read');
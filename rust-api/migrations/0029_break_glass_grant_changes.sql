@@ -0,0 +1,5 @@
+-- Migration: Break-glass grants through four-eyes approval
+-- With approval on, creating a grant becomes a change request like a binding.
+ALTER TABLE policy_change_requests DROP CONSTRAINT IF EXISTS policy_change_requests_change_type_check;
+ALTER TABLE policy_change_requests ADD CONSTRAINT policy_change_requests_change_type_check
+    CHECK (change_type IN ('activate', 'archive', 'delete', 'bind', 'unbind', 'grant_break_glass'));
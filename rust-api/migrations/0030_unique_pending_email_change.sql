@@ -0,0 +1,16 @@
+-- Migration: One pending email change per address
+-- Two accounts could otherwise both hold a confirmation link for the same new
+-- address. Keep the latest open request for each address and cancel the rest.
+UPDATE email_change_requests r
+SET cancelled_at = CURRENT_TIMESTAMP
+WHERE r.confirmed_at IS NULL AND r.cancelled_at IS NULL
+AND EXISTS (
+    SELECT 1 FROM email_change_requests newer
+    WHERE LOWER(newer.new_email) = LOWER(r.new_email)
+    AND newer.confirmed_at IS NULL AND newer.cancelled_at IS NULL
+    AND (newer.created_at, newer.id) > (r.created_at, r.id)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_email_change_requests_pending_email
+    ON email_change_requests (LOWER(new_email))
+    WHERE confirmed_at IS NULL AND cancelled_at IS NULL;
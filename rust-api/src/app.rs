@@ -1,4 +1,4 @@
-use axum::{Router,routing::get, http::{Method, HeaderValue}};
+use axum::{Router, http::{Method, HeaderValue}};
 use tower_http::{
     services::{ServeDir, ServeFile},
     cors::{CorsLayer, Any},
@@ -6,24 +6,49 @@ use tower_http::{
 
 
 use crate::{
-    routes::user_routes,
+    routes::{route_table::RouteTable, user_routes},
     state::app_state::AppState,
     handlers::ws_notifications::ws_notifications,
+    utils::permissions::{self, SESSION},
 };
 
-pub fn create_app(state: AppState) -> Router {
-    let api_router = Router::new()
+/// Every route the app serves, with what each requires of the caller.
+pub fn route_table() -> RouteTable {
+    let api_routes = RouteTable::new()
         .nest("/users", user_routes::routes())
         .nest("/auth", crate::routes::auth_routes::routes())
+        .nest("/me", crate::routes::me_routes::routes())
+        .route(Method::GET, "/avatars/{user_id}/{version}/{file}", SESSION, crate::handlers::avatar_handler::get_avatar)
         .nest("/management", crate::routes::policy_routes::routes())
+        .nest("/break-glass", crate::routes::break_glass_routes::routes())
+        .nest("/delegations", crate::routes::delegation_routes::routes())
+        .nest("/records", crate::routes::record_share_routes::routes())
+        .nest("/access-reviews", crate::routes::access_review_routes::routes())
+        .nest("/org", crate::routes::org_routes::routes())
+        .nest("/invites", crate::routes::invite_routes::routes())
+        .route(Method::GET, "/mail/outbox", permissions::READ_MAIL_OUTBOX, crate::handlers::invite_handler::list_outbox)
         .nest("/notifications", crate::routes::notification_routes::routes())
         // Employee self-service routes
         .nest("/leave-requests", crate::routes::leave_routes::routes())
+        .route(Method::GET, "/leave-types", SESSION, crate::handlers::leave_type_handler::list_requestable_types)
+        .nest("/leave-balances", crate::routes::leave_balance_routes::routes())
+        .route(Method::GET, "/holiday-calendars", SESSION, crate::handlers::holiday_handler::list_calendars)
+        .route(Method::GET, "/holiday-calendars/{id}/holidays", SESSION, crate::handlers::holiday_handler::list_holidays)
         .nest("/reports", crate::routes::report_routes::routes())
         .nest("/payslips", crate::routes::payslip_routes::routes())
         // Admin routes (payslip templates)
-        .nest("/admin/payslip-templates", crate::routes::template_routes::routes());
+        .nest("/admin/payslip-templates", crate::routes::template_routes::routes())
+        .nest("/admin/user-attributes", crate::routes::user_attribute_routes::routes())
+        .nest("/admin/leave-types", crate::routes::leave_type_routes::routes())
+        .route(Method::POST, "/admin/leave-accruals/run", permissions::UPDATE_LEAVE_BALANCE, crate::handlers::leave_balance_handler::run_accruals)
+        .nest("/admin/holiday-calendars", crate::routes::holiday_routes::routes());
+
+    RouteTable::new()
+        .route(Method::GET, "/ws/notifications", SESSION, ws_notifications)
+        .nest("/api", api_routes)
+}
 
+pub fn create_app(state: AppState) -> Router {
     // CORS configuration
     let cors = if let Ok(origins_str) = std::env::var("CORS_ALLOWED_ORIGINS") {
         let origins: Vec<HeaderValue> = origins_str
@@ -49,10 +74,10 @@ pub fn create_app(state: AppState) -> Router {
     let static_service = ServeDir::new(&frontend_dir)
         .not_found_service(ServeFile::new(frontend_dir.join("index.html")));
 
-    Router::new()
-         .route("/ws/notifications", get(ws_notifications))
-        .nest("/api", api_router)
+    route_table()
+        .into_router()
         .fallback_service(static_service)
+        .layer(axum::middleware::from_fn(crate::services::attribute_service::record_peer_addr))
         .layer(cors)
         .with_state(state)
 }
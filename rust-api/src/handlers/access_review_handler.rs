@@ -0,0 +1,257 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    models::access_review::{
+        AccessReviewCampaign, AccessReviewCampaignWithItems, AccessReviewDecisionPayload, AccessReviewItem,
+        CreateAccessReviewPayload, EvidenceQuery, ReassignReviewerPayload,
+    },
+    services::{access_review_service, notification_service},
+    state::app_state::AppState,
+    utils::auth::{authorize_action, current_user},
+    utils::permissions,
+};
+
+async fn notify(state: &AppState, message: &str, user_id: Uuid) {
+    let _ = notification_service::create_notification(
+        &state.db,
+        &state.notifications,
+        "access_review",
+        message,
+        Some(user_id),
+    ).await;
+}
+
+fn map_review_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Access review rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            eprintln!("Access review error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+async fn load_with_items(state: &AppState, id: Uuid) -> Result<AccessReviewCampaignWithItems, StatusCode> {
+    let campaign = access_review_service::get_campaign(&state.db, id)
+        .await
+        .map_err(map_review_error)?;
+    let items = access_review_service::list_items(&state.db, id)
+        .await
+        .map_err(map_review_error)?;
+
+    Ok(AccessReviewCampaignWithItems {
+        progress: access_review_service::progress(&items),
+        campaign,
+        items,
+    })
+}
+
+/// Sends each reviewer with undecided items a notification. Returns how many were notified.
+async fn remind_reviewers(state: &AppState, campaign: &AccessReviewCampaign) -> Result<usize, StatusCode> {
+    let reviewers = access_review_service::pending_reviewers(&state.db, campaign.id)
+        .await
+        .map_err(map_review_error)?;
+
+    for (reviewer_id, pending) in &reviewers {
+        let msg = format!(
+            "Access review '{}': {} binding(s) awaiting your decision, due {}",
+            campaign.name, pending, campaign.due_on
+        );
+        notify(state, &msg, *reviewer_id).await;
+    }
+    Ok(reviewers.len())
+}
+
+pub async fn create_campaign(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateAccessReviewPayload>,
+) -> Result<(StatusCode, Json<AccessReviewCampaignWithItems>), StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::CREATE_ACCESS_REVIEW).await?;
+
+    let campaign = access_review_service::create_campaign(&state.db, &payload, user.id)
+        .await
+        .map_err(map_review_error)?;
+
+    // NOTIFICATION: Assignment doubles as the first reminder
+    remind_reviewers(&state, &campaign).await?;
+
+    Ok((StatusCode::CREATED, Json(load_with_items(&state, campaign.id).await?)))
+}
+
+pub async fn list_campaigns(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AccessReviewCampaign>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_ACCESS_REVIEW).await?;
+
+    let campaigns = access_review_service::list_campaigns(&state.db)
+        .await
+        .map_err(map_review_error)?;
+    Ok(Json(campaigns))
+}
+
+pub async fn get_campaign(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccessReviewCampaignWithItems>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_ACCESS_REVIEW).await?;
+    Ok(Json(load_with_items(&state, id).await?))
+}
+
+/// Items waiting for the signed-in reviewer.
+pub async fn list_my_items(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<AccessReviewItem>>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let items = access_review_service::list_pending_for_reviewer(&state.db, user.id)
+        .await
+        .map_err(map_review_error)?;
+    Ok(Json(items))
+}
+
+pub async fn decide_item(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AccessReviewDecisionPayload>,
+) -> Result<Json<AccessReviewItem>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let item = access_review_service::get_item(&state.db, id)
+        .await
+        .map_err(map_review_error)?;
+
+    if item.reviewer_id != Some(user.id) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    // Nobody certifies their own access; an admin has to reassign the item
+    if item.subject_type == "user" && item.subject_id == user.id {
+        eprintln!("Access review: {} tried to review their own binding {}", user.username, item.binding_id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let updated = access_review_service::decide(&state.db, id, user.id, &payload.decision, payload.comment.as_deref())
+        .await
+        .map_err(map_review_error)?;
+    if !updated {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let item = access_review_service::get_item(&state.db, id)
+        .await
+        .map_err(map_review_error)?;
+    Ok(Json(item))
+}
+
+pub async fn reassign_item(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReassignReviewerPayload>,
+) -> Result<Json<AccessReviewItem>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_ACCESS_REVIEW).await?;
+
+    let updated = access_review_service::reassign(&state.db, id, payload.reviewer_id)
+        .await
+        .map_err(map_review_error)?;
+    if !updated {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let item = access_review_service::get_item(&state.db, id)
+        .await
+        .map_err(map_review_error)?;
+    notify(&state, &format!("An access review item for {} was assigned to you", item.subject_name), payload.reviewer_id).await;
+
+    Ok(Json(item))
+}
+
+pub async fn remind(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_ACCESS_REVIEW).await?;
+
+    let campaign = access_review_service::get_campaign(&state.db, id)
+        .await
+        .map_err(map_review_error)?;
+    if campaign.status != "open" {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let reminded = remind_reviewers(&state, &campaign).await?;
+    Ok(Json(json!({ "reminded": reminded })))
+}
+
+/// Closes the campaign and applies every revoke decision.
+pub async fn close_campaign(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AccessReviewCampaignWithItems>, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::UPDATE_ACCESS_REVIEW).await?;
+
+    let closed = access_review_service::close_campaign(&state.db, id, user.id)
+        .await
+        .map_err(map_review_error)?;
+    if closed.is_none() {
+        // Either unknown or already closed
+        access_review_service::get_campaign(&state.db, id)
+            .await
+            .map_err(map_review_error)?;
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let result = load_with_items(&state, id).await?;
+    let msg = format!(
+        "{} closed access review '{}': {} kept, {} revoked, {} undecided",
+        user.username, result.campaign.name, result.progress.kept, result.progress.revoked, result.progress.pending
+    );
+    notify(&state, &msg, user.id).await;
+
+    Ok(Json(result))
+}
+
+/// Evidence for auditors, as JSON or as a CSV download.
+pub async fn export_evidence(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<EvidenceQuery>,
+) -> Result<Response, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_ACCESS_REVIEW).await?;
+
+    let result = load_with_items(&state, id).await?;
+
+    match query.format.as_deref().unwrap_or("json") {
+        "json" => Ok(Json(result).into_response()),
+        "csv" => {
+            let body = access_review_service::evidence_csv(&result.campaign, &result.items);
+            let disposition = format!("attachment; filename=\"access-review-{}.csv\"", id);
+            Ok((
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                body,
+            ).into_response())
+        }
+        _ => Err(StatusCode::BAD_REQUEST),
+    }
+}
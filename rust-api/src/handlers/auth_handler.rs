@@ -1,3 +1,5 @@
+use std::collections::hash_map::{Entry, HashMap};
+
 use axum::{
     extract::State,
     http::{StatusCode, HeaderMap, header},
@@ -5,14 +7,22 @@ use axum::{
 };
 use bcrypt::verify;
 use serde_json::json;
+use uuid::Uuid;
 
 use crate::{
-    models::user::{LoginPayload, User},
+    models::user::LoginPayload,
+    models::user_response::{FieldVisibility, UserResponse},
+    models::user_role::{AuthContext, PermissionCheckPayload, PermissionCheckResult},
+    services::attribute_service::ResourceRef,
     services::auth_service,
     services::user_service,
     state::app_state::AppState,
+    utils::auth::{build_context, current_user, record_context},
 };
 
+/// Upper bound on checks per batch request
+const MAX_PERMISSION_CHECKS: usize = 100;
+
 pub async fn login(
     State(state): State<AppState>,
     Json(payload): Json<LoginPayload>,
@@ -26,6 +36,11 @@ pub async fn login(
         return Err(StatusCode::UNAUTHORIZED);
     }
 
+    if user.status != "active" {
+        eprintln!("Login rejected: user {} is {}", user.username, user.status);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
     let session = auth_service::create_session(&state.db, user.id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -34,6 +49,7 @@ pub async fn login(
     let cookie = format!("session_token={}; Path=/; HttpOnly; SameSite=Lax; Max-Age=604800", session.token);
     headers.insert(header::SET_COOKIE, cookie.parse().unwrap());
 
+    let user = UserResponse::from_user(user, &FieldVisibility::own(session.user_id));
     Ok((headers, Json(json!({ "message": "Login successful", "user": user }))))
 }
 
@@ -61,7 +77,7 @@ pub async fn logout(
 pub async fn me(
     headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<crate::models::user::UserWithRole>, StatusCode> {
+) -> Result<Json<UserResponse>, StatusCode> {
     let cookie_header = headers.get(header::COOKIE)
         .ok_or(StatusCode::UNAUTHORIZED)?;
     
@@ -80,5 +96,64 @@ pub async fn me(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(Json(user))
+    Ok(Json(UserResponse::from_user_with_role(user, &FieldVisibility::own(session.user_id))))
+}
+
+/// Evaluates a batch of (action, resource) checks for the current user so the UI
+/// can decide which controls to show. The rules and the request context are
+/// loaded once for the whole batch, and each record's attributes once however
+/// many checks name it.
+pub async fn check_permissions(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<PermissionCheckPayload>,
+) -> Result<Json<Vec<PermissionCheckResult>>, StatusCode> {
+    if payload.checks.len() > MAX_PERMISSION_CHECKS {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let user = current_user(&state, &headers).await?;
+    let context = build_context(&state, &headers, &user, None).await?;
+
+    let rules = auth_service::fetch_rules(&state.db, Some(user.id), user.role_id, None)
+        .await
+        .map_err(|e| {
+            eprintln!("Authorization engine error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    let mut record_contexts: HashMap<(String, Uuid), AuthContext> = HashMap::new();
+    let mut results = Vec::with_capacity(payload.checks.len());
+    for check in payload.checks {
+        // Checks naming a record are decided as `authorize_record` would, shares included
+        let decision = match check.resource_id {
+            Some(id) => {
+                let record_context = match record_contexts.entry((check.resource.clone(), id)) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        let target = ResourceRef { resource: &check.resource, id };
+                        entry.insert(record_context(&state, &headers, &user, &context, target).await?)
+                    }
+                };
+                auth_service::authorize_record_with_rules(
+                    &state.db, &user, &rules, &check.action, &check.resource, id, record_context,
+                )
+                .await
+                .map_err(|e| {
+                    eprintln!("Authorization engine error: {:?}", e);
+                    StatusCode::INTERNAL_SERVER_ERROR
+                })?
+            }
+            None => auth_service::authorize_with_rules(&user, &rules, &check.action, &check.resource, &context),
+        };
+        results.push(PermissionCheckResult {
+            action: check.action,
+            resource: check.resource,
+            resource_id: check.resource_id,
+            allowed: decision.allowed,
+            reason: decision.reason,
+        });
+    }
+
+    Ok(Json(results))
 }
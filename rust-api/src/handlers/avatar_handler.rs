@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{header, StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::avatar::avatar_urls,
+    services::avatar_service::{self, AvatarError, MAX_AVATAR_BYTES},
+    state::app_state::AppState,
+    utils::auth::{authorize_action, current_user},
+    utils::permissions,
+};
+
+fn map_avatar_error(e: AvatarError) -> StatusCode {
+    match e {
+        AvatarError::UnsupportedFormat => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        AvatarError::Invalid(msg) => {
+            eprintln!("Avatar rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        AvatarError::Storage(msg) => {
+            eprintln!("Avatar storage error: {}", msg);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+        AvatarError::Database(sqlx::Error::RowNotFound) => StatusCode::NOT_FOUND,
+        AvatarError::Database(e) => {
+            eprintln!("Avatar database error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// Bytes of the multipart field named `file`, refusing anything over the size limit.
+async fn read_upload(mut multipart: Multipart) -> Result<Vec<u8>, StatusCode> {
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| e.status())? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|e| e.status())? {
+            if bytes.len() + chunk.len() > MAX_AVATAR_BYTES {
+                return Err(StatusCode::PAYLOAD_TOO_LARGE);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+        return Ok(bytes);
+    }
+    Err(StatusCode::BAD_REQUEST)
+}
+
+/// Renders and stores the thumbnails. Returns the new avatar URLs.
+async fn upload(state: &AppState, user_id: Uuid, multipart: Multipart) -> Result<BTreeMap<String, String>, StatusCode> {
+    let bytes = read_upload(multipart).await?;
+
+    let thumbnails = tokio::task::spawn_blocking(move || avatar_service::render_thumbnails(&bytes))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(map_avatar_error)?;
+
+    let version = avatar_service::replace_avatar(&state.db, state.storage.as_ref(), user_id, &thumbnails)
+        .await
+        .map_err(map_avatar_error)?;
+
+    Ok(avatar_urls(user_id, &version))
+}
+
+/// Multipart upload of the signed-in user's avatar, field `file` (JPEG, PNG or WebP).
+pub async fn upload_own_avatar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    multipart: Multipart,
+) -> Result<Json<BTreeMap<String, String>>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+    if !state.self_editable_fields.iter().any(|f| f == "avatar") {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(Json(upload(&state, user.id, multipart).await?))
+}
+
+pub async fn delete_own_avatar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<StatusCode, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+    if !state.self_editable_fields.iter().any(|f| f == "avatar") {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let removed = avatar_service::remove_avatar(&state.db, state.storage.as_ref(), user.id)
+        .await
+        .map_err(map_avatar_error)?;
+
+    Ok(if removed { StatusCode::NO_CONTENT } else { StatusCode::NOT_FOUND })
+}
+
+pub async fn upload_user_avatar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    multipart: Multipart,
+) -> Result<Json<BTreeMap<String, String>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_USER).await?;
+
+    Ok(Json(upload(&state, id, multipart).await?))
+}
+
+pub async fn delete_user_avatar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_USER).await?;
+
+    let removed = avatar_service::remove_avatar(&state.db, state.storage.as_ref(), id)
+        .await
+        .map_err(map_avatar_error)?;
+
+    Ok(if removed { StatusCode::NO_CONTENT } else { StatusCode::NOT_FOUND })
+}
+
+/// Serves a thumbnail to any signed-in user. URLs change with every upload, so
+/// responses are cacheable for good; superseded versions are gone (404).
+pub async fn get_avatar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((user_id, version, file)): Path<(Uuid, String, String)>,
+) -> Result<Response, StatusCode> {
+    current_user(&state, &headers).await?;
+
+    let size = file.strip_suffix(".jpg").ok_or(StatusCode::NOT_FOUND)?;
+    let etag = format!("\"{}-{}\"", version, size);
+    let cache_headers = [
+        (header::CACHE_CONTROL, "private, max-age=31536000, immutable".to_string()),
+        (header::ETAG, etag.clone()),
+    ];
+
+    let bytes = avatar_service::get_thumbnail(&state.db, state.storage.as_ref(), user_id, &version, size)
+        .await
+        .map_err(map_avatar_error)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|tag| tag.trim() == etag))
+    {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    Ok((
+        cache_headers,
+        [
+            (header::CONTENT_TYPE, "image/jpeg"),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        ],
+        bytes,
+    ).into_response())
+}
@@ -0,0 +1,212 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    handlers::policy_change_handler::propose_change,
+    models::audit_log::AuditLogEntry,
+    models::break_glass::{
+        ActivateBreakGlassPayload, BreakGlassActivation, BreakGlassGrant, CreateBreakGlassGrantPayload,
+    },
+    models::policy_change::PolicyChange,
+    services::{audit_service, break_glass_service, notification_service, user_service},
+    state::app_state::AppState,
+    utils::auth::{authorize_action, current_user, get_user_id_from_headers},
+    utils::permissions,
+};
+
+/// With four-eyes approval on, the grant waits for a second approver like a binding.
+pub async fn create_grant(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateBreakGlassGrantPayload>,
+) -> Result<Response, StatusCode> {
+    let admin = authorize_action(&state, &headers, permissions::CREATE_BREAK_GLASS).await?;
+
+    if payload.max_duration_minutes.is_some_and(|m| m <= 0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    if state.policy_change_approval {
+        let change = PolicyChange::GrantBreakGlass {
+            policy_id: payload.policy_id,
+            granted_by: admin.id,
+            user_id: payload.user_id,
+            max_duration_minutes: payload.max_duration_minutes,
+        };
+        return propose_change(&state, &admin, change).await;
+    }
+
+    let mut conn = state.db.acquire().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let grant = break_glass_service::create_grant(&mut conn, admin.id, &payload)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Break-glass grant rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            // Unknown user
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => StatusCode::BAD_REQUEST,
+            e => {
+                eprintln!("Create break-glass grant error: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    Ok((StatusCode::CREATED, Json(grant)).into_response())
+}
+
+pub async fn list_grants(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<BreakGlassGrant>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_BREAK_GLASS).await?;
+
+    let grants = break_glass_service::list_grants(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(grants))
+}
+
+pub async fn list_my_grants(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<BreakGlassGrant>>, StatusCode> {
+    let user_id = get_user_id_from_headers(&state, &headers).await?;
+
+    let grants = break_glass_service::list_grants_for_user(&state.db, user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(grants))
+}
+
+pub async fn revoke_grant(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::DELETE_BREAK_GLASS).await?;
+
+    let affected = break_glass_service::revoke_grant(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn activate(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<ActivateBreakGlassPayload>,
+) -> Result<(StatusCode, Json<BreakGlassActivation>), StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    // Justification is mandatory, same bar as a leave reason
+    if payload.justification.split_whitespace().count() < 5 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let grant = break_glass_service::get_grant(&state.db, payload.grant_id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    if grant.user_id != user.id || grant.revoked_at.is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let duration = payload.duration_minutes.unwrap_or(grant.max_duration_minutes);
+    if duration <= 0 || duration > grant.max_duration_minutes {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let activation = break_glass_service::activate(&state.db, &grant, payload.justification.trim(), duration)
+        .await
+        .map_err(|e| {
+            eprintln!("Break-glass activation error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    // NOTIFICATION: Every superadmin hears about it in real time
+    if let Ok(superadmins) = user_service::get_users_by_role_level_lte(&state.db, 0).await {
+        let msg = format!(
+            "BREAK-GLASS: {} activated emergency access for {} minutes. Justification: {}",
+            user.username, duration, activation.justification
+        );
+        for admin in superadmins {
+            let _ = notification_service::create_notification(
+                &state.db,
+                &state.notifications,
+                "break_glass",
+                &msg,
+                Some(admin.id),
+            ).await;
+        }
+    }
+
+    Ok((StatusCode::CREATED, Json(activation)))
+}
+
+pub async fn end_activation(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<BreakGlassActivation>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let activation = break_glass_service::end_activation(&state.db, id, user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Ok(superadmins) = user_service::get_users_by_role_level_lte(&state.db, 0).await {
+        let msg = format!("BREAK-GLASS: {} ended their emergency access", user.username);
+        for admin in superadmins {
+            let _ = notification_service::create_notification(
+                &state.db,
+                &state.notifications,
+                "break_glass",
+                &msg,
+                Some(admin.id),
+            ).await;
+        }
+    }
+
+    Ok(Json(activation))
+}
+
+pub async fn list_activations(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<BreakGlassActivation>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_BREAK_GLASS).await?;
+
+    let activations = break_glass_service::list_activations(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(activations))
+}
+
+pub async fn list_activation_audit(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<AuditLogEntry>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_BREAK_GLASS).await?;
+
+    let entries = audit_service::list_for_break_glass_activation(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(entries))
+}
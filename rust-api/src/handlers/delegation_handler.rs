@@ -0,0 +1,110 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::delegation::{CreateDelegationPayload, MyDelegations, PermissionDelegation},
+    services::{delegation_service, notification_service},
+    state::app_state::AppState,
+    utils::auth::{authorize_action, current_user},
+    utils::permissions,
+};
+
+pub async fn create_delegation(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateDelegationPayload>,
+) -> Result<(StatusCode, Json<Vec<PermissionDelegation>>), StatusCode> {
+    let delegator = current_user(&state, &headers).await?;
+    let delegate_id = payload.delegate_id;
+    let (starts_on, ends_on) = (payload.starts_on, payload.ends_on);
+
+    let delegations = delegation_service::create_delegations(&state.db, &delegator, payload)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Delegation rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    // NOTIFICATION: Tell the delegate what they can now do on the delegator's behalf
+    let granted = delegations
+        .iter()
+        .map(|d| format!("{} {}", d.action, d.resource))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let msg = format!(
+        "{} delegated [{}] to you from {} to {}",
+        delegator.username, granted, starts_on, ends_on
+    );
+    let _ = notification_service::create_notification(
+        &state.db,
+        &state.notifications,
+        "delegation",
+        &msg,
+        Some(delegate_id),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(delegations)))
+}
+
+pub async fn list_my_delegations(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<MyDelegations>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let given = delegation_service::list_given(&state.db, user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let received = delegation_service::list_received(&state.db, user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(MyDelegations { given, received }))
+}
+
+pub async fn list_delegations(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<PermissionDelegation>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_DELEGATION).await?;
+
+    let delegations = delegation_service::list_delegations(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(delegations))
+}
+
+pub async fn revoke_delegation(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let delegation = delegation_service::get_delegation(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    // The delegator can always take it back; anyone else needs the admin permission
+    if delegation.delegator_id != user.id {
+        authorize_action(&state, &headers, permissions::DELETE_DELEGATION).await?;
+    }
+
+    let affected = delegation_service::revoke_delegation(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
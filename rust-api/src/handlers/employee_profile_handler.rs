@@ -0,0 +1,65 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::employee_profile::{EmployeeProfile, EmployeeProfileChange, UpdateEmployeeProfilePayload},
+    services::employee_profile_service,
+    state::app_state::AppState,
+    utils::auth::{authorize_action, authorize_self_or},
+    utils::permissions,
+};
+
+pub async fn get_profile(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<EmployeeProfile>, StatusCode> {
+    // Employees can always read their own profile
+    authorize_self_or(&state, &headers, id, permissions::READ_USER).await?;
+
+    let profile = employee_profile_service::get_profile(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    Ok(Json(profile))
+}
+
+pub async fn update_profile(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateEmployeeProfilePayload>,
+) -> Result<Json<EmployeeProfile>, StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::UPDATE_USER).await?;
+
+    let profile = employee_profile_service::update_profile(&state.db, id, &payload, caller.id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Profile update rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    Ok(Json(profile))
+}
+
+pub async fn list_profile_history(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EmployeeProfileChange>>, StatusCode> {
+    authorize_self_or(&state, &headers, id, permissions::READ_USER).await?;
+
+    let history = employee_profile_service::list_history(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(history))
+}
@@ -0,0 +1,167 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::holiday::{
+        Holiday, HolidayCalendar, HolidayCalendarPayload, HolidayImportQuery, HolidayImportReport, HolidayPayload,
+        HolidayQuery,
+    },
+    services::holiday_service,
+    state::app_state::AppState,
+    utils::auth::{authorize_action, get_user_id_from_headers},
+    utils::permissions,
+};
+
+fn map_holiday_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        // Duplicate calendar name, or a second holiday on the same day
+        sqlx::Error::Database(db) if db.is_unique_violation() => StatusCode::CONFLICT,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Holiday calendar request rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            eprintln!("Holiday calendar error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+pub async fn list_calendars(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<HolidayCalendar>>, StatusCode> {
+    get_user_id_from_headers(&state, &headers).await?;
+
+    let calendars = holiday_service::list_calendars(&state.db)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok(Json(calendars))
+}
+
+pub async fn list_holidays(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<HolidayQuery>,
+) -> Result<Json<Vec<Holiday>>, StatusCode> {
+    get_user_id_from_headers(&state, &headers).await?;
+
+    let holidays = holiday_service::list_holidays(&state.db, id, query.year)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok(Json(holidays))
+}
+
+pub async fn create_calendar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<HolidayCalendarPayload>,
+) -> Result<(StatusCode, Json<HolidayCalendar>), StatusCode> {
+    authorize_action(&state, &headers, permissions::CREATE_HOLIDAY_CALENDAR).await?;
+
+    let calendar = holiday_service::create_calendar(&state.db, &payload)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok((StatusCode::CREATED, Json(calendar)))
+}
+
+pub async fn update_calendar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<HolidayCalendarPayload>,
+) -> Result<Json<HolidayCalendar>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_HOLIDAY_CALENDAR).await?;
+
+    let calendar = holiday_service::update_calendar(&state.db, id, &payload)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok(Json(calendar))
+}
+
+pub async fn delete_calendar(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::DELETE_HOLIDAY_CALENDAR).await?;
+
+    holiday_service::delete_calendar(&state.db, id)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn add_holiday(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<HolidayPayload>,
+) -> Result<(StatusCode, Json<Holiday>), StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_HOLIDAY_CALENDAR).await?;
+
+    let holiday = holiday_service::add_holiday(&state.db, id, &payload)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok((StatusCode::CREATED, Json(holiday)))
+}
+
+pub async fn update_holiday(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((id, holiday_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<HolidayPayload>,
+) -> Result<Json<Holiday>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_HOLIDAY_CALENDAR).await?;
+
+    let holiday = holiday_service::update_holiday(&state.db, id, holiday_id, &payload)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok(Json(holiday))
+}
+
+pub async fn delete_holiday(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((id, holiday_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_HOLIDAY_CALENDAR).await?;
+
+    holiday_service::delete_holiday(&state.db, id, holiday_id)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Imports the holidays of an iCalendar (.ics) file sent as the request body.
+/// Events that give no holiday are listed in the report rather than failing the import.
+pub async fn import_holidays(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<HolidayImportQuery>,
+    body: String,
+) -> Result<Json<HolidayImportReport>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_HOLIDAY_CALENDAR).await?;
+
+    let today = chrono::Utc::now().date_naive();
+    let report = holiday_service::import_ical(&state.db, id, &body, &query, today)
+        .await
+        .map_err(map_holiday_error)?;
+
+    Ok(Json(report))
+}
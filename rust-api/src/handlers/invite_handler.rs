@@ -0,0 +1,151 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use bcrypt::{hash, DEFAULT_COST};
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    models::invite::{AcceptInvitePayload, InviteDetails, OutboxMail, UserInvite},
+    services::{invite_service, mail_service},
+    state::app_state::AppState,
+    utils::auth::authorize_action,
+    utils::permissions,
+};
+
+/// Mails the invite. Failures are logged; the invite can be resent.
+pub async fn send_invite(state: &AppState, username: &str, email: &str, token: &str, expires_at: NaiveDateTime) {
+    let mail = invite_service::invite_mail(&state.app_base_url, username, email, token, expires_at);
+    if let Err(e) = state.mailer.send(&mail).await {
+        eprintln!("Mailer '{}' failed to send invite to {}: {}", state.mailer.name(), email, e);
+    }
+}
+
+pub async fn list_invites(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Vec<UserInvite>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::CREATE_USER).await?;
+
+    let invites = invite_service::list_invites(&state.db, user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(invites))
+}
+
+/// Issues a new invite to a user who hasn't activated yet. Earlier links stop working.
+pub async fn resend_invite(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<(StatusCode, Json<UserInvite>), StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::CREATE_USER).await?;
+
+    let (invite, token) = invite_service::resend_invite(&state.db, user_id, caller.id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Invite not resent: {}", msg);
+                StatusCode::CONFLICT
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    let user = crate::services::user_service::get_user(&state.db, user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    send_invite(&state, &user.username, &user.email, &token, invite.expires_at).await;
+
+    Ok((StatusCode::CREATED, Json(invite)))
+}
+
+/// Revokes the pending invite. The user stays invited and can be sent a new one.
+pub async fn revoke_invite(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::CREATE_USER).await?;
+
+    let revoked = invite_service::revoke_invites(&state.db, user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if revoked == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Public: who the invite is for, so the page can greet them.
+pub async fn get_invite(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Json<InviteDetails>, StatusCode> {
+    let details = invite_service::get_invite_details(&state.db, &token)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(details))
+}
+
+/// Public: sets the password and activates the account. The invitee then signs in.
+pub async fn accept_invite(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Json(payload): Json<AcceptInvitePayload>,
+) -> Result<StatusCode, StatusCode> {
+    if payload.password.chars().count() < invite_service::MIN_PASSWORD_LEN {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let password = payload.password;
+    let password_hash = tokio::task::spawn_blocking(move || hash(password, DEFAULT_COST))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let user_id = invite_service::accept_invite(&state.db, &token, &password_hash)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    let _ = crate::services::notification_service::create_notification(
+        &state.db,
+        &state.notifications,
+        "USER_ACTIVATED",
+        "Your account is active",
+        Some(user_id),
+    ).await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+pub struct OutboxQuery {
+    pub limit: Option<i64>,
+}
+
+/// Messages stored by the outbox mailer, newest first.
+pub async fn list_outbox(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<OutboxQuery>,
+) -> Result<Json<Vec<OutboxMail>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_MAIL_OUTBOX).await?;
+
+    let mails = mail_service::list_outbox(&state.db, query.limit.unwrap_or(50).clamp(1, 500))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(mails))
+}
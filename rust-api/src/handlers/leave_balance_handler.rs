@@ -0,0 +1,128 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::leave_balance::{
+        AccrualRunPayload, AccrualRunResult, LeaveAdjustmentPayload, LeaveBalance, LeaveLedgerEntry, LedgerQuery,
+    },
+    services::leave_balance_service,
+    state::app_state::AppState,
+    utils::auth::{authorize_action, authorize_self_or, get_user_id_from_headers},
+    utils::permissions,
+};
+
+fn map_balance_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Leave balance request rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            eprintln!("Leave balance error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn today() -> chrono::NaiveDate {
+    chrono::Utc::now().date_naive()
+}
+
+pub async fn my_balances(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LeaveBalance>>, StatusCode> {
+    let user_id = get_user_id_from_headers(&state, &headers).await?;
+
+    let balances = leave_balance_service::list_balances(&state.db, user_id, today())
+        .await
+        .map_err(map_balance_error)?;
+
+    Ok(Json(balances))
+}
+
+pub async fn my_ledger(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<Vec<LeaveLedgerEntry>>, StatusCode> {
+    let user_id = get_user_id_from_headers(&state, &headers).await?;
+
+    let entries = leave_balance_service::list_ledger(&state.db, user_id, query.leave_type.as_deref())
+        .await
+        .map_err(map_balance_error)?;
+
+    Ok(Json(entries))
+}
+
+pub async fn user_balances(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<LeaveBalance>>, StatusCode> {
+    authorize_self_or(&state, &headers, id, permissions::READ_LEAVE_BALANCE).await?;
+
+    let balances = leave_balance_service::list_balances(&state.db, id, today())
+        .await
+        .map_err(map_balance_error)?;
+
+    Ok(Json(balances))
+}
+
+pub async fn user_ledger(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<LedgerQuery>,
+) -> Result<Json<Vec<LeaveLedgerEntry>>, StatusCode> {
+    authorize_self_or(&state, &headers, id, permissions::READ_LEAVE_BALANCE).await?;
+
+    let entries = leave_balance_service::list_ledger(&state.db, id, query.leave_type.as_deref())
+        .await
+        .map_err(map_balance_error)?;
+
+    Ok(Json(entries))
+}
+
+/// Manual correction of a balance, e.g. to enter opening balances.
+pub async fn adjust_balance(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<LeaveAdjustmentPayload>,
+) -> Result<(StatusCode, Json<LeaveLedgerEntry>), StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::UPDATE_LEAVE_BALANCE).await?;
+
+    let entry = leave_balance_service::adjust(&state.db, id, &payload, caller.id, today())
+        .await
+        .map_err(map_balance_error)?;
+
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+/// Posts accruals for everyone. This also runs daily on its own, and balances
+/// catch up whenever they are read or used; use it to post a backdated run.
+pub async fn run_accruals(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    payload: Option<Json<AccrualRunPayload>>,
+) -> Result<Json<AccrualRunResult>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_LEAVE_BALANCE).await?;
+
+    let today = today();
+    let as_of = payload.and_then(|Json(p)| p.as_of).unwrap_or(today);
+    if as_of > today {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let result = leave_balance_service::run_accruals(&state.db, as_of)
+        .await
+        .map_err(map_balance_error)?;
+
+    Ok(Json(result))
+}
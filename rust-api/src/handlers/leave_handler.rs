@@ -1,17 +1,35 @@
 use axum::{
-    extract::{Path, State},
-    http::{StatusCode, HeaderMap},
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
     Json,
 };
 use uuid::Uuid;
 
 use crate::{
     models::leave_request::{LeaveRequest, CreateLeaveRequestPayload, UpdateLeaveStatusPayload},
-    services::leave_service,
+    models::holiday::{WorkingDays, WorkingDaysQuery},
+    models::leave_type::LeaveAttachment,
+    services::{holiday_service, leave_service, leave_type_service},
     state::app_state::AppState,
-    utils::auth::{authorize_action, get_user_id_from_headers},
+    utils::auth::{authorize_resource, authorize_rows, authorize_self_or, authorize_with_decision, get_user_id_from_headers},
+    utils::permissions,
 };
 
+fn map_leave_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Leave request rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            eprintln!("Leave request error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
 pub async fn create_leave_request(
     headers: HeaderMap,
     State(state): State<AppState>,
@@ -25,18 +43,40 @@ pub async fn create_leave_request(
         return Err(StatusCode::BAD_REQUEST);
     }
     
-    // Validate leave type
-    let valid_types = ["Casual", "Sick", "Unpaid"];
-    if !valid_types.contains(&payload.leave_type.as_str()) {
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    // Notice, length, eligibility and attachment rules of the leave type
+    let today = chrono::Utc::now().date_naive();
+    let leave_type = leave_type_service::check_request(
+        &state.db,
+        user_id,
+        &payload.leave_type,
+        payload.start_date,
+        payload.end_date,
+        payload.attachment_id,
+        today,
+    )
+    .await
+    .map_err(map_leave_error)?;
 
-    let leave_request = leave_service::create_leave_request(&state.db, user_id, payload)
+    let leave_request = leave_service::create_leave_request(&state.db, user_id, &leave_type, payload, today)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(map_leave_error)?;
 
-    // NOTIFICATION: Notify Admins (Level <= 1)
+    // NOTIFICATION: Notify the requester's manager and Admins (Level <= 1)
+    let mut recipients: Vec<Uuid> = Vec::new();
+    if let Ok(profile) = crate::services::employee_profile_service::get_profile(&state.db, user_id).await
+        && let Some(manager_id) = profile.manager_id
+    {
+        recipients.push(manager_id);
+    }
     if let Ok(admins) = crate::services::user_service::get_users_by_role_level_lte(&state.db, 1).await {
+        for admin in admins {
+            if !recipients.contains(&admin.id) {
+                recipients.push(admin.id);
+            }
+        }
+    }
+
+    if !recipients.is_empty() {
         // Fetch requester details for name
         let requester_name = if let Ok(u) = crate::services::user_service::get_user(&state.db, user_id).await {
             u.username
@@ -45,13 +85,13 @@ pub async fn create_leave_request(
         };
 
         let msg = format!("New Leave Request from {}", requester_name);
-        for admin in admins {
+        for recipient in recipients {
             let _ = crate::services::notification_service::create_notification(
                 &state.db,
                 &state.notifications,
                 "leave_request",
                 &msg,
-                Some(admin.id)
+                Some(recipient)
             ).await;
         }
     }
@@ -59,6 +99,28 @@ pub async fn create_leave_request(
     Ok((StatusCode::CREATED, Json(leave_request)))
 }
 
+/// How many days a request over these dates would take, with the weekend days
+/// and holidays it skips. Another user's count needs the permission to read their requests.
+pub async fn preview_working_days(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<WorkingDaysQuery>,
+) -> Result<Json<WorkingDays>, StatusCode> {
+    let user_id = match query.user_id {
+        Some(id) => {
+            authorize_self_or(&state, &headers, id, permissions::READ_LEAVE_REQUEST).await?;
+            id
+        }
+        None => get_user_id_from_headers(&state, &headers).await?,
+    };
+
+    let working_days = holiday_service::working_days(&state.db, user_id, query.start_date, query.end_date)
+        .await
+        .map_err(map_leave_error)?;
+
+    Ok(Json(working_days))
+}
+
 pub async fn list_my_leave_requests(
     headers: HeaderMap,
     State(state): State<AppState>,
@@ -76,10 +138,10 @@ pub async fn list_all_leave_requests(
     headers: HeaderMap,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<crate::models::leave_request::LeaveRequestWithUser>>, StatusCode> {
-    // Only managers/admins can view all leave requests
-    authorize_action(&state, &headers, "read", "leave_request").await?;
+    // Rule conditions narrow the list, e.g. team leads only see their team
+    let (_, filter) = authorize_rows(&state, &headers, permissions::READ_LEAVE_REQUEST).await?;
 
-    let requests = leave_service::list_all_leave_requests(&state.db)
+    let requests = leave_service::list_all_leave_requests(&state.db, &filter)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -99,7 +161,7 @@ pub async fn get_leave_request(
 
     // Users can only view their own requests unless they're admins
     if request.user_id != user_id {
-        authorize_action(&state, &headers, "read", "leave_request").await?;
+        authorize_resource(&state, &headers, permissions::READ_LEAVE_REQUEST, id).await?;
     }
 
     Ok(Json(request))
@@ -111,10 +173,10 @@ pub async fn update_leave_status(
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateLeaveStatusPayload>,
 ) -> Result<Json<LeaveRequest>, StatusCode> {
-    // Only managers can approve/reject
-    authorize_action(&state, &headers, "update", "leave_request").await?;
-    
-    let approver_id = get_user_id_from_headers(&state, &headers).await?;
+    // Only managers (or their delegates) can approve/reject
+    let (approver, decision) = authorize_with_decision(&state, &headers, permissions::UPDATE_LEAVE_REQUEST, Some(id)).await?;
+    let approver_id = approver.id;
+    let on_behalf_of = decision.delegated_by;
     
     // Validate status
     let valid_statuses = ["Approved", "Rejected"];
@@ -122,24 +184,10 @@ pub async fn update_leave_status(
         return Err(StatusCode::BAD_REQUEST);
     }
 
-    let request_opt = leave_service::update_leave_status(&state.db, id, &payload.status, approver_id)
+    let today = chrono::Utc::now().date_naive();
+    let request = leave_service::update_leave_status(&state.db, id, &payload.status, approver_id, on_behalf_of, today)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let request = match request_opt {
-        Some(r) => r,
-        None => {
-            // Already in that status, or ID not found. 
-            // Check if ID exists to be precise, but for now just return the current state if possible or NotFound if really not found.
-            // If we want to return the object even if not updated, we'd need another fetch.
-            // Simpler: If no update, just return OK (idempotent) and SKIP notifications.
-            // But we need to return the object.
-            return leave_service::get_leave_request(&state.db, id)
-                .await
-                .map(|r| Json(r))
-                .map_err(|_| StatusCode::NOT_FOUND);
-        }
-    };
+        .map_err(map_leave_error)?;
 
     // NOTIFICATION: Notify Requester
     let msg_user = format!("Your leave request has been {}", payload.status);
@@ -153,12 +201,13 @@ pub async fn update_leave_status(
 
     // NOTIFICATION: Notify Admins (transparency)
     if let Ok(admins) = crate::services::user_service::get_users_by_role_level_lte(&state.db, 1).await {
-        // Get approver name
-        let approver_name = if let Ok(u) = crate::services::user_service::get_user(&state.db, approver_id).await {
-            u.username
-        } else {
-            "Admin".to_string()
-        };
+        // Get approver name, noting the delegator when acting on their behalf
+        let mut approver_name = approver.username.clone();
+        if let Some(delegator_id) = on_behalf_of
+            && let Ok(u) = crate::services::user_service::get_user(&state.db, delegator_id).await
+        {
+            approver_name = format!("{} on behalf of {}", approver_name, u.username);
+        }
         
         // Get requester name
         let requester_name = if let Ok(u) = crate::services::user_service::get_user(&state.db, request.user_id).await {
@@ -211,5 +260,120 @@ pub async fn delete_leave_request(
         return Err(StatusCode::NOT_FOUND);
     }
 
+    // The attachment row went with the request; its file is removed here
+    if let Some(attachment_id) = request.attachment_id {
+        let key = leave_type_service::attachment_key(request.user_id, attachment_id);
+        if let Err(e) = state.storage.delete_prefix(&key).await {
+            eprintln!("Storage '{}' failed to delete {}: {}", state.storage.name(), key, e);
+        }
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// The owner withdraws a pending request, or an approved one that hasn't
+/// started yet; approved days go back to their balance.
+pub async fn cancel_leave_request(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LeaveRequest>, StatusCode> {
+    let user_id = get_user_id_from_headers(&state, &headers).await?;
+    let today = chrono::Utc::now().date_naive();
+
+    let request = leave_service::get_leave_request(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    if request.user_id != user_id {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let cancellable = request.status == "Pending" || (request.status == "Approved" && request.start_date > today);
+    if !cancellable {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let request = leave_service::cancel_leave_request(&state.db, id, today)
+        .await
+        .map_err(map_leave_error)?;
+
+    Ok(Json(request))
+}
+
+/// Multipart upload of a supporting document, field `file`. Submit the returned
+/// id as `attachment_id` with the leave request.
+pub async fn upload_attachment(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<(StatusCode, Json<LeaveAttachment>), StatusCode> {
+    let user_id = get_user_id_from_headers(&state, &headers).await?;
+
+    while let Some(mut field) = multipart.next_field().await.map_err(|e| e.status())? {
+        if field.name() != Some("file") {
+            continue;
+        }
+        let file_name = field.file_name().unwrap_or_default().to_string();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = field.chunk().await.map_err(|e| e.status())? {
+            if bytes.len() + chunk.len() > leave_type_service::MAX_ATTACHMENT_BYTES {
+                return Err(StatusCode::PAYLOAD_TOO_LARGE);
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        let attachment = leave_type_service::create_attachment(&state.db, state.storage.as_ref(), user_id, &file_name, &bytes)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Protocol(msg) => {
+                    eprintln!("Leave attachment rejected: {}", msg);
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE
+                }
+                e => map_leave_error(e),
+            })?;
+
+        return Ok((StatusCode::CREATED, Json(attachment)));
+    }
+    Err(StatusCode::BAD_REQUEST)
+}
+
+/// Downloads the request's supporting document; same access as the request itself.
+pub async fn get_attachment(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    let user_id = get_user_id_from_headers(&state, &headers).await?;
+
+    let request = leave_service::get_leave_request(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    if request.user_id != user_id {
+        authorize_resource(&state, &headers, permissions::READ_LEAVE_REQUEST, id).await?;
+    }
+
+    let attachment = leave_type_service::get_request_attachment(&state.db, id)
+        .await
+        .map_err(map_leave_error)?;
+    let bytes = state
+        .storage
+        .get(&leave_type_service::attachment_key(attachment.user_id, attachment.id))
+        .await
+        .map_err(|e| {
+            eprintln!("Storage '{}' failed to read attachment {}: {}", state.storage.name(), attachment.id, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let disposition = format!(
+        "attachment; filename=\"{}\"",
+        attachment.file_name.replace(|c: char| c == '"' || c == '\\' || !c.is_ascii(), "_")
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        bytes,
+    ).into_response())
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::leave_type::{LeaveType, LeaveTypePayload},
+    services::leave_type_service,
+    state::app_state::AppState,
+    utils::auth::{authorize_action, get_user_id_from_headers},
+    utils::permissions,
+};
+
+fn map_leave_type_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        // Duplicate name, or deleting a type that requests still use
+        sqlx::Error::Database(db) if db.is_unique_violation() || db.is_foreign_key_violation() => StatusCode::CONFLICT,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Leave type request rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            eprintln!("Leave type error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+/// The types the signed-in user may request.
+pub async fn list_requestable_types(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LeaveType>>, StatusCode> {
+    let user_id = get_user_id_from_headers(&state, &headers).await?;
+
+    let types = leave_type_service::list_requestable_types(&state.db, user_id)
+        .await
+        .map_err(map_leave_type_error)?;
+
+    Ok(Json(types))
+}
+
+/// Every type, including inactive ones and those the caller isn't eligible for.
+pub async fn list_types(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LeaveType>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_LEAVE_REQUEST).await?;
+
+    let types = leave_type_service::list_types(&state.db)
+        .await
+        .map_err(map_leave_type_error)?;
+
+    Ok(Json(types))
+}
+
+pub async fn create_type(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<LeaveTypePayload>,
+) -> Result<(StatusCode, Json<LeaveType>), StatusCode> {
+    authorize_action(&state, &headers, permissions::CREATE_LEAVE_TYPE).await?;
+
+    let leave_type = leave_type_service::create_type(&state.db, &payload)
+        .await
+        .map_err(map_leave_type_error)?;
+
+    Ok((StatusCode::CREATED, Json(leave_type)))
+}
+
+pub async fn update_type(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<LeaveTypePayload>,
+) -> Result<Json<LeaveType>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_LEAVE_TYPE).await?;
+
+    let leave_type = leave_type_service::update_type(&state.db, id, &payload)
+        .await
+        .map_err(map_leave_type_error)?;
+
+    Ok(Json(leave_type))
+}
+
+/// Only types no request uses can be deleted (409 otherwise); set `active` to
+/// false to retire the others.
+pub async fn delete_type(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::DELETE_LEAVE_TYPE).await?;
+
+    leave_type_service::delete_type(&state.db, id)
+        .await
+        .map_err(map_leave_type_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -7,3 +7,17 @@ pub mod leave_handler;
 pub mod report_handler;
 pub mod payslip_handler;
 pub mod template_handler;
+pub mod policy_change_handler;
+pub mod break_glass_handler;
+pub mod delegation_handler;
+pub mod record_share_handler;
+pub mod access_review_handler;
+pub mod employee_profile_handler;
+pub mod org_handler;
+pub mod invite_handler;
+pub mod user_attribute_handler;
+pub mod self_profile_handler;
+pub mod avatar_handler;
+pub mod leave_type_handler;
+pub mod leave_balance_handler;
+pub mod holiday_handler;
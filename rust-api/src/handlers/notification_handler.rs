@@ -9,6 +9,7 @@ use crate::{
     state::app_state::AppState,
     services::notification_service,
     utils::auth::authorize_action,
+    utils::permissions,
 };
 
 pub async fn mark_read(
@@ -16,7 +17,7 @@ pub async fn mark_read(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    authorize_action(&state, &headers, "update", "notification").await?;
+    authorize_action(&state, &headers, permissions::UPDATE_NOTIFICATION).await?;
 
     match notification_service::mark_as_read(&state.db, id).await {
         Ok(_) => Ok(StatusCode::OK),
@@ -28,7 +29,7 @@ pub async fn mark_all_read(
     headers: HeaderMap,
     State(state): State<AppState>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    authorize_action(&state, &headers, "update", "notification").await?;
+    authorize_action(&state, &headers, permissions::UPDATE_NOTIFICATION).await?;
 
     match notification_service::mark_all_as_read(&state.db).await {
         Ok(_) => Ok(StatusCode::OK),
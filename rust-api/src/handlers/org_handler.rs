@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::org::{OrgChartNode, OrgChartQuery, OrgMember, ReportsQuery},
+    services::org_service,
+    state::app_state::AppState,
+    utils::auth::{authorize_action, authorize_self_or},
+    utils::permissions,
+};
+
+pub async fn get_chart(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<OrgChartQuery>,
+) -> Result<Json<Vec<OrgChartNode>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_USER).await?;
+
+    let depth = query.depth.unwrap_or(org_service::DEFAULT_CHART_DEPTH);
+    let chart = org_service::get_chart(&state.db, query.root, depth)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    Ok(Json(chart))
+}
+
+pub async fn list_reports(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ReportsQuery>,
+) -> Result<Json<Vec<OrgMember>>, StatusCode> {
+    // Managers can always see who reports to them
+    authorize_self_or(&state, &headers, id, permissions::READ_USER).await?;
+
+    let reports = org_service::list_reports(&state.db, id, query.transitive)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(reports))
+}
+
+pub async fn get_chain(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<OrgMember>>, StatusCode> {
+    authorize_self_or(&state, &headers, id, permissions::READ_USER).await?;
+
+    let chain = org_service::get_chain(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(chain))
+}
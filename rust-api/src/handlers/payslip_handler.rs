@@ -9,7 +9,8 @@ use crate::{
     models::payslip::{Payslip, CreatePayslipPayload},
     services::payslip_service,
     state::app_state::AppState,
-    utils::auth::{authorize_action, get_user_id_from_headers},
+    utils::auth::{authorize_action, authorize_resource, get_user_id_from_headers},
+    utils::permissions,
 };
 
 pub async fn create_payslip(
@@ -18,7 +19,7 @@ pub async fn create_payslip(
     Json(payload): Json<CreatePayslipPayload>,
 ) -> Result<(StatusCode, Json<Payslip>), StatusCode> {
     // Only managers/admins can create payslips
-    authorize_action(&state, &headers, "create", "payslip").await?;
+    authorize_action(&state, &headers, permissions::CREATE_PAYSLIP).await?;
 
     let payslip = payslip_service::create_payslip(&state.db, payload)
         .await
@@ -53,7 +54,7 @@ pub async fn get_payslip(
 
     // Users can only view their own payslips
     if payslip.user_id != user_id {
-        authorize_action(&state, &headers, "read", "payslip").await?;
+        authorize_resource(&state, &headers, permissions::READ_PAYSLIP, id).await?;
     }
 
     Ok(Json(payslip))
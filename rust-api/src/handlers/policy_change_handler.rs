@@ -0,0 +1,160 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::policy_change::{
+        ListPolicyChangesQuery, PolicyChange, PolicyChangeDecisionPayload, PolicyChangeRequest,
+        PolicyChangeRequestWithHistory,
+    },
+    models::user::User,
+    services::notification_service,
+    services::policy_change_service::{self, Approval},
+    state::app_state::AppState,
+    utils::auth::authorize_action,
+    utils::permissions,
+};
+
+async fn notify(state: &AppState, message: &str, actor_id: Uuid) {
+    let _ = notification_service::create_notification(
+        &state.db,
+        &state.notifications,
+        "policy_change",
+        message,
+        Some(actor_id),
+    ).await;
+}
+
+/// Records a pending change instead of applying it. Used by the policy handlers
+/// when four-eyes approval is enabled.
+pub async fn propose_change(
+    state: &AppState,
+    user: &User,
+    change: PolicyChange,
+) -> Result<Response, StatusCode> {
+    let request = policy_change_service::propose(&state.db, &change, user.id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Policy change proposal rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            e => {
+                eprintln!("Propose policy change error: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    let msg = format!(
+        "{} proposed to {} policy {} (awaiting approval)",
+        user.username, request.change_type, change.policy_id()
+    );
+    notify(state, &msg, user.id).await;
+
+    Ok((StatusCode::ACCEPTED, Json(request)).into_response())
+}
+
+pub async fn list_changes(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<ListPolicyChangesQuery>,
+) -> Result<Json<Vec<PolicyChangeRequest>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_POLICY).await?;
+
+    let changes = policy_change_service::list_changes(&state.db, query.status.as_deref())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(changes))
+}
+
+pub async fn get_change(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PolicyChangeRequestWithHistory>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_POLICY).await?;
+
+    let request = policy_change_service::get_change(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let history = policy_change_service::list_events(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(PolicyChangeRequestWithHistory { request, history }))
+}
+
+/// Checks that the change is still pending and that the caller didn't propose it.
+async fn load_decidable(state: &AppState, id: Uuid, user: &User) -> Result<PolicyChangeRequest, StatusCode> {
+    let request = policy_change_service::get_change(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    if request.status != "pending" {
+        return Err(StatusCode::CONFLICT);
+    }
+    if request.proposed_by == Some(user.id) {
+        eprintln!("Four-eyes violation: {} tried to decide their own change {}", user.username, id);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(request)
+}
+
+pub async fn approve_change(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<PolicyChangeDecisionPayload>,
+) -> Result<Response, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::APPROVE_POLICY).await?;
+    load_decidable(&state, id, &user).await?;
+
+    let outcome = policy_change_service::approve(&state.db, id, user.id, payload.comment.as_deref())
+        .await
+        .map_err(|e| {
+            eprintln!("Approve policy change error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?
+        .ok_or(StatusCode::CONFLICT)?;
+
+    match outcome {
+        Approval::Applied(approved) => {
+            let change = approved.change().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            notify(&state, &format!("{} approved the change to {} policy {}", user.username, approved.change_type, change.policy_id()), user.id).await;
+            notify(&state, &format!("Policy change applied: {} policy {}", approved.change_type, change.policy_id()), user.id).await;
+            Ok(Json(approved).into_response())
+        }
+        Approval::Failed(failed, reason) => {
+            eprintln!("Apply policy change {} refused: {}", id, reason);
+            let change = failed.change().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            notify(&state, &format!("Policy change to {} policy {} failed: {}", failed.change_type, change.policy_id(), reason), user.id).await;
+            Ok((StatusCode::CONFLICT, Json(failed)).into_response())
+        }
+    }
+}
+
+pub async fn reject_change(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<PolicyChangeDecisionPayload>,
+) -> Result<Json<PolicyChangeRequest>, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::APPROVE_POLICY).await?;
+    load_decidable(&state, id, &user).await?;
+
+    let rejected = policy_change_service::reject(&state.db, id, user.id, payload.comment.as_deref())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::CONFLICT)?;
+
+    let change = rejected.change().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    notify(&state, &format!("{} rejected the change to {} policy {}", user.username, rejected.change_type, change.policy_id()), user.id).await;
+
+    Ok(Json(rejected))
+}
@@ -1,7 +1,8 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    http::HeaderMap,
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
     Json,
 };
 use uuid::Uuid;
@@ -9,11 +10,19 @@ use serde::Deserialize;
 use serde_json::json;
 
 use crate::{
-    models::user_role::{Policy, PolicyRule, PolicyBinding, AuthContext},
+    models::user_role::{Policy, PolicyRule, PolicyBinding, PolicyStatus, AuthContext},
+    models::policy_test::{PolicyTestCase, CreatePolicyTestCasePayload, PolicyTestSuiteResult},
+    models::policy_impact::{ImpactScenario, ImpactReport},
+    models::policy_change::PolicyChange,
+    handlers::policy_change_handler::propose_change,
     services::policy_service,
+    services::policy_test_service,
+    services::policy_impact_service,
     services::auth_service,
     state::app_state::AppState,
     utils::auth::authorize_action,
+    utils::permissions,
+    utils::policy_lang::{self, RuleSource},
 };
 
 #[derive(Deserialize)]
@@ -53,6 +62,14 @@ pub async fn list_roles(
     Ok(Json(roles))
 }
 
+pub async fn list_permissions() -> Json<serde_json::Value> {
+    Json(json!({
+        "actions": permissions::ACTIONS,
+        "resources": permissions::RESOURCES,
+        "routes": permissions::protected_routes(),
+    }))
+}
+
 pub async fn list_policies(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<Policy>>, StatusCode> {
@@ -67,7 +84,7 @@ pub async fn create_policy(
     State(state): State<AppState>,
     Json(payload): Json<CreatePolicyPayload>,
 ) -> Result<(StatusCode, Json<Policy>), StatusCode> {
-    authorize_action(&state, &headers, "create", "policy").await?;
+    authorize_action(&state, &headers, permissions::CREATE_POLICY).await?;
 
     let policy = policy_service::create_policy(
         &state.db,
@@ -88,42 +105,84 @@ pub async fn activate_policy(
     headers: HeaderMap,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
-    authorize_action(&state, &headers, "activate", "policy").await?;
+) -> Result<Response, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::ACTIVATE_POLICY).await?;
 
-    policy_service::activate_policy(&state.db, id)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if state.policy_change_approval {
+        return propose_change(&state, &user, PolicyChange::Activate { policy_id: id }).await;
+    }
 
-    Ok(StatusCode::OK)
+    let policy = policy_service::get_policy(&state.db, id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+    if policy.status != PolicyStatus::Draft.to_string() {
+        eprintln!("Activate policy rejected: policy {} is {}", id, policy.status);
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let (affected, suite) = policy_service::activate_policy(&state.db, id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Activate policy rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            e => {
+                eprintln!("Activate policy error: {:?}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        })?;
+
+    // A failing suite blocks activation; the per-case results explain why.
+    if !suite.passed {
+        return Ok((StatusCode::CONFLICT, Json(suite)).into_response());
+    }
+    // Someone else activated or archived it while the suite ran
+    if affected == 0 {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    Ok((StatusCode::OK, Json(suite)).into_response())
 }
 
 pub async fn archive_policy(
     headers: HeaderMap,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
-    authorize_action(&state, &headers, "archive", "policy").await?;
+) -> Result<Response, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::ARCHIVE_POLICY).await?;
+
+    if state.policy_change_approval {
+        return propose_change(&state, &user, PolicyChange::Archive { policy_id: id }).await;
+    }
 
     policy_service::archive_policy(&state.db, id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 pub async fn delete_policy(
     headers: HeaderMap,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
-    authorize_action(&state, &headers, "delete", "policy").await?;
+) -> Result<Response, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::DELETE_POLICY).await?;
+
+    if state.policy_change_approval {
+        return propose_change(&state, &user, PolicyChange::Delete { policy_id: id }).await;
+    }
 
     policy_service::delete_policy(&state.db, id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 pub async fn add_policy_rule(
@@ -132,7 +191,7 @@ pub async fn add_policy_rule(
     Path(id): Path<Uuid>,
     Json(payload): Json<AddRulePayload>,
 ) -> Result<(StatusCode, Json<PolicyRule>), StatusCode> {
-    authorize_action(&state, &headers, "edit", "policy").await?;
+    authorize_action(&state, &headers, permissions::EDIT_POLICY).await?;
 
     let rule = policy_service::add_policy_rule(
         &state.db,
@@ -143,7 +202,13 @@ pub async fn add_policy_rule(
         payload.conditions,
     )
     .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .map_err(|e| match e {
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Add rule rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    })?;
 
     Ok((StatusCode::CREATED, Json(rule)))
 }
@@ -153,8 +218,27 @@ pub async fn bind_policy(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(payload): Json<BindPolicyPayload>,
-) -> Result<(StatusCode, Json<PolicyBinding>), StatusCode> {
-    authorize_action(&state, &headers, "bind", "policy").await?;
+) -> Result<Response, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::BIND_POLICY).await?;
+
+    if state.policy_change_approval {
+        let change = PolicyChange::Bind {
+            policy_id: id,
+            subject_type: payload.subject_type,
+            subject_id: payload.subject_id,
+        };
+        return propose_change(&state, &user, change).await;
+    }
+
+    policy_service::check_binding_subject(&state.db, &payload.subject_type, payload.subject_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Bind policy rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
 
     let binding = policy_service::bind_policy(
         &state.db,
@@ -165,7 +249,7 @@ pub async fn bind_policy(
     .await
     .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok((StatusCode::CREATED, Json(binding)))
+    Ok((StatusCode::CREATED, Json(binding)).into_response())
 }
 
 pub async fn simulate_auth(
@@ -174,7 +258,7 @@ pub async fn simulate_auth(
     Json(payload): Json<SimulatePayload>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Current user must have 'simulate' permission
-    let user = authorize_action(&state, &headers, "simulate", "auth").await?;
+    let user = authorize_action(&state, &headers, permissions::SIMULATE_AUTH).await?;
 
     let decision = auth_service::authorize(
         &state.db,
@@ -202,6 +286,60 @@ pub async fn list_policy_rules(
     Ok(Json(rules))
 }
 
+/// The policy's rules as policy language source.
+pub async fn get_policy_source(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, StatusCode> {
+    policy_service::get_policy(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    let rules = policy_service::list_policy_rules(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // Rules stored with conditions the language can't express are kept visible as comments
+    let source: String = rules
+        .iter()
+        .map(|rule| match RuleSource::from_rule(rule) {
+            Ok(parsed) => policy_lang::print_rule(&parsed) + "\n",
+            Err(reason) => format!("// rule {} cannot be expressed: {}\n", rule.id, reason),
+        })
+        .collect();
+
+    Ok(([(header::CONTENT_TYPE, "text/plain; charset=utf-8")], source).into_response())
+}
+
+/// Replaces the rules of a draft policy with the given source.
+/// Parse errors come back as 400 with the line and column.
+pub async fn put_policy_source(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    source: String,
+) -> Result<Response, StatusCode> {
+    authorize_action(&state, &headers, permissions::EDIT_POLICY).await?;
+
+    let parsed = match policy_lang::parse(&source) {
+        Ok(parsed) => parsed,
+        Err(e) => return Ok((StatusCode::BAD_REQUEST, Json(e)).into_response()),
+    };
+
+    let rules = policy_service::replace_policy_rules(&state.db, id, &parsed)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Policy source rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    Ok(Json(rules).into_response())
+}
+
 pub async fn list_policy_bindings(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
@@ -237,11 +375,18 @@ pub async fn remove_policy_rule(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
-    authorize_action(&state, &headers, "edit", "policy").await?;
+    authorize_action(&state, &headers, permissions::EDIT_POLICY).await?;
 
     policy_service::remove_policy_rule(&state.db, id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Remove rule rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
 
     Ok(StatusCode::NO_CONTENT)
 }
@@ -250,12 +395,114 @@ pub async fn unbind_policy(
     headers: HeaderMap,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, StatusCode> {
-    authorize_action(&state, &headers, "bind", "policy").await?;
+) -> Result<Response, StatusCode> {
+    let user = authorize_action(&state, &headers, permissions::BIND_POLICY).await?;
+
+    if state.policy_change_approval {
+        let binding = policy_service::get_binding(&state.db, id)
+            .await
+            .map_err(|_| StatusCode::NOT_FOUND)?;
+        let change = PolicyChange::Unbind { policy_id: binding.policy_id, binding_id: id };
+        return propose_change(&state, &user, change).await;
+    }
 
     policy_service::unbind_policy(&state.db, id)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+pub async fn list_policy_tests(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<PolicyTestCase>>, StatusCode> {
+    let cases = policy_test_service::list_test_cases(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(cases))
+}
+
+pub async fn add_policy_test(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CreatePolicyTestCasePayload>,
+) -> Result<(StatusCode, Json<PolicyTestCase>), StatusCode> {
+    authorize_action(&state, &headers, permissions::EDIT_POLICY).await?;
+
+    let case = policy_test_service::add_test_case(&state.db, id, payload)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Add test case rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    Ok((StatusCode::CREATED, Json(case)))
+}
+
+pub async fn remove_policy_test(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::EDIT_POLICY).await?;
+
+    let affected = policy_test_service::remove_test_case(&state.db, id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Remove test case rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
     Ok(StatusCode::NO_CONTENT)
 }
+
+pub async fn run_policy_tests(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<PolicyTestSuiteResult>, StatusCode> {
+    authorize_action(&state, &headers, permissions::SIMULATE_AUTH).await?;
+
+    let suite = policy_test_service::run_suite(&state.db, id)
+        .await
+        .map_err(|e| {
+            eprintln!("Run policy tests error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+
+    Ok(Json(suite))
+}
+
+pub async fn analyze_impact(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<ImpactScenario>,
+) -> Result<Json<ImpactReport>, StatusCode> {
+    authorize_action(&state, &headers, permissions::SIMULATE_AUTH).await?;
+
+    let report = policy_impact_service::analyze(&state.db, payload)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Impact analysis rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    Ok(Json(report))
+}
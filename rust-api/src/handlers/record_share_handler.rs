@@ -0,0 +1,121 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::record_share::{CreateRecordSharePayload, RecordAccessEntry, RecordShare},
+    models::user::User,
+    services::{notification_service, record_share_service},
+    state::app_state::AppState,
+    utils::auth::{authorize_action, current_user},
+    utils::permissions::{self, Permission},
+};
+
+fn map_share_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Record share rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// The record's owner manages its shares; anyone else needs the admin permission.
+async fn authorize_owner_or(
+    state: &AppState,
+    headers: &HeaderMap,
+    resource: &str,
+    resource_id: Uuid,
+    permission: Permission,
+) -> Result<User, StatusCode> {
+    let user = current_user(state, headers).await?;
+    let owner_id = record_share_service::get_owner(&state.db, resource, resource_id)
+        .await
+        .map_err(map_share_error)?;
+
+    if owner_id != user.id {
+        authorize_action(state, headers, permission).await?;
+    }
+    Ok(user)
+}
+
+pub async fn create_share(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((resource, resource_id)): Path<(String, Uuid)>,
+    Json(payload): Json<CreateRecordSharePayload>,
+) -> Result<(StatusCode, Json<RecordShare>), StatusCode> {
+    let user = authorize_owner_or(&state, &headers, &resource, resource_id, permissions::CREATE_RECORD_SHARE).await?;
+
+    let share = record_share_service::create_share(&state.db, &resource, resource_id, &payload, user.id)
+        .await
+        .map_err(map_share_error)?;
+
+    // NOTIFICATION: Tell the grantee what was shared with them
+    let msg = format!("{} shared a {} with you ({})", user.username, resource, share.action);
+    let _ = notification_service::create_notification(
+        &state.db,
+        &state.notifications,
+        "record_share",
+        &msg,
+        Some(share.grantee_id),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(share)))
+}
+
+pub async fn list_shares(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((resource, resource_id)): Path<(String, Uuid)>,
+) -> Result<Json<Vec<RecordShare>>, StatusCode> {
+    authorize_owner_or(&state, &headers, &resource, resource_id, permissions::READ_RECORD_SHARE).await?;
+
+    let shares = record_share_service::list_shares(&state.db, &resource, resource_id)
+        .await
+        .map_err(map_share_error)?;
+
+    Ok(Json(shares))
+}
+
+/// Who has access to the record, and through what: ownership, a share or a policy.
+pub async fn list_access(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path((resource, resource_id)): Path<(String, Uuid)>,
+) -> Result<Json<Vec<RecordAccessEntry>>, StatusCode> {
+    authorize_owner_or(&state, &headers, &resource, resource_id, permissions::READ_RECORD_SHARE).await?;
+
+    let entries = record_share_service::list_access(&state.db, &resource, resource_id)
+        .await
+        .map_err(map_share_error)?;
+
+    Ok(Json(entries))
+}
+
+pub async fn revoke_share(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let share = record_share_service::get_share(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    authorize_owner_or(&state, &headers, &share.resource, share.resource_id, permissions::DELETE_RECORD_SHARE).await?;
+
+    let affected = record_share_service::revoke_share(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if affected == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -9,7 +9,8 @@ use crate::{
     models::report::{Report, CreateReportPayload, UpdateReportStatusPayload},
     services::report_service,
     state::app_state::AppState,
-    utils::auth::{authorize_action, get_user_id_from_headers},
+    utils::auth::{authorize_resource, authorize_rows, get_user_id_from_headers},
+    utils::permissions,
 };
 
 pub async fn create_report(
@@ -43,10 +44,10 @@ pub async fn list_all_reports(
     headers: HeaderMap,
     State(state): State<AppState>,
 ) -> Result<Json<Vec<Report>>, StatusCode> {
-    // Only managers can view all reports
-    authorize_action(&state, &headers, "read", "report").await?;
+    // Rule conditions narrow the list, e.g. team leads only see their team
+    let (_, filter) = authorize_rows(&state, &headers, permissions::READ_REPORT).await?;
 
-    let reports = report_service::list_all_reports(&state.db)
+    let reports = report_service::list_all_reports(&state.db, &filter)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -66,7 +67,7 @@ pub async fn get_report(
 
     // Users can only view their own reports unless they're admins
     if report.user_id != user_id {
-        authorize_action(&state, &headers, "read", "report").await?;
+        authorize_resource(&state, &headers, permissions::READ_REPORT, id).await?;
     }
 
     Ok(Json(report))
@@ -79,7 +80,7 @@ pub async fn update_report_status(
     Json(payload): Json<UpdateReportStatusPayload>,
 ) -> Result<Json<Report>, StatusCode> {
     // Only managers can mark reports as reviewed
-    authorize_action(&state, &headers, "update", "report").await?;
+    authorize_resource(&state, &headers, permissions::UPDATE_REPORT, id).await?;
     
     let valid_statuses = ["Submitted", "Reviewed"];
     if !valid_statuses.contains(&payload.status.as_str()) {
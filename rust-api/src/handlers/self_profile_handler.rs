@@ -0,0 +1,159 @@
+use axum::{
+    extract::State,
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use bcrypt::verify;
+
+use crate::{
+    models::self_profile::{ChangeEmailPayload, ConfirmEmailPayload, SelfProfile, UpdateSelfProfilePayload},
+    services::self_profile_service,
+    state::app_state::AppState,
+    utils::auth::current_user,
+};
+
+fn map_self_profile_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("Self-service profile change rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            eprintln!("Self-service profile error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+fn is_editable(state: &AppState, field: &str) -> bool {
+    state.self_editable_fields.iter().any(|f| f == field)
+}
+
+pub async fn get_profile(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<SelfProfile>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let profile = self_profile_service::get_profile(&state.db, user.id, &state.self_editable_fields)
+        .await
+        .map_err(map_self_profile_error)?;
+
+    Ok(Json(profile))
+}
+
+/// Changes the fields present in the body. Any field this deployment doesn't
+/// let users edit is refused with 403 and nothing is changed.
+pub async fn update_profile(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<UpdateSelfProfilePayload>,
+) -> Result<Json<SelfProfile>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    if let Some(field) = payload.fields().into_iter().find(|f| !is_editable(&state, f)) {
+        eprintln!("User {} tried to edit '{}', which is not self-editable", user.username, field);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    self_profile_service::update_profile(&state.db, user.id, &payload)
+        .await
+        .map_err(map_self_profile_error)?;
+
+    let profile = self_profile_service::get_profile(&state.db, user.id, &state.self_editable_fields)
+        .await
+        .map_err(map_self_profile_error)?;
+
+    Ok(Json(profile))
+}
+
+/// Starts an email change after checking the current password. The new address
+/// gets a confirmation link and the current one a notice; nothing changes until
+/// the link is used.
+pub async fn request_email_change(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<ChangeEmailPayload>,
+) -> Result<(StatusCode, Json<SelfProfile>), StatusCode> {
+    let user = current_user(&state, &headers).await?;
+    if !is_editable(&state, "email") {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    let password_hash = user.password_hash.clone();
+    let password_ok = tokio::task::spawn_blocking(move || verify(payload.current_password, &password_hash))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if !password_ok {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+
+    let (pending, token) = self_profile_service::request_email_change(&state.db, user.id, &payload.new_email)
+        .await
+        .map_err(map_self_profile_error)?;
+
+    let mails = [
+        self_profile_service::confirm_email_mail(&state.app_base_url, &user.username, &pending, &token),
+        self_profile_service::email_change_notice(&user.username, &user.email, &pending.new_email, false),
+    ];
+    for mail in &mails {
+        if let Err(e) = state.mailer.send(mail).await {
+            eprintln!("Mailer '{}' failed to send to {}: {}", state.mailer.name(), mail.to, e);
+        }
+    }
+
+    let profile = self_profile_service::get_profile(&state.db, user.id, &state.self_editable_fields)
+        .await
+        .map_err(map_self_profile_error)?;
+
+    Ok((StatusCode::ACCEPTED, Json(profile)))
+}
+
+pub async fn cancel_email_change(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<StatusCode, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let cancelled = self_profile_service::cancel_email_change(&state.db, user.id)
+        .await
+        .map_err(map_self_profile_error)?;
+    if cancelled == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Applies the pending change. The token must come from the mail sent to the
+/// new address and the request must be made by the same signed-in user.
+pub async fn confirm_email_change(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<ConfirmEmailPayload>,
+) -> Result<Json<SelfProfile>, StatusCode> {
+    let user = current_user(&state, &headers).await?;
+
+    let (old_email, new_email) = self_profile_service::confirm_email_change(&state.db, user.id, payload.token.trim())
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Email change not applied: {}", msg);
+                StatusCode::CONFLICT
+            }
+            e => map_self_profile_error(e),
+        })?;
+
+    let notice = self_profile_service::email_change_notice(&user.username, &old_email, &new_email, true);
+    if let Err(e) = state.mailer.send(&notice).await {
+        eprintln!("Mailer '{}' failed to send to {}: {}", state.mailer.name(), notice.to, e);
+    }
+
+    let profile = self_profile_service::get_profile(&state.db, user.id, &state.self_editable_fields)
+        .await
+        .map_err(map_self_profile_error)?;
+
+    Ok(Json(profile))
+}
@@ -10,6 +10,7 @@ use crate::{
     services::template_service,
     state::app_state::AppState,
     utils::auth::{authorize_action, get_user_id_from_headers},
+    utils::permissions,
 };
 
 pub async fn create_template(
@@ -18,7 +19,7 @@ pub async fn create_template(
     Json(payload): Json<CreatePayslipTemplatePayload>,
 ) -> Result<(StatusCode, Json<PayslipTemplate>), StatusCode> {
     // Only managers/admins can create templates
-    authorize_action(&state, &headers, "create", "payslip_template").await?;
+    authorize_action(&state, &headers, permissions::CREATE_PAYSLIP_TEMPLATE).await?;
     
     let user_id = get_user_id_from_headers(&state, &headers).await?;
 
@@ -34,7 +35,7 @@ pub async fn list_templates(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<PayslipTemplate>>, StatusCode> {
     // Only managers/admins can view templates
-    authorize_action(&state, &headers, "read", "payslip_template").await?;
+    authorize_action(&state, &headers, permissions::READ_PAYSLIP_TEMPLATE).await?;
 
     let templates = template_service::list_templates(&state.db)
         .await
@@ -48,7 +49,7 @@ pub async fn get_template(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<PayslipTemplate>, StatusCode> {
-    authorize_action(&state, &headers, "read", "payslip_template").await?;
+    authorize_action(&state, &headers, permissions::READ_PAYSLIP_TEMPLATE).await?;
 
     let template = template_service::get_template(&state.db, id)
         .await
@@ -63,7 +64,7 @@ pub async fn update_template(
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdatePayslipTemplatePayload>,
 ) -> Result<Json<PayslipTemplate>, StatusCode> {
-    authorize_action(&state, &headers, "update", "payslip_template").await?;
+    authorize_action(&state, &headers, permissions::UPDATE_PAYSLIP_TEMPLATE).await?;
 
     let template = template_service::update_template(&state.db, id, payload)
         .await
@@ -77,7 +78,7 @@ pub async fn set_active_template(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<Json<PayslipTemplate>, StatusCode> {
-    authorize_action(&state, &headers, "update", "payslip_template").await?;
+    authorize_action(&state, &headers, permissions::UPDATE_PAYSLIP_TEMPLATE).await?;
 
     let template = template_service::set_active_template(&state.db, id)
         .await
@@ -91,7 +92,7 @@ pub async fn delete_template(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
-    authorize_action(&state, &headers, "delete", "payslip_template").await?;
+    authorize_action(&state, &headers, permissions::DELETE_PAYSLIP_TEMPLATE).await?;
 
     let affected = template_service::delete_template(&state.db, id)
         .await
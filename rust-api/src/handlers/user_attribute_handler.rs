@@ -0,0 +1,120 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, HeaderMap},
+    Json,
+};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{
+    models::user_attribute::{
+        CreateUserAttributeDefinitionPayload, UpdateUserAttributeDefinitionPayload, UserAttributeDefinition,
+    },
+    services::user_attribute_service,
+    state::app_state::AppState,
+    utils::auth::{authorize_action, authorize_self_or},
+    utils::permissions,
+};
+
+fn map_attribute_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+        sqlx::Error::Database(db) if db.is_unique_violation() => StatusCode::CONFLICT,
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("User attribute request rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        e => {
+            eprintln!("User attribute error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+pub async fn list_definitions(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<UserAttributeDefinition>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_USER).await?;
+
+    let definitions = user_attribute_service::list_definitions(&state.db)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(definitions))
+}
+
+pub async fn create_definition(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Json(payload): Json<CreateUserAttributeDefinitionPayload>,
+) -> Result<(StatusCode, Json<UserAttributeDefinition>), StatusCode> {
+    authorize_action(&state, &headers, permissions::CREATE_USER_ATTRIBUTE).await?;
+
+    let definition = user_attribute_service::create_definition(&state.db, &payload)
+        .await
+        .map_err(map_attribute_error)?;
+
+    Ok((StatusCode::CREATED, Json(definition)))
+}
+
+pub async fn update_definition(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateUserAttributeDefinitionPayload>,
+) -> Result<Json<UserAttributeDefinition>, StatusCode> {
+    authorize_action(&state, &headers, permissions::UPDATE_USER_ATTRIBUTE).await?;
+
+    let definition = user_attribute_service::update_definition(&state.db, id, &payload)
+        .await
+        .map_err(map_attribute_error)?;
+
+    Ok(Json(definition))
+}
+
+/// Also removes every stored value of the attribute.
+pub async fn delete_definition(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    authorize_action(&state, &headers, permissions::DELETE_USER_ATTRIBUTE).await?;
+
+    user_attribute_service::delete_definition(&state.db, id)
+        .await
+        .map_err(map_attribute_error)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_values(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Map<String, Value>>, StatusCode> {
+    // Employees can always read their own attributes
+    authorize_self_or(&state, &headers, id, permissions::READ_USER).await?;
+
+    let values = user_attribute_service::get_values(&state.db, id)
+        .await
+        .map_err(map_attribute_error)?;
+
+    Ok(Json(values))
+}
+
+/// Replaces all of the user's attribute values.
+pub async fn set_values(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(values): Json<Map<String, Value>>,
+) -> Result<Json<Map<String, Value>>, StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::UPDATE_USER).await?;
+
+    let values = user_attribute_service::set_values(&state.db, id, &values, caller.id)
+        .await
+        .map_err(map_attribute_error)?;
+
+    Ok(Json(values))
+}
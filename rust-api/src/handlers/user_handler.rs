@@ -1,40 +1,68 @@
 use axum::{
-    extract::{Path, State},
-    http::{StatusCode, HeaderMap},
+    extract::{Path, Query, State},
+    http::{header, StatusCode, HeaderMap},
+    response::{IntoResponse, Response},
     Json,
 };
 use uuid::Uuid;
 use bcrypt::{hash, DEFAULT_COST};
+use futures_util::stream::{self, StreamExt};
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
 
 use crate::{
-    models::user::{CreateUserPayload, UpdateUserPayload, User, UserWithRole},
-    services::user_service,
+    models::user::{
+        ChangeUserStatusPayload, CreateUserPayload, ListUsersQuery, UpdateUserPayload, UserPage,
+        UserStatusChange,
+    },
+    models::user_import::{ImportUsersQuery, UserImportReport},
+    models::user_response::UserResponse,
+    services::{invite_service, user_attribute_service, user_import_service, user_service},
     state::app_state::AppState,
-    utils::auth::authorize_action,
+    utils::auth::{authorize_action, field_visibility},
+    handlers::invite_handler::send_invite,
+    utils::permissions,
 };
 
+/// Passwords hashed in parallel during a CSV import
+const IMPORT_HASH_CONCURRENCY: usize = 4;
+
+/// A secret nobody knows, hashed; stands in for the password until the invitee
+/// chooses one.
+async fn placeholder_password_hash() -> Result<String, StatusCode> {
+    let secret: String = thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect();
+    tokio::task::spawn_blocking(move || hash(secret, DEFAULT_COST))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Creates the user in the invited state and mails them an invite to choose
+/// their password.
 pub async fn create_user(
     headers: HeaderMap,
     State(state): State<AppState>,
     Json(payload): Json<CreateUserPayload>,
-) -> Result<(StatusCode, Json<User>), StatusCode> {
-    authorize_action(&state, &headers, "create", "user").await?;
+) -> Result<(StatusCode, Json<UserResponse>), StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::CREATE_USER).await?;
 
-    let username = payload.username.clone();
-    
-    // Hash password before storage
-    let password_hash = hash(&payload.password_hash, DEFAULT_COST)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-
-    let user_payload = CreateUserPayload {
-        username: payload.username.clone(),
-        email: payload.email.clone(),
-        password_hash,
-    };
+    let username = payload.username.trim().to_string();
+    let email = payload.email.trim().to_string();
+    if username.is_empty() || !email.contains('@') {
+        return Err(StatusCode::BAD_REQUEST);
+    }
 
-    let user = user_service::create_user(&state.db, user_payload)
+    let password_hash = placeholder_password_hash().await?;
+    let (user, invite, token) = invite_service::create_invited_user(&state.db, &username, &email, &password_hash, caller.id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Create user rejected: {}", msg);
+                StatusCode::CONFLICT
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    send_invite(&state, &user.username, &user.email, &token, invite.expires_at).await;
 
     // Trigger notification
     let _ = crate::services::notification_service::create_notification(
@@ -45,34 +73,89 @@ pub async fn create_user(
         Some(user.id),
     ).await;
 
-    Ok((StatusCode::CREATED, Json(user)))
+    let visibility = field_visibility(&state, &headers, &caller).await;
+    Ok((StatusCode::CREATED, Json(UserResponse::from_user(user, &visibility))))
+}
+
+fn map_list_error(e: sqlx::Error) -> StatusCode {
+    match e {
+        sqlx::Error::Protocol(msg) => {
+            eprintln!("List users rejected: {}", msg);
+            StatusCode::BAD_REQUEST
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Adds the `attr.<name>=value` parameters of the query string to the list filters.
+async fn with_attribute_filter(
+    state: &AppState,
+    mut query: ListUsersQuery,
+    params: &[(String, String)],
+) -> Result<ListUsersQuery, StatusCode> {
+    query.attributes = user_attribute_service::filter_from_query(&state.db, params)
+        .await
+        .map_err(map_list_error)?;
+    Ok(query)
 }
 
 pub async fn list_users(
     headers: HeaderMap,
     State(state): State<AppState>,
-) -> Result<Json<Vec<UserWithRole>>, StatusCode> {
-    authorize_action(&state, &headers, "read", "user").await?;
+    Query(query): Query<ListUsersQuery>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<Json<UserPage>, StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::READ_USER).await?;
+    let visibility = field_visibility(&state, &headers, &caller).await;
+    let query = with_attribute_filter(&state, query, &params).await?;
+
+    let page = user_service::list_users(&state.db, &query, &visibility)
+        .await
+        .map_err(map_list_error)?;
+
+    Ok(Json(page))
+}
+
+/// The filtered user list as a CSV download, custom attributes included.
+pub async fn export_users(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<ListUsersQuery>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Result<Response, StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::READ_USER).await?;
+    let visibility = field_visibility(&state, &headers, &caller).await;
+    let query = with_attribute_filter(&state, query, &params).await?;
 
-    let users = user_service::list_users(&state.db)
+    let definitions = user_attribute_service::list_definitions(&state.db)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let body = user_service::export_users_csv(&state.db, &query, &visibility, &definitions)
+        .await
+        .map_err(map_list_error)?;
 
-    Ok(Json(users))
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"users.csv\""),
+        ],
+        body,
+    ).into_response())
 }
 
 pub async fn get_user(
     headers: HeaderMap,
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<User>, StatusCode> {
-    authorize_action(&state, &headers, "read", "user").await?;
+) -> Result<Json<UserResponse>, StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::READ_USER).await?;
 
-    let user = user_service::get_user(&state.db, id)
+    let user = user_service::get_user_with_role(&state.db, id)
         .await
         .map_err(|_| StatusCode::NOT_FOUND)?;
 
-    Ok(Json(user))
+    let visibility = field_visibility(&state, &headers, &caller).await;
+    Ok(Json(UserResponse::from_user_with_role(user, &visibility)))
 }
 
 pub async fn update_user(
@@ -80,14 +163,15 @@ pub async fn update_user(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateUserPayload>,
-) -> Result<Json<User>, StatusCode> {
-    authorize_action(&state, &headers, "update", "user").await?;
+) -> Result<Json<UserResponse>, StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::UPDATE_USER).await?;
 
     let user = user_service::update_user(&state.db, id, payload)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(Json(user))
+    let visibility = field_visibility(&state, &headers, &caller).await;
+    Ok(Json(UserResponse::from_user(user, &visibility)))
 }
 
 pub async fn delete_user(
@@ -95,7 +179,7 @@ pub async fn delete_user(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
 ) -> Result<StatusCode, StatusCode> {
-    authorize_action(&state, &headers, "delete", "user").await?;
+    authorize_action(&state, &headers, permissions::DELETE_USER).await?;
 
     // Fetch user first to get the username for the notification
     let user = match user_service::get_user(&state.db, id).await {
@@ -105,12 +189,24 @@ pub async fn delete_user(
 
     let username = user.username.clone();
 
-    let affected = user_service::delete_user(&state.db, id)
+    let affected = user_service::delete_user(&state.db, id, state.user_retention_days)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    // Only users terminated longer than the retention period can be removed
     if affected == 0 {
-        return Err(StatusCode::NOT_FOUND);
+        eprintln!(
+            "Delete rejected: user {} has not been terminated for {} days",
+            username, state.user_retention_days
+        );
+        return Err(StatusCode::CONFLICT);
+    }
+
+    // Their stored files: avatars and leave attachments
+    for prefix in [format!("avatars/{}", id), format!("leave-attachments/{}", id)] {
+        if let Err(e) = state.storage.delete_prefix(&prefix).await {
+            eprintln!("Storage '{}' failed to delete {}: {}", state.storage.name(), prefix, e);
+        }
     }
 
     // Trigger notification
@@ -125,3 +221,129 @@ pub async fn delete_user(
     Ok(StatusCode::NO_CONTENT)
 }
 
+
+/// Suspends, terminates or reactivates a user, now or at a future date.
+/// Deactivating someone signs them out everywhere.
+pub async fn change_user_status(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ChangeUserStatusPayload>,
+) -> Result<Json<UserStatusChange>, StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::UPDATE_USER).await?;
+
+    if caller.id == id {
+        eprintln!("Status change rejected: {} tried to change their own status", caller.username);
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let change = user_service::change_status(&state.db, id, &payload, caller.id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => StatusCode::NOT_FOUND,
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("Status change rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    let _ = crate::services::notification_service::create_notification(
+        &state.db,
+        &state.notifications,
+        "USER_STATUS_CHANGED",
+        &format!("User status set to {} from {}", change.status, change.effective_at),
+        Some(id),
+    ).await;
+
+    Ok(Json(change))
+}
+
+pub async fn list_user_status_history(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<UserStatusChange>>, StatusCode> {
+    authorize_action(&state, &headers, permissions::READ_USER).await?;
+
+    let history = user_service::list_status_history(&state.db, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(history))
+}
+
+/// Creates users from a CSV body with the columns username, email, role,
+/// department, manager_email and password. Nothing is written unless every row
+/// is valid; with `?dry_run=true` only the validation report is returned.
+pub async fn import_users(
+    headers: HeaderMap,
+    State(state): State<AppState>,
+    Query(query): Query<ImportUsersQuery>,
+    body: String,
+) -> Result<(StatusCode, Json<UserImportReport>), StatusCode> {
+    let caller = authorize_action(&state, &headers, permissions::CREATE_USER).await?;
+
+    let (mut report, rows) = user_import_service::validate_import(&state.db, &body, query.dry_run)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("User import rejected: {}", msg);
+                StatusCode::BAD_REQUEST
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    if query.dry_run {
+        return Ok((StatusCode::OK, Json(report)));
+    }
+    if report.invalid_rows > 0 {
+        return Ok((StatusCode::BAD_REQUEST, Json(report)));
+    }
+
+    // Same cost as create_user. Rows without a password get a placeholder until
+    // the invitee chooses one. Hashing is bounded so a large file doesn't take
+    // over the blocking pool.
+    let secrets: Vec<String> = rows
+        .iter()
+        .map(|validated| match validated.row.password.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+            Some(password) => password.to_string(),
+            None => thread_rng().sample_iter(&Alphanumeric).take(32).map(char::from).collect(),
+        })
+        .collect();
+    let hashes = stream::iter(secrets)
+        .map(|secret| tokio::task::spawn_blocking(move || hash(secret, DEFAULT_COST)))
+        .buffered(IMPORT_HASH_CONCURRENCY)
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .map(|joined| joined.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let imported = user_import_service::commit_import(&state.db, &rows, &hashes, caller.id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Protocol(msg) => {
+                eprintln!("User import aborted: {}", msg);
+                StatusCode::CONFLICT
+            }
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+    report.committed = true;
+
+    for (issued, validated) in imported.iter().zip(&rows) {
+        if let Some((invite, token)) = issued {
+            send_invite(&state, &validated.row.username, &validated.row.email, token, invite.expires_at).await;
+        }
+    }
+
+    let _ = crate::services::notification_service::create_notification(
+        &state.db,
+        &state.notifications,
+        "USERS_IMPORTED",
+        &format!("{} users imported", imported.len()),
+        Some(caller.id),
+    ).await;
+
+    Ok((StatusCode::CREATED, Json(report)))
+}
@@ -1,19 +1,36 @@
-use axum::{ extract::{ ws::{ WebSocket, WebSocketUpgrade }, State }, response::IntoResponse };
+use std::collections::BTreeMap;
+
+use axum::{ extract::{ ws::{ WebSocket, WebSocketUpgrade }, State }, http::HeaderMap, response::IntoResponse };
 use futures_util::StreamExt;
 
+use crate::services::notification_service;
 use crate::state::app_state::AppState;
+use crate::utils::auth::get_user_id_from_headers;
 
 pub async fn ws_notifications(
     ws: WebSocketUpgrade,
+    headers: HeaderMap,
     State(state): State<AppState>
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    // Signed-in users only get the topics they haven't switched off, as they
+    // were when the socket opened
+    let preferences = match get_user_id_from_headers(&state, &headers).await {
+        Ok(user_id) => notification_service::get_preferences(&state.db, user_id).await.unwrap_or_else(|e| {
+            eprintln!("Failed to load notification preferences: {:?}", e);
+            BTreeMap::new()
+        }),
+        Err(_) => BTreeMap::new(),
+    };
+    ws.on_upgrade(move |socket| handle_socket(socket, state, preferences))
 }
 
-async fn handle_socket(mut socket: WebSocket, state: AppState) {
+async fn handle_socket(mut socket: WebSocket, state: AppState, preferences: BTreeMap<String, bool>) {
     // Send historical notifications first
     if let Ok(notifications) = crate::services::notification_service::get_recent_notifications(&state.db, 50).await {
         for notification in notifications {
+            if !notification_service::wants(&preferences, &notification.event_type) {
+                continue;
+            }
             let msg = serde_json::to_string(&notification).unwrap();
             if socket.send(msg.into()).await.is_err() {
                 return;
@@ -26,6 +43,9 @@ async fn handle_socket(mut socket: WebSocket, state: AppState) {
     loop {
         tokio::select! {
     Ok(event) = rx.recv() => {
+        if !notification_service::wants(&preferences, &event.event_type) {
+            continue;
+        }
         let msg = serde_json::to_string(&event).unwrap();
         if socket.send(msg.into()).await.is_err() {
             break;
@@ -149,6 +149,8 @@
 // }
 
 
+use std::net::SocketAddr;
+
 use tokio::net::TcpListener;
 
 mod app;
@@ -167,7 +169,15 @@ mod events;
 async fn main() {
     config::env::load();
 
+    if let Err(problems) = utils::permissions::verify_registry() {
+        for problem in &problems {
+            eprintln!("Permission registry: {}", problem);
+        }
+        panic!("Permission registry check failed");
+    }
+
     let state = state::app_state::AppState::new().await;
+    tokio::spawn(services::leave_balance_service::run_daily_accruals(state.db.clone()));
     let app = app::create_app(state);
 
     let listener = TcpListener::bind("0.0.0.0:8000")
@@ -175,5 +185,7 @@ async fn main() {
         .expect("Failed to bind port");
 
     println!("🚀 Server running on http://localhost:8000");
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .unwrap();
 }
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{NaiveDate, NaiveDateTime};
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct AccessReviewCampaign {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub policy_id: Option<Uuid>,
+    pub role_id: Option<Uuid>,
+    pub status: String,
+    pub due_on: NaiveDate,
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub closed_by: Option<Uuid>,
+    pub closed_at: Option<NaiveDateTime>,
+}
+
+/// One snapshotted binding awaiting a keep or revoke decision.
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct AccessReviewItem {
+    pub id: Uuid,
+    pub campaign_id: Uuid,
+    pub binding_id: Uuid,
+    pub policy_id: Uuid,
+    pub policy_name: String,
+    pub subject_type: String,
+    pub subject_id: Uuid,
+    pub subject_name: String,
+    pub reviewer_id: Option<Uuid>,
+    pub decision: Option<String>,
+    pub comment: Option<String>,
+    pub decided_at: Option<NaiveDateTime>,
+    pub applied_at: Option<NaiveDateTime>,
+    pub apply_error: Option<String>,
+    #[sqlx(default)]
+    pub reviewer_name: Option<String>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AccessReviewProgress {
+    pub total: usize,
+    pub kept: usize,
+    pub revoked: usize,
+    pub pending: usize,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct AccessReviewCampaignWithItems {
+    #[serde(flatten)]
+    pub campaign: AccessReviewCampaign,
+    pub progress: AccessReviewProgress,
+    pub items: Vec<AccessReviewItem>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateAccessReviewPayload {
+    pub name: String,
+    pub description: Option<String>,
+    /// Only review bindings of this policy
+    pub policy_id: Option<Uuid>,
+    /// Only review bindings reaching users of this role
+    pub role_id: Option<Uuid>,
+    /// Reviews items that have no manager to review them
+    pub default_reviewer_id: Uuid,
+    pub due_on: NaiveDate,
+}
+
+#[derive(Deserialize)]
+pub struct AccessReviewDecisionPayload {
+    pub decision: String,
+    pub comment: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ReassignReviewerPayload {
+    pub reviewer_id: Uuid,
+}
+
+#[derive(Deserialize)]
+pub struct EvidenceQuery {
+    /// "json" (default) or "csv"
+    pub format: Option<String>,
+}
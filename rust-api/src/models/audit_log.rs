@@ -0,0 +1,18 @@
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::NaiveDateTime;
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct AuditLogEntry {
+    pub id: Uuid,
+    pub user_id: Option<Uuid>,
+    pub action: String,
+    pub resource: String,
+    pub allowed: bool,
+    pub reason: String,
+    pub policy_id: Option<Uuid>,
+    pub break_glass_activation_id: Option<Uuid>,
+    pub on_behalf_of: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,20 @@
+use std::collections::BTreeMap;
+
+use uuid::Uuid;
+
+/// Thumbnail names and their edge length in pixels. Every upload is stored in
+/// each size as a square JPEG.
+pub const AVATAR_SIZES: &[(&str, u32)] = &[("small", 48), ("medium", 128), ("large", 512)];
+
+/// Storage key of one thumbnail.
+pub fn avatar_key(user_id: Uuid, version: &str, size: &str) -> String {
+    format!("avatars/{}/{}/{}.jpg", user_id, version, size)
+}
+
+/// URL of every thumbnail by size name.
+pub fn avatar_urls(user_id: Uuid, version: &str) -> BTreeMap<String, String> {
+    AVATAR_SIZES
+        .iter()
+        .map(|(size, _)| (size.to_string(), format!("/api/avatars/{}/{}/{}.jpg", user_id, version, size)))
+        .collect()
+}
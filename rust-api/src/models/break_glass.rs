@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::NaiveDateTime;
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct BreakGlassGrant {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub policy_id: Uuid,
+    pub max_duration_minutes: i32,
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct BreakGlassActivation {
+    pub id: Uuid,
+    pub grant_id: Uuid,
+    pub user_id: Uuid,
+    pub justification: String,
+    pub activated_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub ended_at: Option<NaiveDateTime>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CreateBreakGlassGrantPayload {
+    pub user_id: Uuid,
+    pub policy_id: Uuid,
+    pub max_duration_minutes: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct ActivateBreakGlassPayload {
+    pub grant_id: Uuid,
+    pub justification: String,
+    pub duration_minutes: Option<i32>,
+}
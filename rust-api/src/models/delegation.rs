@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::{NaiveDate, NaiveDateTime};
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct PermissionDelegation {
+    pub id: Uuid,
+    pub delegator_id: Uuid,
+    pub delegate_id: Uuid,
+    pub action: String,
+    pub resource: String,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DelegatedPermission {
+    pub action: String,
+    pub resource: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateDelegationPayload {
+    pub delegate_id: Uuid,
+    pub permissions: Vec<DelegatedPermission>,
+    pub starts_on: NaiveDate,
+    pub ends_on: NaiveDate,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MyDelegations {
+    pub given: Vec<PermissionDelegation>,
+    pub received: Vec<PermissionDelegation>,
+}
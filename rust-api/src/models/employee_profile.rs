@@ -0,0 +1,43 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// HR profile of a user. Stored on `users`; every user has one, possibly empty.
+#[derive(Serialize, FromRow, Debug, Clone, PartialEq)]
+pub struct EmployeeProfile {
+    pub user_id: Uuid,
+    pub department: Option<String>,
+    pub location: Option<String>,
+    pub job_title: Option<String>,
+    pub manager_id: Option<Uuid>,
+    pub manager_name: Option<String>,
+    pub hire_date: Option<NaiveDate>,
+    /// "full_time", "part_time", "contractor" or "intern"
+    pub employment_type: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// Replaces the whole profile; omitted fields are cleared.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct UpdateEmployeeProfilePayload {
+    pub department: Option<String>,
+    pub location: Option<String>,
+    pub job_title: Option<String>,
+    pub manager_id: Option<Uuid>,
+    pub hire_date: Option<NaiveDate>,
+    pub employment_type: Option<String>,
+    pub phone: Option<String>,
+}
+
+/// One changed field. Values are stored as text; `None` means the field was empty.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct EmployeeProfileChange {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub field: String,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_by: Option<Uuid>,
+    pub changed_at: NaiveDateTime,
+}
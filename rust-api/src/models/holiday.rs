@@ -0,0 +1,126 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Weekend days and public holidays of one or more locations.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct HolidayCalendar {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Matched case-insensitively against `users.location`
+    pub locations: Vec<String>,
+    /// ISO weekdays, 1 = Monday to 7 = Sunday
+    pub weekend_days: Vec<i32>,
+    /// Used by users whose location no calendar lists
+    pub is_default: bool,
+    pub holiday_count: i64,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Used for both create and update; an update replaces every setting.
+#[derive(Deserialize, Debug, Clone)]
+pub struct HolidayCalendarPayload {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub locations: Vec<String>,
+    /// Defaults to Saturday and Sunday
+    pub weekend_days: Option<Vec<i32>>,
+    /// Making a calendar the default takes it from the previous one
+    #[serde(default)]
+    pub is_default: bool,
+}
+
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct Holiday {
+    pub id: Uuid,
+    pub calendar_id: Uuid,
+    pub holiday_date: NaiveDate,
+    pub name: String,
+    /// UID of the iCalendar event it was imported from
+    pub source_uid: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct HolidayPayload {
+    pub holiday_date: NaiveDate,
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HolidayQuery {
+    /// Only holidays of this year; all when omitted
+    pub year: Option<i32>,
+}
+
+/// Query string of `POST /api/admin/holiday-calendars/{id}/import`.
+#[derive(Deserialize, Debug, Default)]
+pub struct HolidayImportQuery {
+    /// Parse and report without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Also remove the calendar's holidays the file doesn't have
+    #[serde(default)]
+    pub replace: bool,
+}
+
+/// A day off read from an iCalendar event. Events spanning several days give one per day.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ImportedHoliday {
+    pub holiday_date: NaiveDate,
+    pub name: String,
+    pub uid: Option<String>,
+}
+
+/// An event that gave no holiday. `line` is the line of its `BEGIN:VEVENT` in the file.
+#[derive(Serialize, Debug, Clone)]
+pub struct SkippedEvent {
+    pub line: usize,
+    pub summary: Option<String>,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct HolidayImportReport {
+    pub dry_run: bool,
+    /// Whether the holidays were written. Never true for a dry run.
+    pub committed: bool,
+    pub events: usize,
+    pub created: usize,
+    /// Existing dates given a new name
+    pub updated: usize,
+    pub unchanged: usize,
+    /// Existing dates missing from the file; only removed with `replace`
+    pub removed: usize,
+    pub holidays: Vec<ImportedHoliday>,
+    pub skipped: Vec<SkippedEvent>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WorkingDaysQuery {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Whose calendar to use; defaults to the caller
+    pub user_id: Option<Uuid>,
+}
+
+/// How many days a leave request over these dates would take.
+#[derive(Serialize, Debug, Clone)]
+pub struct WorkingDays {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// `None` when no calendar applies and only Saturday and Sunday are skipped
+    pub calendar_id: Option<Uuid>,
+    pub calendar: Option<String>,
+    pub calendar_days: i64,
+    /// Weekend days in the range
+    pub weekend_days: i64,
+    /// Holidays in the range falling on working weekdays
+    pub holidays: Vec<Holiday>,
+    /// What the request takes from a balance
+    pub days: f64,
+}
@@ -0,0 +1,38 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Invite metadata. The token itself is only ever sent to the invitee.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct UserInvite {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub expires_at: NaiveDateTime,
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub accepted_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+/// What the invitee sees before choosing a password.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct InviteDetails {
+    pub username: String,
+    pub email: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct AcceptInvitePayload {
+    pub password: String,
+}
+
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct OutboxMail {
+    pub id: Uuid,
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,71 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A user's balance of one leave type that keeps one.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct LeaveBalance {
+    pub leave_type_id: Uuid,
+    pub leave_type: String,
+    pub accrual_method: String,
+    /// Sum of the ledger, including approved leave that hasn't started yet
+    pub balance: f64,
+    /// Days of requests still waiting for approval
+    pub pending_days: f64,
+    /// `balance - pending_days`, what a new request can take
+    pub available: f64,
+    pub allow_negative: bool,
+    pub accrued_through: Option<NaiveDate>,
+}
+
+/// One movement of a balance.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct LeaveLedgerEntry {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub leave_type_id: Uuid,
+    pub leave_type: String,
+    /// "accrual", "grant", "debit", "credit", "forfeit", "expiry" or "adjustment"
+    pub entry_type: String,
+    /// Positive adds to the balance, negative takes from it
+    pub days: f64,
+    pub effective_date: NaiveDate,
+    /// Accrual period ("2026" or "2026-03") of entries posted by the accrual engine
+    pub period: Option<String>,
+    pub leave_request_id: Option<Uuid>,
+    pub note: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LedgerQuery {
+    /// Name of the leave type; all types when omitted
+    pub leave_type: Option<String>,
+}
+
+/// Manual correction, e.g. an opening balance. `days` may be negative.
+#[derive(Deserialize, Debug)]
+pub struct LeaveAdjustmentPayload {
+    pub leave_type: String,
+    pub days: f64,
+    /// Defaults to today
+    pub effective_date: Option<NaiveDate>,
+    pub note: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct AccrualRunPayload {
+    /// Post accruals up to this day instead of today; can't be in the future
+    pub as_of: Option<NaiveDate>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AccrualRunResult {
+    pub as_of: NaiveDate,
+    /// User and leave type pairs brought up to date
+    pub balances: usize,
+    /// Ledger entries posted
+    pub entries: u64,
+}
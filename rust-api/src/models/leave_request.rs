@@ -3,15 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::{NaiveDate, NaiveDateTime};
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
-#[sqlx(type_name = "VARCHAR")]
-#[sqlx(rename_all = "PascalCase")]
-pub enum LeaveType {
-    Casual,
-    Sick,
-    Unpaid,
-}
-
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "VARCHAR")]
 #[sqlx(rename_all = "PascalCase")]
@@ -25,12 +17,17 @@ pub enum LeaveStatus {
 pub struct LeaveRequest {
     pub id: Uuid,
     pub user_id: Uuid,
+    /// Name of the leave type
     pub leave_type: String,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+    /// Working days taken from the balance
+    pub days: f64,
     pub reason: String,
     pub status: String,
     pub approved_by: Option<Uuid>,
+    pub approved_on_behalf_of: Option<Uuid>,
+    pub attachment_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -44,19 +41,27 @@ pub struct LeaveRequestWithUser {
     pub leave_type: String,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+    pub days: f64,
     pub reason: String,
     pub status: String,
     pub approved_by: Option<Uuid>,
+    pub approved_on_behalf_of: Option<Uuid>,
+    pub attachment_id: Option<Uuid>,
+    /// Requester's current balance of the type; `None` if it keeps none
+    pub balance: Option<f64>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CreateLeaveRequestPayload {
+    /// Name of an active leave type the requester is eligible for
     pub leave_type: String,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
     pub reason: String,
+    /// From `POST /api/leave-requests/attachments`; required by some types
+    pub attachment_id: Option<Uuid>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -71,10 +76,14 @@ pub struct LeaveRequestWithApprover {
     pub leave_type: String,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+    pub days: f64,
     pub reason: String,
     pub status: String,
     pub approved_by: Option<Uuid>,
     pub approver_name: Option<String>,
+    pub approved_on_behalf_of: Option<Uuid>,
+    pub on_behalf_of_name: Option<String>,
+    pub attachment_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
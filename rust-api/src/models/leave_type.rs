@@ -0,0 +1,94 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A kind of leave employees can request, with the rules checked on submission.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct LeaveType {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub paid: bool,
+    pub requires_attachment: bool,
+    /// Days between submitting and the first day of leave
+    pub min_notice_days: i32,
+    pub max_consecutive_days: Option<i32>,
+    /// `None`: open to every role
+    pub eligible_role_ids: Option<Vec<Uuid>>,
+    /// `None`: open to every employment type
+    pub eligible_employment_types: Option<Vec<String>>,
+    pub active: bool,
+    /// "none" (no balance is kept), "monthly" or "annual"
+    pub accrual_method: String,
+    /// Yearly entitlement, required unless `accrual_method` is "none"
+    pub annual_days: Option<f64>,
+    /// Most days carried into a new year; `None`: no limit
+    pub carry_over_cap: Option<f64>,
+    /// Months into the new year before carried days expire; `None`: never
+    pub carry_over_expiry_months: Option<i32>,
+    /// Requests may take the balance below zero
+    pub allow_negative: bool,
+    /// No accrual is posted for periods before this date
+    pub accrual_start: NaiveDate,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl LeaveType {
+    /// Whether a balance is kept for this type.
+    pub fn tracks_balance(&self) -> bool {
+        self.accrual_method != "none"
+    }
+
+    pub fn is_eligible(&self, role_id: Option<Uuid>, employment_type: Option<&str>) -> bool {
+        let role_ok = self
+            .eligible_role_ids
+            .as_ref()
+            .is_none_or(|roles| role_id.is_some_and(|r| roles.contains(&r)));
+        let employment_ok = self
+            .eligible_employment_types
+            .as_ref()
+            .is_none_or(|types| employment_type.is_some_and(|t| types.iter().any(|e| e == t)));
+        role_ok && employment_ok
+    }
+}
+
+/// Used for both create and update; an update replaces every setting.
+#[derive(Deserialize, Debug, Clone)]
+pub struct LeaveTypePayload {
+    pub name: String,
+    pub description: Option<String>,
+    pub paid: bool,
+    #[serde(default)]
+    pub requires_attachment: bool,
+    #[serde(default)]
+    pub min_notice_days: i32,
+    pub max_consecutive_days: Option<i32>,
+    pub eligible_role_ids: Option<Vec<Uuid>>,
+    pub eligible_employment_types: Option<Vec<String>>,
+    /// Defaults to active
+    pub active: Option<bool>,
+    /// Defaults to "none"
+    pub accrual_method: Option<String>,
+    pub annual_days: Option<f64>,
+    pub carry_over_cap: Option<f64>,
+    pub carry_over_expiry_months: Option<i32>,
+    #[serde(default)]
+    pub allow_negative: bool,
+    /// Defaults to 1 January of the current year on create, and is kept on update
+    pub accrual_start: Option<NaiveDate>,
+}
+
+/// A supporting document for a leave request.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct LeaveAttachment {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// `None` until a request is submitted with it
+    pub leave_request_id: Option<Uuid>,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i32,
+    pub created_at: NaiveDateTime,
+}
@@ -3,4 +3,25 @@ pub mod user_role;
 pub mod leave_request;
 pub mod report;
 pub mod payslip;
-pub mod payslip_template;
\ No newline at end of file
+pub mod payslip_template;
+pub mod policy_test;
+
+pub mod policy_impact;
+pub mod policy_change;
+pub mod break_glass;
+pub mod audit_log;
+pub mod delegation;
+pub mod row_filter;
+pub mod record_share;
+pub mod access_review;
+pub mod user_response;
+pub mod user_import;
+pub mod employee_profile;
+pub mod org;
+pub mod invite;
+pub mod user_attribute;
+pub mod self_profile;
+pub mod avatar;
+pub mod leave_type;
+pub mod leave_balance;
+pub mod holiday;
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A user's place in the reporting structure. `depth` is the distance from the
+/// user the query started at: 1 for a direct report or direct manager.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct OrgMember {
+    pub id: Uuid,
+    pub username: String,
+    pub job_title: Option<String>,
+    pub department: Option<String>,
+    pub manager_id: Option<Uuid>,
+    pub status: String,
+    pub depth: i32,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct OrgChartNode {
+    pub id: Uuid,
+    pub username: String,
+    pub job_title: Option<String>,
+    pub department: Option<String>,
+    pub status: String,
+    pub reports: Vec<OrgChartNode>,
+}
+
+/// Query string of `GET /api/org/chart`.
+#[derive(Deserialize, Debug, Default)]
+pub struct OrgChartQuery {
+    /// Start from this user; without it the chart starts at everyone with no manager
+    pub root: Option<Uuid>,
+    /// Levels below the root, 3 by default
+    pub depth: Option<i32>,
+}
+
+/// Query string of `GET /api/users/{id}/reports`.
+#[derive(Deserialize, Debug, Default)]
+pub struct ReportsQuery {
+    /// Include reports of reports, all the way down
+    #[serde(default)]
+    pub transitive: bool,
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::NaiveDateTime;
+
+/// A policy change that needs a second approver when four-eyes mode is on.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "change_type", rename_all = "snake_case")]
+pub enum PolicyChange {
+    Activate { policy_id: Uuid },
+    Archive { policy_id: Uuid },
+    Delete { policy_id: Uuid },
+    Bind { policy_id: Uuid, subject_type: String, subject_id: Uuid },
+    Unbind { policy_id: Uuid, binding_id: Uuid },
+    /// A break-glass grant on the policy, created by `granted_by` once approved
+    GrantBreakGlass { policy_id: Uuid, granted_by: Uuid, user_id: Uuid, max_duration_minutes: Option<i32> },
+}
+
+impl PolicyChange {
+    pub fn change_type(&self) -> &'static str {
+        match self {
+            Self::Activate { .. } => "activate",
+            Self::Archive { .. } => "archive",
+            Self::Delete { .. } => "delete",
+            Self::Bind { .. } => "bind",
+            Self::Unbind { .. } => "unbind",
+            Self::GrantBreakGlass { .. } => "grant_break_glass",
+        }
+    }
+
+    pub fn policy_id(&self) -> Uuid {
+        match self {
+            Self::Activate { policy_id }
+            | Self::Archive { policy_id }
+            | Self::Delete { policy_id }
+            | Self::Bind { policy_id, .. }
+            | Self::Unbind { policy_id, .. }
+            | Self::GrantBreakGlass { policy_id, .. } => *policy_id,
+        }
+    }
+}
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct PolicyChangeRequest {
+    pub id: Uuid,
+    /// `None` once the policy has been deleted; the payload still names it
+    pub policy_id: Option<Uuid>,
+    pub change_type: String,
+    pub payload: serde_json::Value,
+    pub status: String, // "pending", "approved", "rejected" or "failed"
+    pub proposed_by: Option<Uuid>,
+    pub decided_by: Option<Uuid>,
+    pub decision_comment: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub decided_at: Option<NaiveDateTime>,
+}
+
+impl PolicyChangeRequest {
+    pub fn change(&self) -> serde_json::Result<PolicyChange> {
+        serde_json::from_value(self.payload.clone())
+    }
+}
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct PolicyChangeEvent {
+    pub id: Uuid,
+    pub change_request_id: Uuid,
+    pub event: String,
+    pub actor_id: Option<Uuid>,
+    pub comment: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PolicyChangeRequestWithHistory {
+    #[serde(flatten)]
+    pub request: PolicyChangeRequest,
+    pub history: Vec<PolicyChangeEvent>,
+}
+
+#[derive(Deserialize)]
+pub struct ListPolicyChangesQuery {
+    pub status: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct PolicyChangeDecisionPayload {
+    pub comment: Option<String>,
+}
@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utils::permissions::Permission;
+
+/// Hypothetical change to evaluate before it is made.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(tag = "scenario", rename_all = "snake_case")]
+pub enum ImpactScenario {
+    ActivateDraft { policy_id: Uuid },
+    ArchivePolicy { policy_id: Uuid },
+    RemoveBinding { binding_id: Uuid },
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UserImpact {
+    pub user_id: Uuid,
+    pub username: String,
+    pub gained: Vec<Permission>,
+    pub lost: Vec<Permission>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImpactReport {
+    pub scenario: ImpactScenario,
+    pub policy_id: Uuid,
+    pub evaluated_users: usize,
+    pub changed_users: usize,
+    pub users: Vec<UserImpact>,
+}
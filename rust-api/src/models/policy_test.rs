@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::NaiveDateTime;
+
+use crate::models::user_role::AuthContext;
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct PolicyTestCase {
+    pub id: Uuid,
+    pub policy_id: Uuid,
+    pub name: String,
+    pub subject_type: String, // "role" or "user"
+    pub subject_id: Uuid,
+    pub action: String,
+    pub resource: String,
+    pub context: Option<serde_json::Value>,
+    pub expected_effect: String, // "allow" or "deny"
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct CreatePolicyTestCasePayload {
+    pub name: String,
+    pub subject_type: String,
+    pub subject_id: Uuid,
+    pub action: String,
+    pub resource: String,
+    pub context: Option<AuthContext>,
+    pub expected_effect: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PolicyTestCaseResult {
+    pub test_case_id: Uuid,
+    pub name: String,
+    pub expected_effect: String,
+    pub actual_effect: String,
+    pub passed: bool,
+    pub reason: String,
+    pub policy_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PolicyTestSuiteResult {
+    pub policy_id: Uuid,
+    pub passed: bool,
+    pub total: usize,
+    pub failed: usize,
+    pub results: Vec<PolicyTestCaseResult>,
+}
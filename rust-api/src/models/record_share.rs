@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use chrono::NaiveDateTime;
+
+#[derive(Serialize, FromRow, Clone, Debug)]
+pub struct RecordShare {
+    pub id: Uuid,
+    pub resource: String,
+    pub resource_id: Uuid,
+    pub grantee_id: Uuid,
+    pub action: String,
+    pub expires_at: Option<NaiveDateTime>,
+    pub granted_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub revoked_at: Option<NaiveDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateRecordSharePayload {
+    pub grantee_id: Uuid,
+    pub action: String,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// One line of the "who has access" view of a record.
+#[derive(Serialize, Clone, Debug)]
+pub struct RecordAccessEntry {
+    pub user_id: Uuid,
+    pub username: String,
+    pub action: String,
+    /// "owner", "share" or "policy"
+    pub source: String,
+    pub share_id: Option<Uuid>,
+    pub policy_id: Option<Uuid>,
+    pub expires_at: Option<NaiveDateTime>,
+}
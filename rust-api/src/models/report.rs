@@ -3,6 +3,7 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::NaiveDateTime;
 
+#[allow(dead_code)]
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "VARCHAR")]
 #[sqlx(rename_all = "PascalCase")]
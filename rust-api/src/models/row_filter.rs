@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::models::user_role::AuthContext;
+
+/// The `conditions` of a policy rule. Every condition that is set must hold.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConditions {
+    /// "self" for the subject's own rows, "reports" for rows of anyone below them
+    /// in the management chain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// "mine" for the subject's own department, otherwise a department name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub department: Option<String>,
+    /// Attributes the request context must carry, checked on every decision
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<ContextConditions>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ContextConditions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub department: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Provider attributes by name, e.g. {"request.ip": "10.0.0.1"}
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+}
+
+impl RuleConditions {
+    pub fn parse(value: &serde_json::Value) -> Result<Self, String> {
+        let conditions: Self = serde_json::from_value(value.clone())
+            .map_err(|e| format!("Invalid rule conditions: {}", e))?;
+
+        if let Some(owner) = &conditions.owner
+            && !["self", "reports"].contains(&owner.as_str())
+        {
+            return Err(format!("Unknown owner condition '{}'", owner));
+        }
+        if conditions.department.as_deref().is_some_and(|d| d.trim().is_empty()) {
+            return Err("Department condition cannot be empty".into());
+        }
+
+        Ok(conditions)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the request context satisfies the `context` conditions.
+    pub fn context_holds(&self, context: &AuthContext) -> bool {
+        let Some(expected) = &self.context else {
+            return true;
+        };
+        let attribute_holds = |want: &Option<String>, have: &Option<String>| match want {
+            Some(want) => have.as_deref() == Some(want.as_str()),
+            None => true,
+        };
+        let provided_holds = |name: &String, want: &String| match context.attributes.get(name) {
+            Some(serde_json::Value::String(have)) => have == want,
+            Some(serde_json::Value::Null) | None => false,
+            Some(other) => want.eq(&other.to_string()),
+        };
+        attribute_holds(&expected.department, &context.department)
+            && attribute_holds(&expected.location, &context.location)
+            && expected.attributes.iter().all(|(name, want)| provided_holds(name, want))
+    }
+
+    /// Whether the rule is limited to some records by `owner` or `department`.
+    pub fn is_scoped(&self) -> bool {
+        self.owner.is_some() || self.department.is_some()
+    }
+
+    /// Whether one record is among the rows `scope(subject_id)` describes.
+    /// Records without a known owner are outside every scope.
+    pub fn record_in_scope(&self, subject_id: Uuid, record: &RecordScope) -> bool {
+        let Some(owner_id) = record.owner_id else {
+            return !self.is_scoped();
+        };
+        let owner_holds = match self.owner.as_deref() {
+            Some("self") => owner_id == subject_id,
+            Some("reports") => record.managers.contains(&subject_id),
+            Some(_) => false,
+            None => true,
+        };
+        let department_holds = match self.department.as_deref() {
+            Some("mine") => record
+                .departments
+                .get(&subject_id)
+                .is_some_and(|d| record.owner_department.as_ref() == Some(d)),
+            Some(name) => record.owner_department.as_deref() == Some(name),
+            None => true,
+        };
+        owner_holds && department_holds
+    }
+
+    /// The rows this rule applies to when `subject_id` is asking.
+    pub fn scope(&self, subject_id: Uuid) -> RowFilter {
+        let mut parts = Vec::new();
+        match self.owner.as_deref() {
+            Some("self") => parts.push(RowFilter::OwnerIs(subject_id)),
+            Some("reports") => parts.push(RowFilter::OwnerReportsTo(subject_id)),
+            _ => {}
+        }
+        match self.department.as_deref() {
+            Some("mine") => parts.push(RowFilter::OwnerInDepartmentOf(subject_id)),
+            Some(name) => parts.push(RowFilter::OwnerInDepartment(name.to_string())),
+            None => {}
+        }
+        RowFilter::and(parts)
+    }
+}
+
+/// What owner and department scopes are checked against when a decision is
+/// about one record. Subjects are the user asking and any delegator whose
+/// rules reach them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecordScope {
+    /// The user asking
+    pub subject_id: Uuid,
+    /// `None` when the record has no owner or doesn't exist
+    pub owner_id: Option<Uuid>,
+    pub owner_department: Option<String>,
+    /// Subjects with the owner somewhere below them in the management chain
+    pub managers: Vec<Uuid>,
+    /// Department of each subject that has one
+    pub departments: HashMap<Uuid, String>,
+}
+
+/// Result of partially evaluating the rules for a resource type: which rows the
+/// subject may see, expressed over the user that owns each row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowFilter {
+    All,
+    Nothing,
+    OwnerIs(Uuid),
+    OwnerReportsTo(Uuid),
+    OwnerInDepartmentOf(Uuid),
+    OwnerInDepartment(String),
+    Not(Box<RowFilter>),
+    And(Vec<RowFilter>),
+    Or(Vec<RowFilter>),
+}
+
+impl RowFilter {
+    pub fn and(parts: Vec<RowFilter>) -> RowFilter {
+        let mut kept = Vec::new();
+        for part in parts {
+            match part {
+                RowFilter::All => {}
+                RowFilter::Nothing => return RowFilter::Nothing,
+                other => kept.push(other),
+            }
+        }
+        match kept.len() {
+            0 => RowFilter::All,
+            1 => kept.remove(0),
+            _ => RowFilter::And(kept),
+        }
+    }
+
+    pub fn or(parts: Vec<RowFilter>) -> RowFilter {
+        let mut kept = Vec::new();
+        for part in parts {
+            match part {
+                RowFilter::Nothing => {}
+                RowFilter::All => return RowFilter::All,
+                other => kept.push(other),
+            }
+        }
+        match kept.len() {
+            0 => RowFilter::Nothing,
+            1 => kept.remove(0),
+            _ => RowFilter::Or(kept),
+        }
+    }
+
+    pub fn not(inner: RowFilter) -> RowFilter {
+        match inner {
+            RowFilter::All => RowFilter::Nothing,
+            RowFilter::Nothing => RowFilter::All,
+            RowFilter::Not(inner) => *inner,
+            other => RowFilter::Not(Box::new(other)),
+        }
+    }
+
+    /// Appends the predicate to a query. `owner_column` is the column holding the
+    /// id of the user that owns each row, e.g. `l.user_id`.
+    pub fn push_sql(&self, qb: &mut QueryBuilder<'_, Postgres>, owner_column: &str) {
+        match self {
+            RowFilter::All => {
+                qb.push("TRUE");
+            }
+            RowFilter::Nothing => {
+                qb.push("FALSE");
+            }
+            RowFilter::OwnerIs(id) => {
+                qb.push(owner_column).push(" = ").push_bind(*id);
+            }
+            RowFilter::OwnerReportsTo(id) => {
+                // Everyone below the subject; UNION stops on reporting loops
+                qb.push(owner_column)
+                    .push(" IN (WITH RECURSIVE below AS (SELECT id FROM users WHERE manager_id = ")
+                    .push_bind(*id)
+                    .push(" UNION SELECT u.id FROM users u JOIN below b ON u.manager_id = b.id) SELECT id FROM below)");
+            }
+            RowFilter::OwnerInDepartmentOf(id) => {
+                qb.push(owner_column)
+                    .push(" IN (SELECT o.id FROM users o JOIN users s ON s.department = o.department WHERE s.id = ")
+                    .push_bind(*id)
+                    .push(")");
+            }
+            RowFilter::OwnerInDepartment(name) => {
+                qb.push(owner_column)
+                    .push(" IN (SELECT id FROM users WHERE department = ")
+                    .push_bind(name.clone())
+                    .push(")");
+            }
+            RowFilter::Not(inner) => {
+                qb.push("NOT (");
+                inner.push_sql(qb, owner_column);
+                qb.push(")");
+            }
+            RowFilter::And(parts) | RowFilter::Or(parts) => {
+                let joiner = if matches!(self, RowFilter::And(_)) { " AND " } else { " OR " };
+                qb.push("(");
+                for (i, part) in parts.iter().enumerate() {
+                    if i > 0 {
+                        qb.push(joiner);
+                    }
+                    part.push_sql(qb, owner_column);
+                }
+                qb.push(")");
+            }
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EmergencyContact {
+    pub name: String,
+    pub relationship: Option<String>,
+    pub phone: String,
+}
+
+/// The fields of `GET /api/me/profile`, as stored on `users`.
+#[derive(FromRow, Debug, Clone)]
+pub struct SelfProfileRow {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub emergency_contacts: Json<Vec<EmergencyContact>>,
+    pub notification_preferences: Json<BTreeMap<String, bool>>,
+    pub avatar_version: Option<String>,
+}
+
+/// An email change waiting for the new address to be confirmed.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct PendingEmailChange {
+    pub new_email: String,
+    pub expires_at: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SelfProfile {
+    pub user_id: Uuid,
+    pub username: String,
+    pub email: String,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    /// Thumbnail URLs by size; upload through `PUT /api/me/avatar`
+    pub avatar_urls: Option<BTreeMap<String, String>>,
+    pub emergency_contacts: Vec<EmergencyContact>,
+    pub notification_preferences: BTreeMap<String, bool>,
+    pub pending_email: Option<PendingEmailChange>,
+    /// Fields this deployment lets users change themselves
+    pub editable_fields: Vec<String>,
+}
+
+/// Partial update: only the fields present change. Blank text clears a field.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct UpdateSelfProfilePayload {
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub emergency_contacts: Option<Vec<EmergencyContact>>,
+    /// Merged into the stored preferences
+    pub notification_preferences: Option<BTreeMap<String, bool>>,
+}
+
+impl UpdateSelfProfilePayload {
+    /// Names of the fields this update touches.
+    pub fn fields(&self) -> Vec<&'static str> {
+        [
+            ("display_name", self.display_name.is_some()),
+            ("phone", self.phone.is_some()),
+            ("emergency_contacts", self.emergency_contacts.is_some()),
+            ("notification_preferences", self.notification_preferences.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, present)| present.then_some(name))
+        .collect()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ChangeEmailPayload {
+    pub new_email: String,
+    /// Re-authentication before a sensitive change
+    pub current_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmEmailPayload {
+    pub token: String,
+}
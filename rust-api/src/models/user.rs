@@ -3,43 +3,103 @@ use sqlx::FromRow;
 use uuid::Uuid;
 use chrono::NaiveDateTime;
 
+use crate::models::user_response::UserResponse;
+
+/// New users are invited and choose their own password.
 #[derive(Deserialize)]
 pub struct CreateUserPayload {
     pub username: String,
     pub email: String,
-    pub password_hash: String,
 }
 
-#[derive(Serialize, FromRow)]
+/// Database row. Not serializable on purpose; respond with `UserResponse`.
+#[derive(FromRow)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
     pub email: String,
     pub password_hash: String,
     pub role_id: Option<Uuid>,
+    /// Effective status: "active", "suspended" or "terminated"
+    pub status: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
-/// User with role name resolved from join
-#[derive(Serialize, FromRow)]
+/// User with role name resolved from join. Respond with `UserResponse`.
+#[derive(FromRow)]
 pub struct UserWithRole {
     pub id: Uuid,
     pub username: String,
     pub email: String,
-    pub password_hash: String,
     pub role_id: Option<Uuid>,
     pub role_name: Option<String>,
+    pub display_name: Option<String>,
+    /// Current avatar upload, see `models::avatar::avatar_urls`
+    pub avatar_version: Option<String>,
+    pub status: String,
+    /// Custom attribute values keyed by attribute name
+    pub attributes: serde_json::Value,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
 
+/// Query string of `GET /api/users` and `GET /api/users/export`.
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct ListUsersQuery {
+    /// Opaque cursor from the previous page's `next_cursor`
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+    /// "username" (default), "email", "created_at" or "role"
+    pub sort: Option<String>,
+    /// "asc" (default) or "desc"
+    pub order: Option<String>,
+    pub role_id: Option<Uuid>,
+    /// Case-insensitive match on username or email
+    pub search: Option<String>,
+    /// Effective status: "active", "suspended" or "terminated"
+    pub status: Option<String>,
+    /// Custom attribute values users must have. Built by the handler from
+    /// `attr.<name>=value` parameters, see `user_attribute_service::filter_from_query`.
+    #[serde(skip)]
+    pub attributes: Option<serde_json::Value>,
+}
+
+/// One page of users plus the number of users matching the filters.
+#[derive(Serialize)]
+pub struct UserPage {
+    pub items: Vec<UserResponse>,
+    pub total: i64,
+    pub next_cursor: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct UpdateUserPayload {
     pub username: String,
     pub email: String,
 }
 
+/// Entry in a user's status history. A change dated in the future is scheduled
+/// and takes effect on its own once `effective_at` passes.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct UserStatusChange {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: String,
+    pub effective_at: NaiveDateTime,
+    pub reason: Option<String>,
+    pub changed_by: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+pub struct ChangeUserStatusPayload {
+    pub status: String,
+    /// Defaults to now
+    pub effective_at: Option<NaiveDateTime>,
+    pub reason: Option<String>,
+}
+
 #[derive(Deserialize)]
 pub struct LoginPayload {
     pub identity: String, // username or email
@@ -0,0 +1,51 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Admin-defined user field. Values are stored in `users.attributes` under `name`.
+#[derive(Serialize, FromRow, Debug, Clone)]
+pub struct UserAttributeDefinition {
+    pub id: Uuid,
+    pub name: String,
+    pub label: String,
+    /// "string", "number", "date" or "enum"
+    pub data_type: String,
+    pub required: bool,
+    /// string only
+    pub max_length: Option<i32>,
+    /// number only, inclusive
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    /// enum only
+    pub options: Option<Vec<String>>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CreateUserAttributeDefinitionPayload {
+    /// Lowercase letters, digits and underscores; can't be changed later
+    pub name: String,
+    pub label: String,
+    /// Can't be changed later
+    pub data_type: String,
+    #[serde(default)]
+    pub required: bool,
+    pub max_length: Option<i32>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub options: Option<Vec<String>>,
+}
+
+/// Replaces the label and validation. Refused if existing values would no longer be valid.
+#[derive(Deserialize, Debug, Clone)]
+pub struct UpdateUserAttributeDefinitionPayload {
+    pub label: String,
+    #[serde(default)]
+    pub required: bool,
+    pub max_length: Option<i32>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    pub options: Option<Vec<String>>,
+}
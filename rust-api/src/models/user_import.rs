@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+/// Query string of `POST /api/users/import`.
+#[derive(Deserialize, Debug, Default)]
+pub struct ImportUsersQuery {
+    /// Validate and report without writing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// One CSV row. Optional columns may be left empty or omitted from the header.
+#[derive(Deserialize, Debug, Clone)]
+pub struct ImportUserRow {
+    pub username: String,
+    pub email: String,
+    /// Role name, as in `roles.name`
+    #[serde(default)]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub department: Option<String>,
+    /// Email of an existing user or of another row in the same file
+    #[serde(default)]
+    pub manager_email: Option<String>,
+    /// Initial password. Rows without one are created invited and sent an invite.
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// Validation outcome of one row. `line` is the line in the file, header included.
+#[derive(Serialize, Debug, Clone)]
+pub struct ImportRowResult {
+    pub line: u64,
+    pub username: String,
+    pub email: String,
+    /// No password given: the user is invited to choose one
+    pub invite: bool,
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UserImportReport {
+    pub dry_run: bool,
+    /// Whether the users were written. Never true for a dry run or when any row is invalid.
+    pub committed: bool,
+    pub total_rows: usize,
+    pub invalid_rows: usize,
+    pub rows: Vec<ImportRowResult>,
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use uuid::Uuid;
+use chrono::NaiveDateTime;
+
+use crate::models::avatar::avatar_urls;
+use crate::models::user::{User, UserWithRole};
+
+/// Which private fields the caller may see on user records.
+/// Users always see their own; anyone else needs `read user_private`.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldVisibility {
+    pub viewer_id: Uuid,
+    pub private_fields: bool,
+}
+
+impl FieldVisibility {
+    /// A user looking at their own record.
+    pub fn own(viewer_id: Uuid) -> Self {
+        Self { viewer_id, private_fields: false }
+    }
+
+    pub fn shows_private(&self, subject_id: Uuid) -> bool {
+        self.private_fields || self.viewer_id == subject_id
+    }
+}
+
+/// API representation of a user. `User` and `UserWithRole` are database rows and
+/// are not serializable, so the password hash can't reach a response.
+#[derive(Serialize, Debug, Clone)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub username: String,
+    /// Only present for the user themselves or callers allowed to see private fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    pub role_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role_name: Option<String>,
+    /// Name chosen by the user; absent on responses that don't load it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// Thumbnail URLs by size ("small", "medium", "large"); absent without an avatar
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avatar_urls: Option<BTreeMap<String, String>>,
+    pub status: String,
+    /// Custom attribute values; present on reads that load them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes: Option<Map<String, Value>>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl UserResponse {
+    pub fn from_user(user: User, visibility: &FieldVisibility) -> Self {
+        let private = visibility.shows_private(user.id);
+        Self {
+            id: user.id,
+            username: user.username,
+            email: private.then_some(user.email),
+            role_id: user.role_id,
+            role_name: None,
+            display_name: None,
+            avatar_urls: None,
+            status: user.status,
+            attributes: None,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+
+    pub fn from_user_with_role(user: UserWithRole, visibility: &FieldVisibility) -> Self {
+        let private = visibility.shows_private(user.id);
+        Self {
+            id: user.id,
+            username: user.username,
+            email: private.then_some(user.email),
+            role_id: user.role_id,
+            role_name: user.role_name,
+            avatar_urls: user.avatar_version.as_deref().map(|v| avatar_urls(user.id, v)),
+            display_name: user.display_name,
+            status: user.status,
+            attributes: match user.attributes {
+                Value::Object(map) => Some(map),
+                _ => Some(Map::new()),
+            },
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
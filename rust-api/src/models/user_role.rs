@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
+use std::collections::BTreeMap;
 use chrono::NaiveDateTime;
 
 #[derive(Serialize, FromRow, Clone, Debug)]
@@ -68,6 +69,14 @@ pub struct PolicyRule {
     pub action: String,
     pub conditions: Option<serde_json::Value>,
     pub created_at: NaiveDateTime,
+    /// Set when the rule only applies through an open break-glass window
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub break_glass_activation_id: Option<Uuid>,
+    /// Set when the rule reaches the user through a delegation from this user
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delegator_id: Option<Uuid>,
 }
 
 #[derive(Serialize, FromRow, Clone, Debug)]
@@ -85,6 +94,9 @@ pub struct AuthContext {
     pub location: Option<String>,
     pub time: String,
     pub resource_owner_id: Option<Uuid>,
+    /// Named attributes added by the attribute providers, e.g. "request.ip"
+    #[serde(default)]
+    pub attributes: BTreeMap<String, serde_json::Value>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -92,6 +104,30 @@ pub struct Decision {
     pub allowed: bool,
     pub reason: String,
     pub policy_id: Option<Uuid>,
+    pub break_glass_activation_id: Option<Uuid>,
+    /// The delegator, when access comes from a delegation ("on behalf of")
+    pub delegated_by: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PermissionCheck {
+    pub action: String,
+    pub resource: String,
+    pub resource_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PermissionCheckPayload {
+    pub checks: Vec<PermissionCheck>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PermissionCheckResult {
+    pub action: String,
+    pub resource: String,
+    pub resource_id: Option<Uuid>,
+    pub allowed: bool,
+    pub reason: String,
 }
 
 #[derive(Serialize, FromRow, Clone, Debug)]
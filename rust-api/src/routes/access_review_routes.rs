@@ -0,0 +1,20 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::access_review_handler,
+    routes::route_table::RouteTable,
+    utils::permissions::{self, SESSION},
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", permissions::CREATE_ACCESS_REVIEW, access_review_handler::create_campaign)
+        .route(Method::GET, "/", permissions::READ_ACCESS_REVIEW, access_review_handler::list_campaigns)
+        .route(Method::GET, "/mine", SESSION, access_review_handler::list_my_items)
+        .route(Method::GET, "/{id}", permissions::READ_ACCESS_REVIEW, access_review_handler::get_campaign)
+        .route(Method::POST, "/{id}/remind", permissions::UPDATE_ACCESS_REVIEW, access_review_handler::remind)
+        .route(Method::POST, "/{id}/close", permissions::UPDATE_ACCESS_REVIEW, access_review_handler::close_campaign)
+        .route(Method::GET, "/{id}/evidence", permissions::READ_ACCESS_REVIEW, access_review_handler::export_evidence)
+        .route(Method::POST, "/items/{id}/decision", SESSION, access_review_handler::decide_item)
+        .route(Method::PUT, "/items/{id}/reviewer", permissions::UPDATE_ACCESS_REVIEW, access_review_handler::reassign_item)
+}
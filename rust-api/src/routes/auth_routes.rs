@@ -1,16 +1,15 @@
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::http::Method;
 
 use crate::{
     handlers::auth_handler,
-    state::app_state::AppState,
+    routes::route_table::RouteTable,
+    utils::permissions::SESSION,
 };
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/login", post(auth_handler::login))
-        .route("/logout", post(auth_handler::logout))
-        .route("/me", get(auth_handler::me))
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/login", SESSION, auth_handler::login)
+        .route(Method::POST, "/logout", SESSION, auth_handler::logout)
+        .route(Method::GET, "/me", SESSION, auth_handler::me)
+        .route(Method::POST, "/permissions/check", SESSION, auth_handler::check_permissions)
 }
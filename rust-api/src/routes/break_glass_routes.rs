@@ -0,0 +1,19 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::break_glass_handler,
+    routes::route_table::RouteTable,
+    utils::permissions::{self, SESSION},
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/grants", permissions::CREATE_BREAK_GLASS, break_glass_handler::create_grant)
+        .route(Method::GET, "/grants", permissions::READ_BREAK_GLASS, break_glass_handler::list_grants)
+        .route(Method::GET, "/grants/mine", SESSION, break_glass_handler::list_my_grants)
+        .route(Method::DELETE, "/grants/{id}", permissions::DELETE_BREAK_GLASS, break_glass_handler::revoke_grant)
+        .route(Method::POST, "/activate", SESSION, break_glass_handler::activate)
+        .route(Method::GET, "/activations", permissions::READ_BREAK_GLASS, break_glass_handler::list_activations)
+        .route(Method::POST, "/activations/{id}/end", SESSION, break_glass_handler::end_activation)
+        .route(Method::GET, "/activations/{id}/audit", permissions::READ_BREAK_GLASS, break_glass_handler::list_activation_audit)
+}
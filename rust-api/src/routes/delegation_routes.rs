@@ -0,0 +1,15 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::delegation_handler,
+    routes::route_table::RouteTable,
+    utils::permissions::{self, SESSION},
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", SESSION, delegation_handler::create_delegation)
+        .route(Method::GET, "/", permissions::READ_DELEGATION, delegation_handler::list_delegations)
+        .route(Method::GET, "/mine", SESSION, delegation_handler::list_my_delegations)
+        .route(Method::DELETE, "/{id}", permissions::DELETE_DELEGATION, delegation_handler::revoke_delegation)
+}
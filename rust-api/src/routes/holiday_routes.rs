@@ -0,0 +1,18 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::holiday_handler,
+    routes::route_table::RouteTable,
+    utils::permissions,
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", permissions::CREATE_HOLIDAY_CALENDAR, holiday_handler::create_calendar)
+        .route(Method::PUT, "/{id}", permissions::UPDATE_HOLIDAY_CALENDAR, holiday_handler::update_calendar)
+        .route(Method::DELETE, "/{id}", permissions::DELETE_HOLIDAY_CALENDAR, holiday_handler::delete_calendar)
+        .route(Method::POST, "/{id}/holidays", permissions::UPDATE_HOLIDAY_CALENDAR, holiday_handler::add_holiday)
+        .route(Method::PUT, "/{id}/holidays/{holiday_id}", permissions::UPDATE_HOLIDAY_CALENDAR, holiday_handler::update_holiday)
+        .route(Method::DELETE, "/{id}/holidays/{holiday_id}", permissions::UPDATE_HOLIDAY_CALENDAR, holiday_handler::delete_holiday)
+        .route(Method::POST, "/{id}/import", permissions::UPDATE_HOLIDAY_CALENDAR, holiday_handler::import_holidays)
+}
@@ -0,0 +1,14 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::invite_handler,
+    routes::route_table::RouteTable,
+    utils::permissions::SESSION,
+};
+
+/// Public invite endpoints; the token is the credential.
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/{token}", SESSION, invite_handler::get_invite)
+        .route(Method::POST, "/{token}/accept", SESSION, invite_handler::accept_invite)
+}
@@ -0,0 +1,13 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::leave_balance_handler,
+    routes::route_table::RouteTable,
+    utils::permissions::SESSION,
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/", SESSION, leave_balance_handler::my_balances)
+        .route(Method::GET, "/ledger", SESSION, leave_balance_handler::my_ledger)
+}
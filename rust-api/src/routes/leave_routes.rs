@@ -1,25 +1,22 @@
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::http::Method;
 
 use crate::{
     handlers::leave_handler,
-    state::app_state::AppState,
+    routes::route_table::RouteTable,
+    services::leave_type_service::MAX_ATTACHMENT_BYTES,
+    utils::permissions::{self, SESSION},
 };
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route(
-            "/",
-            post(leave_handler::create_leave_request)
-            .get(leave_handler::list_my_leave_requests),
-        )
-        .route("/all", get(leave_handler::list_all_leave_requests))
-        .route(
-            "/{id}",
-            get(leave_handler::get_leave_request)
-                .delete(leave_handler::delete_leave_request),
-        )
-        .route("/{id}/status", post(leave_handler::update_leave_status))
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", SESSION, leave_handler::create_leave_request)
+        .route(Method::GET, "/", SESSION, leave_handler::list_my_leave_requests)
+        .route(Method::GET, "/all", permissions::READ_LEAVE_REQUEST, leave_handler::list_all_leave_requests)
+        .route(Method::GET, "/working-days", permissions::READ_LEAVE_REQUEST, leave_handler::preview_working_days)
+        .route(Method::GET, "/{id}", permissions::READ_LEAVE_REQUEST, leave_handler::get_leave_request)
+        .route(Method::DELETE, "/{id}", SESSION, leave_handler::delete_leave_request)
+        .route(Method::POST, "/{id}/status", permissions::UPDATE_LEAVE_REQUEST, leave_handler::update_leave_status)
+        .route(Method::POST, "/{id}/cancel", SESSION, leave_handler::cancel_leave_request)
+        .route(Method::GET, "/{id}/attachment", permissions::READ_LEAVE_REQUEST, leave_handler::get_attachment)
+        .upload(Method::POST, "/attachments", SESSION, leave_handler::upload_attachment, MAX_ATTACHMENT_BYTES + 64 * 1024)
 }
@@ -0,0 +1,15 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::leave_type_handler,
+    routes::route_table::RouteTable,
+    utils::permissions,
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/", permissions::READ_LEAVE_REQUEST, leave_type_handler::list_types)
+        .route(Method::POST, "/", permissions::CREATE_LEAVE_TYPE, leave_type_handler::create_type)
+        .route(Method::PUT, "/{id}", permissions::UPDATE_LEAVE_TYPE, leave_type_handler::update_type)
+        .route(Method::DELETE, "/{id}", permissions::DELETE_LEAVE_TYPE, leave_type_handler::delete_type)
+}
@@ -0,0 +1,21 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::{avatar_handler, self_profile_handler},
+    routes::route_table::RouteTable,
+    services::avatar_service::MAX_AVATAR_BYTES,
+    utils::permissions::SESSION,
+};
+
+/// Self-service endpoints; they only ever act on the signed-in user.
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/profile", SESSION, self_profile_handler::get_profile)
+        .route(Method::PATCH, "/profile", SESSION, self_profile_handler::update_profile)
+        .route(Method::POST, "/email", SESSION, self_profile_handler::request_email_change)
+        .route(Method::DELETE, "/email", SESSION, self_profile_handler::cancel_email_change)
+        .route(Method::POST, "/email/verify", SESSION, self_profile_handler::confirm_email_change)
+        // Room for the multipart framing around the image
+        .upload(Method::PUT, "/avatar", SESSION, avatar_handler::upload_own_avatar, MAX_AVATAR_BYTES + 64 * 1024)
+        .route(Method::DELETE, "/avatar", SESSION, avatar_handler::delete_own_avatar)
+}
@@ -5,4 +5,16 @@ pub mod policy_routes;
 pub mod leave_routes;
 pub mod report_routes;
 pub mod payslip_routes;
-pub mod template_routes;
\ No newline at end of file
+pub mod template_routes;
+pub mod break_glass_routes;
+pub mod delegation_routes;
+pub mod record_share_routes;
+pub mod access_review_routes;
+pub mod org_routes;
+pub mod invite_routes;
+pub mod user_attribute_routes;
+pub mod me_routes;
+pub mod leave_type_routes;
+pub mod leave_balance_routes;
+pub mod holiday_routes;
+pub mod route_table;
@@ -1,13 +1,13 @@
-use axum::{
-    routing::{post},
-    Router,
-};
+use axum::http::Method;
 
-use crate::handlers::notification_handler;
-use crate::state::app_state::AppState;
+use crate::{
+    handlers::notification_handler,
+    routes::route_table::RouteTable,
+    utils::permissions,
+};
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/read-all", post(notification_handler::mark_all_read))
-        .route("/{id}/read", post(notification_handler::mark_read))
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/read-all", permissions::UPDATE_NOTIFICATION, notification_handler::mark_all_read)
+        .route(Method::POST, "/{id}/read", permissions::UPDATE_NOTIFICATION, notification_handler::mark_read)
 }
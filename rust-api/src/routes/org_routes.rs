@@ -0,0 +1,12 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::org_handler,
+    routes::route_table::RouteTable,
+    utils::permissions,
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/chart", permissions::READ_USER, org_handler::get_chart)
+}
@@ -1,22 +1,14 @@
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::http::Method;
 
 use crate::{
     handlers::payslip_handler,
-    state::app_state::AppState,
+    routes::route_table::RouteTable,
+    utils::permissions::{self, SESSION},
 };
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route(
-            "/",
-            post(payslip_handler::create_payslip)
-            .get(payslip_handler::list_my_payslips),
-        )
-        .route(
-            "/{id}",
-            get(payslip_handler::get_payslip),
-        )
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", permissions::CREATE_PAYSLIP, payslip_handler::create_payslip)
+        .route(Method::GET, "/", SESSION, payslip_handler::list_my_payslips)
+        .route(Method::GET, "/{id}", permissions::READ_PAYSLIP, payslip_handler::get_payslip)
 }
@@ -1,26 +1,39 @@
-use axum::{
-    routing::{get, post, delete},
-    Router,
-};
+use axum::http::Method;
 
 use crate::{
     handlers::policy_handler,
-    state::app_state::AppState,
+    handlers::policy_change_handler,
+    routes::route_table::RouteTable,
+    utils::permissions::{self, SESSION},
 };
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route("/roles", get(policy_handler::list_roles))
-        .route("/roles/{id}/policies", get(policy_handler::list_role_policies))
-        .route("/users/{id}/policies", get(policy_handler::list_user_policies))
-        .route("/policies", get(policy_handler::list_policies).post(policy_handler::create_policy))
-        .route("/policies/{id}/activate", post(policy_handler::activate_policy))
-        .route("/policies/{id}/archive", post(policy_handler::archive_policy))
-        .route("/policies/{id}/rules", get(policy_handler::list_policy_rules).post(policy_handler::add_policy_rule))
-        .route("/policies/{id}/bindings", get(policy_handler::list_policy_bindings))
-        .route("/policies/{id}/bind", post(policy_handler::bind_policy))
-        .route("/policies/{id}", delete(policy_handler::delete_policy))
-        .route("/policies/rules/{id}", delete(policy_handler::remove_policy_rule))
-        .route("/policies/bindings/{id}", delete(policy_handler::unbind_policy))
-        .route("/simulate", post(policy_handler::simulate_auth))
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/roles", SESSION, policy_handler::list_roles)
+        .route(Method::GET, "/permissions", SESSION, policy_handler::list_permissions)
+        .route(Method::GET, "/roles/{id}/policies", SESSION, policy_handler::list_role_policies)
+        .route(Method::GET, "/users/{id}/policies", SESSION, policy_handler::list_user_policies)
+        .route(Method::GET, "/policies", SESSION, policy_handler::list_policies)
+        .route(Method::POST, "/policies", permissions::CREATE_POLICY, policy_handler::create_policy)
+        .route(Method::POST, "/policies/{id}/activate", permissions::ACTIVATE_POLICY, policy_handler::activate_policy)
+        .route(Method::POST, "/policies/{id}/archive", permissions::ARCHIVE_POLICY, policy_handler::archive_policy)
+        .route(Method::GET, "/policies/{id}/rules", SESSION, policy_handler::list_policy_rules)
+        .route(Method::POST, "/policies/{id}/rules", permissions::EDIT_POLICY, policy_handler::add_policy_rule)
+        .route(Method::GET, "/policies/{id}/source", SESSION, policy_handler::get_policy_source)
+        .route(Method::PUT, "/policies/{id}/source", permissions::EDIT_POLICY, policy_handler::put_policy_source)
+        .route(Method::GET, "/policies/{id}/bindings", SESSION, policy_handler::list_policy_bindings)
+        .route(Method::POST, "/policies/{id}/bind", permissions::BIND_POLICY, policy_handler::bind_policy)
+        .route(Method::GET, "/policies/{id}/tests", SESSION, policy_handler::list_policy_tests)
+        .route(Method::POST, "/policies/{id}/tests", permissions::EDIT_POLICY, policy_handler::add_policy_test)
+        .route(Method::POST, "/policies/{id}/tests/run", permissions::SIMULATE_AUTH, policy_handler::run_policy_tests)
+        .route(Method::DELETE, "/policies/{id}", permissions::DELETE_POLICY, policy_handler::delete_policy)
+        .route(Method::DELETE, "/policies/rules/{id}", permissions::EDIT_POLICY, policy_handler::remove_policy_rule)
+        .route(Method::DELETE, "/policies/bindings/{id}", permissions::BIND_POLICY, policy_handler::unbind_policy)
+        .route(Method::DELETE, "/policies/tests/{id}", permissions::EDIT_POLICY, policy_handler::remove_policy_test)
+        .route(Method::GET, "/changes", permissions::READ_POLICY, policy_change_handler::list_changes)
+        .route(Method::GET, "/changes/{id}", permissions::READ_POLICY, policy_change_handler::get_change)
+        .route(Method::POST, "/changes/{id}/approve", permissions::APPROVE_POLICY, policy_change_handler::approve_change)
+        .route(Method::POST, "/changes/{id}/reject", permissions::APPROVE_POLICY, policy_change_handler::reject_change)
+        .route(Method::POST, "/simulate", permissions::SIMULATE_AUTH, policy_handler::simulate_auth)
+        .route(Method::POST, "/impact", permissions::SIMULATE_AUTH, policy_handler::analyze_impact)
 }
@@ -0,0 +1,15 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::record_share_handler,
+    routes::route_table::RouteTable,
+    utils::permissions,
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/{resource}/{id}/shares", permissions::READ_RECORD_SHARE, record_share_handler::list_shares)
+        .route(Method::POST, "/{resource}/{id}/shares", permissions::CREATE_RECORD_SHARE, record_share_handler::create_share)
+        .route(Method::GET, "/{resource}/{id}/access", permissions::READ_RECORD_SHARE, record_share_handler::list_access)
+        .route(Method::DELETE, "/shares/{id}", permissions::DELETE_RECORD_SHARE, record_share_handler::revoke_share)
+}
@@ -1,24 +1,16 @@
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::http::Method;
 
 use crate::{
     handlers::report_handler,
-    state::app_state::AppState,
+    routes::route_table::RouteTable,
+    utils::permissions::{self, SESSION},
 };
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route(
-            "/",
-            post(report_handler::create_report)
-            .get(report_handler::list_my_reports),
-        )
-        .route("/all", get(report_handler::list_all_reports))
-        .route(
-            "/{id}",
-            get(report_handler::get_report),
-        )
-        .route("/{id}/status", post(report_handler::update_report_status))
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", SESSION, report_handler::create_report)
+        .route(Method::GET, "/", SESSION, report_handler::list_my_reports)
+        .route(Method::GET, "/all", permissions::READ_REPORT, report_handler::list_all_reports)
+        .route(Method::GET, "/{id}", permissions::READ_REPORT, report_handler::get_report)
+        .route(Method::POST, "/{id}/status", permissions::UPDATE_REPORT, report_handler::update_report_status)
 }
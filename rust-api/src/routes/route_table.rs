@@ -0,0 +1,79 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    handler::Handler,
+    http::Method,
+    routing::{on, MethodFilter, MethodRouter},
+    Router,
+};
+
+use crate::{
+    state::app_state::AppState,
+    utils::permissions::{Access, RouteDef},
+};
+
+/// A router that records, for every route it serves, what the route requires
+/// of the caller. The same table builds the app and backs `verify_registry`.
+pub struct RouteTable {
+    router: Router<AppState>,
+    routes: Vec<RouteDef>,
+}
+
+impl Default for RouteTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouteTable {
+    pub fn new() -> Self {
+        Self { router: Router::new(), routes: Vec::new() }
+    }
+
+    /// Serves `handler` for `method` on `path`, guarded as `access` says.
+    pub fn route<H, T>(self, method: Method, path: &str, access: impl Into<Access>, handler: H) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        let method_router = on(method_filter(&method), handler);
+        self.add(method, path, access.into(), method_router)
+    }
+
+    /// Like `route`, for uploads that need a larger request body than the default.
+    pub fn upload<H, T>(self, method: Method, path: &str, access: impl Into<Access>, handler: H, max_bytes: usize) -> Self
+    where
+        H: Handler<T, AppState>,
+        T: 'static,
+    {
+        let method_router = on(method_filter(&method), handler).layer(DefaultBodyLimit::max(max_bytes));
+        self.add(method, path, access.into(), method_router)
+    }
+
+    /// Serves `other` under `prefix`, like `Router::nest`.
+    pub fn nest(mut self, prefix: &str, other: RouteTable) -> Self {
+        self.routes.extend(other.routes.into_iter().map(|r| RouteDef {
+            path: if r.path == "/" { prefix.to_string() } else { format!("{}{}", prefix, r.path) },
+            ..r
+        }));
+        self.router = self.router.nest(prefix, other.router);
+        self
+    }
+
+    pub fn into_router(self) -> Router<AppState> {
+        self.router
+    }
+
+    pub fn into_routes(self) -> Vec<RouteDef> {
+        self.routes
+    }
+
+    fn add(mut self, method: Method, path: &str, access: Access, method_router: MethodRouter<AppState>) -> Self {
+        self.routes.push(RouteDef { method: method.to_string(), path: path.to_string(), access });
+        self.router = self.router.route(path, method_router);
+        self
+    }
+}
+
+fn method_filter(method: &Method) -> MethodFilter {
+    MethodFilter::try_from(method.clone()).expect("route method")
+}
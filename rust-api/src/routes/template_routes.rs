@@ -1,25 +1,17 @@
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::http::Method;
 
 use crate::{
     handlers::template_handler,
-    state::app_state::AppState,
+    routes::route_table::RouteTable,
+    utils::permissions,
 };
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route(
-            "/",
-            post(template_handler::create_template)
-            .get(template_handler::list_templates),
-        )
-        .route(
-            "/{id}",
-            get(template_handler::get_template)
-                .put(template_handler::update_template)
-                .delete(template_handler::delete_template),
-        )
-        .route("/{id}/activate", post(template_handler::set_active_template))
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", permissions::CREATE_PAYSLIP_TEMPLATE, template_handler::create_template)
+        .route(Method::GET, "/", permissions::READ_PAYSLIP_TEMPLATE, template_handler::list_templates)
+        .route(Method::GET, "/{id}", permissions::READ_PAYSLIP_TEMPLATE, template_handler::get_template)
+        .route(Method::PUT, "/{id}", permissions::UPDATE_PAYSLIP_TEMPLATE, template_handler::update_template)
+        .route(Method::DELETE, "/{id}", permissions::DELETE_PAYSLIP_TEMPLATE, template_handler::delete_template)
+        .route(Method::POST, "/{id}/activate", permissions::UPDATE_PAYSLIP_TEMPLATE, template_handler::set_active_template)
 }
@@ -0,0 +1,15 @@
+use axum::http::Method;
+
+use crate::{
+    handlers::user_attribute_handler,
+    routes::route_table::RouteTable,
+    utils::permissions,
+};
+
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::GET, "/", permissions::READ_USER, user_attribute_handler::list_definitions)
+        .route(Method::POST, "/", permissions::CREATE_USER_ATTRIBUTE, user_attribute_handler::create_definition)
+        .route(Method::PUT, "/{id}", permissions::UPDATE_USER_ATTRIBUTE, user_attribute_handler::update_definition)
+        .route(Method::DELETE, "/{id}", permissions::DELETE_USER_ATTRIBUTE, user_attribute_handler::delete_definition)
+}
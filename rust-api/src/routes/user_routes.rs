@@ -1,24 +1,39 @@
-use axum::{
-    routing::{get, post},
-    Router,
-};
+use axum::http::Method;
 
 use crate::{
-    handlers::user_handler,
-    state::app_state::AppState,
+    handlers::{
+        avatar_handler, employee_profile_handler, invite_handler, leave_balance_handler, org_handler,
+        user_attribute_handler, user_handler,
+    },
+    routes::route_table::RouteTable,
+    services::avatar_service::MAX_AVATAR_BYTES,
+    utils::permissions,
 };
 
-pub fn routes() -> Router<AppState> {
-    Router::new()
-        .route(
-            "/",
-            post(user_handler::create_user)
-            .get(user_handler::list_users),
-        )
-        .route(
-            "/{id}",
-            get(user_handler::get_user)
-            .put(user_handler::update_user)
-                .delete(user_handler::delete_user),
-        )
+pub fn routes() -> RouteTable {
+    RouteTable::new()
+        .route(Method::POST, "/", permissions::CREATE_USER, user_handler::create_user)
+        .route(Method::GET, "/", permissions::READ_USER, user_handler::list_users)
+        .route(Method::POST, "/import", permissions::CREATE_USER, user_handler::import_users)
+        .route(Method::GET, "/export", permissions::READ_USER, user_handler::export_users)
+        .route(Method::GET, "/{id}", permissions::READ_USER, user_handler::get_user)
+        .route(Method::PUT, "/{id}", permissions::UPDATE_USER, user_handler::update_user)
+        .route(Method::DELETE, "/{id}", permissions::DELETE_USER, user_handler::delete_user)
+        .route(Method::GET, "/{id}/status", permissions::READ_USER, user_handler::list_user_status_history)
+        .route(Method::PUT, "/{id}/status", permissions::UPDATE_USER, user_handler::change_user_status)
+        .route(Method::GET, "/{id}/profile", permissions::READ_USER, employee_profile_handler::get_profile)
+        .route(Method::PUT, "/{id}/profile", permissions::UPDATE_USER, employee_profile_handler::update_profile)
+        .route(Method::GET, "/{id}/profile/history", permissions::READ_USER, employee_profile_handler::list_profile_history)
+        .upload(Method::PUT, "/{id}/avatar", permissions::UPDATE_USER, avatar_handler::upload_user_avatar, MAX_AVATAR_BYTES + 64 * 1024)
+        .route(Method::DELETE, "/{id}/avatar", permissions::UPDATE_USER, avatar_handler::delete_user_avatar)
+        .route(Method::GET, "/{id}/attributes", permissions::READ_USER, user_attribute_handler::get_values)
+        .route(Method::PUT, "/{id}/attributes", permissions::UPDATE_USER, user_attribute_handler::set_values)
+        .route(Method::GET, "/{id}/leave-balances", permissions::READ_LEAVE_BALANCE, leave_balance_handler::user_balances)
+        .route(Method::GET, "/{id}/leave-balances/ledger", permissions::READ_LEAVE_BALANCE, leave_balance_handler::user_ledger)
+        .route(Method::POST, "/{id}/leave-balances/adjustments", permissions::UPDATE_LEAVE_BALANCE, leave_balance_handler::adjust_balance)
+        .route(Method::GET, "/{id}/reports", permissions::READ_USER, org_handler::list_reports)
+        .route(Method::GET, "/{id}/chain", permissions::READ_USER, org_handler::get_chain)
+        .route(Method::GET, "/{id}/invite", permissions::CREATE_USER, invite_handler::list_invites)
+        .route(Method::POST, "/{id}/invite", permissions::CREATE_USER, invite_handler::resend_invite)
+        .route(Method::DELETE, "/{id}/invite", permissions::CREATE_USER, invite_handler::revoke_invite)
 }
@@ -0,0 +1,328 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::access_review::{
+    AccessReviewCampaign, AccessReviewItem, AccessReviewProgress, CreateAccessReviewPayload,
+};
+
+const CAMPAIGN_COLUMNS: &str = "id, name, description, policy_id, role_id, status, due_on, created_by, created_at, closed_by, closed_at";
+
+const ITEM_COLUMNS: &str = "i.id, i.campaign_id, i.binding_id, i.policy_id, i.policy_name, i.subject_type, i.subject_id, i.subject_name, i.reviewer_id, i.decision, i.comment, i.decided_at, i.applied_at, i.apply_error, rv.username AS reviewer_name";
+
+/// Creates the campaign and snapshots every matching binding into a review item.
+/// User bindings go to the user's manager; everything else to the default reviewer.
+pub async fn create_campaign(
+    pool: &PgPool,
+    payload: &CreateAccessReviewPayload,
+    created_by: Uuid,
+) -> sqlx::Result<AccessReviewCampaign> {
+    if payload.name.trim().is_empty() {
+        return Err(sqlx::Error::Protocol("Campaign name is required".into()));
+    }
+    if payload.due_on < chrono::Utc::now().date_naive() {
+        return Err(sqlx::Error::Protocol("Campaign is due in the past".into()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let campaign = sqlx::query_as::<_, AccessReviewCampaign>(&format!(
+        r#"
+        INSERT INTO access_review_campaigns (name, description, policy_id, role_id, due_on, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {}
+        "#,
+        CAMPAIGN_COLUMNS
+    ))
+    .bind(payload.name.trim())
+    .bind(&payload.description)
+    .bind(payload.policy_id)
+    .bind(payload.role_id)
+    .bind(payload.due_on)
+    .bind(created_by)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            sqlx::Error::Protocol("Unknown policy or role filter".into())
+        }
+        other => other,
+    })?;
+
+    let snapshotted = sqlx::query(
+        r#"
+        INSERT INTO access_review_items (campaign_id, binding_id, policy_id, policy_name, subject_type, subject_id, subject_name, reviewer_id)
+        SELECT $1, pb.id, p.id, p.name, pb.subject_type, pb.subject_id,
+               COALESCE(u.username, r.name, pb.subject_id::TEXT),
+               COALESCE(u.manager_id, $4)
+        FROM policy_bindings pb
+        JOIN policies p ON p.id = pb.policy_id AND p.status != 'archived'
+        LEFT JOIN users u ON pb.subject_type = 'user' AND u.id = pb.subject_id
+        LEFT JOIN roles r ON pb.subject_type = 'role' AND r.id = pb.subject_id
+        WHERE ($2::UUID IS NULL OR pb.policy_id = $2)
+        AND (
+            $3::UUID IS NULL
+            OR (pb.subject_type = 'role' AND pb.subject_id = $3)
+            OR (pb.subject_type = 'user' AND u.role_id = $3)
+        )
+        "#
+    )
+    .bind(campaign.id)
+    .bind(payload.policy_id)
+    .bind(payload.role_id)
+    .bind(payload.default_reviewer_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            sqlx::Error::Protocol("Unknown default reviewer".into())
+        }
+        other => other,
+    })?;
+
+    if snapshotted.rows_affected() == 0 {
+        return Err(sqlx::Error::Protocol("No policy bindings match the campaign filters".into()));
+    }
+
+    tx.commit().await?;
+    Ok(campaign)
+}
+
+pub async fn list_campaigns(pool: &PgPool) -> sqlx::Result<Vec<AccessReviewCampaign>> {
+    sqlx::query_as::<_, AccessReviewCampaign>(&format!(
+        "SELECT {} FROM access_review_campaigns ORDER BY created_at DESC",
+        CAMPAIGN_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_campaign(pool: &PgPool, id: Uuid) -> sqlx::Result<AccessReviewCampaign> {
+    sqlx::query_as::<_, AccessReviewCampaign>(&format!(
+        "SELECT {} FROM access_review_campaigns WHERE id = $1",
+        CAMPAIGN_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn list_items(pool: &PgPool, campaign_id: Uuid) -> sqlx::Result<Vec<AccessReviewItem>> {
+    sqlx::query_as::<_, AccessReviewItem>(&format!(
+        r#"
+        SELECT {}
+        FROM access_review_items i
+        LEFT JOIN users rv ON rv.id = i.reviewer_id
+        WHERE i.campaign_id = $1
+        ORDER BY i.policy_name ASC, i.subject_name ASC
+        "#,
+        ITEM_COLUMNS
+    ))
+    .bind(campaign_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_item(pool: &PgPool, id: Uuid) -> sqlx::Result<AccessReviewItem> {
+    sqlx::query_as::<_, AccessReviewItem>(&format!(
+        r#"
+        SELECT {}
+        FROM access_review_items i
+        LEFT JOIN users rv ON rv.id = i.reviewer_id
+        WHERE i.id = $1
+        "#,
+        ITEM_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Undecided items assigned to the reviewer in campaigns that are still open.
+pub async fn list_pending_for_reviewer(pool: &PgPool, reviewer_id: Uuid) -> sqlx::Result<Vec<AccessReviewItem>> {
+    sqlx::query_as::<_, AccessReviewItem>(&format!(
+        r#"
+        SELECT {}
+        FROM access_review_items i
+        JOIN access_review_campaigns c ON c.id = i.campaign_id AND c.status = 'open'
+        LEFT JOIN users rv ON rv.id = i.reviewer_id
+        WHERE i.reviewer_id = $1 AND i.decision IS NULL
+        ORDER BY c.due_on ASC, i.policy_name ASC
+        "#,
+        ITEM_COLUMNS
+    ))
+    .bind(reviewer_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Records the reviewer's decision. `false` if the item is no longer assigned to
+/// them or its campaign is closed. Decisions can be changed until the close.
+pub async fn decide(
+    pool: &PgPool,
+    item_id: Uuid,
+    reviewer_id: Uuid,
+    decision: &str,
+    comment: Option<&str>,
+) -> sqlx::Result<bool> {
+    if !["keep", "revoke"].contains(&decision) {
+        return Err(sqlx::Error::Protocol(format!("Unknown decision '{}'", decision)));
+    }
+
+    let result = sqlx::query(
+        r#"
+        UPDATE access_review_items i
+        SET decision = $3, comment = $4, decided_at = CURRENT_TIMESTAMP
+        FROM access_review_campaigns c
+        WHERE i.id = $1 AND i.reviewer_id = $2
+        AND c.id = i.campaign_id AND c.status = 'open'
+        "#
+    )
+    .bind(item_id)
+    .bind(reviewer_id)
+    .bind(decision)
+    .bind(comment)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Moves an undecided item of an open campaign to another reviewer.
+pub async fn reassign(pool: &PgPool, item_id: Uuid, reviewer_id: Uuid) -> sqlx::Result<bool> {
+    let result = sqlx::query(
+        r#"
+        UPDATE access_review_items i
+        SET reviewer_id = $2
+        FROM access_review_campaigns c
+        WHERE i.id = $1 AND i.decision IS NULL
+        AND c.id = i.campaign_id AND c.status = 'open'
+        "#
+    )
+    .bind(item_id)
+    .bind(reviewer_id)
+    .execute(pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            sqlx::Error::Protocol("Unknown reviewer".into())
+        }
+        other => other,
+    })?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Reviewers with undecided items in the campaign, with how many each has left.
+pub async fn pending_reviewers(pool: &PgPool, campaign_id: Uuid) -> sqlx::Result<Vec<(Uuid, i64)>> {
+    sqlx::query_as::<_, (Uuid, i64)>(
+        r#"
+        SELECT reviewer_id, COUNT(*)
+        FROM access_review_items
+        WHERE campaign_id = $1 AND decision IS NULL AND reviewer_id IS NOT NULL
+        GROUP BY reviewer_id
+        "#
+    )
+    .bind(campaign_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Closes the campaign and removes every binding a reviewer decided to revoke,
+/// all in one transaction. Undecided items are left as they are and show up as
+/// pending in the evidence. `None` if the campaign was already closed.
+pub async fn close_campaign(
+    pool: &PgPool,
+    id: Uuid,
+    closed_by: Uuid,
+) -> sqlx::Result<Option<AccessReviewCampaign>> {
+    let mut tx = pool.begin().await?;
+
+    let campaign = sqlx::query_as::<_, AccessReviewCampaign>(&format!(
+        r#"
+        UPDATE access_review_campaigns
+        SET status = 'closed', closed_by = $2, closed_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND status = 'open'
+        RETURNING {}
+        "#,
+        CAMPAIGN_COLUMNS
+    ))
+    .bind(id)
+    .bind(closed_by)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(campaign) = campaign else {
+        return Ok(None);
+    };
+
+    let revocations = sqlx::query_as::<_, (Uuid, Uuid)>(
+        "SELECT id, binding_id FROM access_review_items WHERE campaign_id = $1 AND decision = 'revoke'"
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    for (item_id, binding_id) in revocations {
+        let removed = sqlx::query("DELETE FROM policy_bindings WHERE id = $1")
+            .bind(binding_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let apply_error = (removed == 0).then_some("Binding no longer exists");
+        sqlx::query(
+            "UPDATE access_review_items SET applied_at = CURRENT_TIMESTAMP, apply_error = $2 WHERE id = $1"
+        )
+        .bind(item_id)
+        .bind(apply_error)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(Some(campaign))
+}
+
+pub fn progress(items: &[AccessReviewItem]) -> AccessReviewProgress {
+    let count = |decision: Option<&str>| items.iter().filter(|i| i.decision.as_deref() == decision).count();
+    AccessReviewProgress {
+        total: items.len(),
+        kept: count(Some("keep")),
+        revoked: count(Some("revoke")),
+        pending: count(None),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Evidence report: one CSV row per reviewed binding.
+pub fn evidence_csv(campaign: &AccessReviewCampaign, items: &[AccessReviewItem]) -> String {
+    let mut out = String::from(
+        "campaign,campaign_status,policy,subject_type,subject,reviewer,decision,comment,decided_at,applied_at,apply_error\n"
+    );
+    let time = |t: Option<chrono::NaiveDateTime>| t.map(|t| t.to_string()).unwrap_or_default();
+
+    for item in items {
+        let row = [
+            campaign.name.clone(),
+            campaign.status.clone(),
+            item.policy_name.clone(),
+            item.subject_type.clone(),
+            item.subject_name.clone(),
+            item.reviewer_name.clone().unwrap_or_default(),
+            item.decision.clone().unwrap_or_else(|| "pending".to_string()),
+            item.comment.clone().unwrap_or_default(),
+            time(item.decided_at),
+            time(item.applied_at),
+            item.apply_error.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
@@ -0,0 +1,447 @@
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use axum::extract::{ConnectInfo, Request};
+use axum::http::{header, HeaderMap};
+use axum::middleware::Next;
+use axum::response::Response;
+use chrono::{Datelike, Timelike, Utc};
+use chrono_tz::Tz;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::user::User;
+use crate::models::user_role::AuthContext;
+use crate::services::{employee_profile_service, org_service, user_attribute_service};
+
+/// The record a decision is about, when there is one.
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceRef<'a> {
+    pub resource: &'a str,
+    pub id: Uuid,
+}
+
+/// Everything a provider may look at while enriching the context.
+pub struct AttributeRequest<'a> {
+    pub headers: &'a HeaderMap,
+    /// Address of the connection's peer; `None` outside `record_peer_addr`
+    pub peer: Option<IpAddr>,
+    pub user: &'a User,
+    pub resource: Option<ResourceRef<'a>>,
+}
+
+/// Adds named attributes to the `AuthContext` of a request. Policies reference
+/// them as `context.<name>`.
+#[async_trait]
+pub trait AttributeProvider: Send + Sync {
+    /// Used when logging provider failures.
+    fn name(&self) -> &'static str;
+
+    /// Whether the attributes depend on `request.resource`. Only these run
+    /// again when one request needs the context of several records.
+    fn per_resource(&self) -> bool {
+        false
+    }
+
+    async fn provide(
+        &self,
+        pool: &PgPool,
+        request: &AttributeRequest<'_>,
+        context: &mut AuthContext,
+    ) -> sqlx::Result<()>;
+}
+
+/// Ordered set of providers held in `AppState`. Later providers see (and may
+/// overwrite) what earlier ones added.
+#[derive(Clone)]
+pub struct AttributeProviders {
+    providers: Vec<Arc<dyn AttributeProvider>>,
+}
+
+impl AttributeProviders {
+    /// The built-in providers: user profile, request metadata, org-local time
+    /// and on-demand resource attributes.
+    pub fn builtin(timezone: Tz, trusted_proxies: Vec<TrustedProxy>) -> Self {
+        Self { providers: Vec::new() }
+            .with(ProfileProvider)
+            .with(RequestProvider { trusted_proxies })
+            .with(TimeProvider { timezone })
+            .with(ResourceProvider)
+    }
+
+    pub fn with(mut self, provider: impl AttributeProvider + 'static) -> Self {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    /// Runs every provider. A failing provider fails the whole context: with
+    /// its attributes missing, a deny condition on them wouldn't match and the
+    /// request could be allowed when it shouldn't be.
+    pub async fn enrich(&self, pool: &PgPool, request: &AttributeRequest<'_>, context: &mut AuthContext) -> sqlx::Result<()> {
+        self.run(pool, request, context, |_| true).await
+    }
+
+    /// Runs only the providers whose attributes depend on the record, over a
+    /// context `enrich` already filled in for the same request.
+    pub async fn enrich_resource(&self, pool: &PgPool, request: &AttributeRequest<'_>, context: &mut AuthContext) -> sqlx::Result<()> {
+        self.run(pool, request, context, |provider| provider.per_resource()).await
+    }
+
+    async fn run(
+        &self,
+        pool: &PgPool,
+        request: &AttributeRequest<'_>,
+        context: &mut AuthContext,
+        selected: impl Fn(&dyn AttributeProvider) -> bool,
+    ) -> sqlx::Result<()> {
+        for provider in self.providers.iter().filter(|p| selected(p.as_ref())) {
+            provider.provide(pool, request, context).await.inspect_err(|e| {
+                eprintln!("Attribute provider '{}' failed: {:?}", provider.name(), e);
+            })?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the org time zone from `ORG_TIMEZONE` (an IANA name), defaulting to UTC.
+pub fn org_timezone_from_env() -> Tz {
+    match std::env::var("ORG_TIMEZONE") {
+        Ok(name) => name.trim().parse().unwrap_or_else(|_| {
+            eprintln!("Unknown ORG_TIMEZONE '{}', using UTC", name);
+            Tz::UTC
+        }),
+        Err(_) => Tz::UTC,
+    }
+}
+
+/// `principal.*`: the signed-in user's role and employee profile, and custom
+/// attributes as `principal.attributes.<name>`. Also fills in `context.department`
+/// and `context.location`.
+pub struct ProfileProvider;
+
+#[async_trait]
+impl AttributeProvider for ProfileProvider {
+    fn name(&self) -> &'static str {
+        "profile"
+    }
+
+    async fn provide(
+        &self,
+        pool: &PgPool,
+        request: &AttributeRequest<'_>,
+        context: &mut AuthContext,
+    ) -> sqlx::Result<()> {
+        let profile = employee_profile_service::get_profile(pool, request.user.id).await?;
+        let (role_name, role_level) =
+            sqlx::query_as::<_, (Option<String>, Option<i32>)>(
+                r#"
+                SELECT r.name, r.level
+                FROM users u
+                LEFT JOIN roles r ON u.role_id = r.id
+                WHERE u.id = $1
+                "#
+            )
+            .bind(request.user.id)
+            .fetch_one(pool)
+            .await?;
+
+        let attributes = &mut context.attributes;
+        attributes.insert("principal.id".into(), json!(request.user.id));
+        attributes.insert("principal.username".into(), json!(request.user.username));
+        if let Some(role) = role_name {
+            attributes.insert("principal.role".into(), json!(role));
+        }
+        if let Some(level) = role_level {
+            attributes.insert("principal.role_level".into(), json!(level));
+        }
+        let fields = [
+            ("principal.manager_id", profile.manager_id.map(|v| json!(v))),
+            ("principal.department", profile.department.as_ref().map(|v| json!(v))),
+            ("principal.location", profile.location.as_ref().map(|v| json!(v))),
+            ("principal.job_title", profile.job_title.as_ref().map(|v| json!(v))),
+            ("principal.hire_date", profile.hire_date.map(|v| json!(v))),
+            ("principal.employment_type", profile.employment_type.as_ref().map(|v| json!(v))),
+        ];
+        for (name, value) in fields {
+            if let Some(value) = value {
+                attributes.insert(name.into(), value);
+            }
+        }
+        for (name, value) in user_attribute_service::get_values(pool, request.user.id).await? {
+            attributes.insert(format!("principal.attributes.{}", name), value);
+        }
+        context.department = profile.department;
+        context.location = profile.location;
+        Ok(())
+    }
+}
+
+tokio::task_local! {
+    static PEER_ADDR: SocketAddr;
+}
+
+/// Middleware making the peer address from `ConnectInfo` available while the
+/// request is handled, since handlers build their context from the headers alone.
+pub async fn record_peer_addr(request: Request, next: Next) -> Response {
+    match request.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => {
+            let addr = *addr;
+            PEER_ADDR.scope(addr, next.run(request)).await
+        }
+        None => next.run(request).await,
+    }
+}
+
+/// Peer address of the request being handled, if `record_peer_addr` saw one.
+pub fn peer_addr() -> Option<IpAddr> {
+    PEER_ADDR.try_with(|addr| addr.ip()).ok()
+}
+
+/// An address or CIDR range whose forwarding headers are believed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrustedProxy {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl TrustedProxy {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let shift = |bits: u8| u32::from(bits - self.prefix);
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                u32::from(net).checked_shr(shift(32)).unwrap_or(0) == u32::from(ip).checked_shr(shift(32)).unwrap_or(0)
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                u128::from(net).checked_shr(shift(128)).unwrap_or(0) == u128::from(ip).checked_shr(shift(128)).unwrap_or(0)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for TrustedProxy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let network = addr.trim().parse::<IpAddr>().map_err(|_| format!("'{}' is not an IP address", addr.trim()))?.to_canonical();
+        let bits = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(p) => p.trim().parse::<u8>().ok().filter(|p| *p <= bits).ok_or_else(|| format!("Invalid prefix length '{}'", p.trim()))?,
+            None => bits,
+        };
+        Ok(Self { network, prefix })
+    }
+}
+
+/// Proxies listed in `TRUSTED_PROXIES` (comma-separated addresses or CIDR ranges).
+/// None by default, so forwarding headers are ignored unless configured.
+pub fn trusted_proxies_from_env() -> Vec<TrustedProxy> {
+    let Ok(value) = std::env::var("TRUSTED_PROXIES") else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            entry
+                .parse()
+                .map_err(|e| eprintln!("Ignoring TRUSTED_PROXIES entry '{}': {}", entry, e))
+                .ok()
+        })
+        .collect()
+}
+
+/// `request.*`: client IP and user agent. The IP is the connection's peer, unless
+/// the peer is a trusted proxy: then `X-Forwarded-For` is walked back from the
+/// nearest hop to the first address that isn't a trusted proxy.
+pub struct RequestProvider {
+    pub trusted_proxies: Vec<TrustedProxy>,
+}
+
+impl RequestProvider {
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|proxy| proxy.contains(ip))
+    }
+
+    fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let mut client = peer.to_canonical();
+        if !self.is_trusted(client) {
+            return client;
+        }
+
+        let forwarded: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .flat_map(|v| v.split(','))
+            .map(str::trim)
+            .filter(|hop| !hop.is_empty())
+            .collect();
+
+        if forwarded.is_empty() {
+            return headers
+                .get("x-real-ip")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<IpAddr>().ok())
+                .map(|ip| ip.to_canonical())
+                .unwrap_or(client);
+        }
+
+        for hop in forwarded.iter().rev() {
+            // An unparseable hop ends the chain we can vouch for
+            let Ok(ip) = hop.parse::<IpAddr>() else { break };
+            client = ip.to_canonical();
+            if !self.is_trusted(client) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+#[async_trait]
+impl AttributeProvider for RequestProvider {
+    fn name(&self) -> &'static str {
+        "request"
+    }
+
+    async fn provide(
+        &self,
+        _pool: &PgPool,
+        request: &AttributeRequest<'_>,
+        context: &mut AuthContext,
+    ) -> sqlx::Result<()> {
+        if let Some(peer) = request.peer {
+            let ip = self.client_ip(peer, request.headers);
+            context.attributes.insert("request.ip".into(), json!(ip.to_string()));
+        }
+
+        let agent = request
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        if let Some(agent) = agent {
+            context.attributes.insert("request.user_agent".into(), json!(agent));
+        }
+        Ok(())
+    }
+}
+
+/// `time.*`: the current time in the org time zone. Also replaces `context.time`.
+pub struct TimeProvider {
+    pub timezone: Tz,
+}
+
+#[async_trait]
+impl AttributeProvider for TimeProvider {
+    fn name(&self) -> &'static str {
+        "time"
+    }
+
+    async fn provide(
+        &self,
+        _pool: &PgPool,
+        _request: &AttributeRequest<'_>,
+        context: &mut AuthContext,
+    ) -> sqlx::Result<()> {
+        let now = Utc::now().with_timezone(&self.timezone);
+
+        context.time = now.to_rfc3339();
+        let attributes = &mut context.attributes;
+        attributes.insert("time.timezone".into(), json!(self.timezone.name()));
+        attributes.insert("time.local".into(), json!(now.to_rfc3339()));
+        attributes.insert("time.date".into(), json!(now.date_naive()));
+        attributes.insert("time.hour".into(), json!(now.hour()));
+        attributes.insert("time.weekday".into(), json!(now.weekday().to_string()));
+        Ok(())
+    }
+}
+
+/// `resource.*`: owner and status of the target record, the owner's custom
+/// attributes (`resource.owner_attributes.<name>`), and whether its owner is
+/// managed by the principal (`resource.owner_managed_by_principal`, anywhere
+/// in the chain). Only queries the database when the decision is about a
+/// specific record.
+pub struct ResourceProvider;
+
+/// Tables holding owned records, with the expression for their status.
+const RESOURCE_TABLES: &[(&str, &str, &str)] = &[
+    ("leave_request", "leave_requests", "t.status::TEXT"),
+    ("report", "reports", "t.status::TEXT"),
+    ("payslip", "payslips", "NULL::TEXT"),
+];
+
+#[async_trait]
+impl AttributeProvider for ResourceProvider {
+    fn name(&self) -> &'static str {
+        "resource"
+    }
+
+    fn per_resource(&self) -> bool {
+        true
+    }
+
+    async fn provide(
+        &self,
+        pool: &PgPool,
+        request: &AttributeRequest<'_>,
+        context: &mut AuthContext,
+    ) -> sqlx::Result<()> {
+        let Some(target) = request.resource else {
+            return Ok(());
+        };
+
+        context.attributes.insert("resource.type".into(), json!(target.resource));
+        context.attributes.insert("resource.id".into(), json!(target.id));
+
+        let Some((_, table, status)) = RESOURCE_TABLES.iter().find(|(name, _, _)| *name == target.resource) else {
+            return Ok(());
+        };
+
+        let row = sqlx::query_as::<_, (Uuid, Option<String>, Option<String>, Option<String>, Value)>(&format!(
+            r#"
+            SELECT t.user_id, {}, u.department, u.location, u.attributes
+            FROM {} t
+            JOIN users u ON t.user_id = u.id
+            WHERE t.id = $1
+            "#,
+            status, table
+        ))
+        .bind(target.id)
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some((owner_id, status, owner_department, owner_location, owner_attributes)) = row {
+            context.resource_owner_id = Some(owner_id);
+            let attributes = &mut context.attributes;
+            attributes.insert("resource.owner_id".into(), json!(owner_id));
+            if let Some(status) = status {
+                attributes.insert("resource.status".into(), Value::String(status));
+            }
+            if let Some(department) = owner_department {
+                attributes.insert("resource.owner_department".into(), Value::String(department));
+            }
+            if let Some(location) = owner_location {
+                attributes.insert("resource.owner_location".into(), Value::String(location));
+            }
+            if let Value::Object(values) = owner_attributes {
+                for (name, value) in values {
+                    attributes.insert(format!("resource.owner_attributes.{}", name), value);
+                }
+            }
+            let managed = org_service::is_in_management_chain(pool, request.user.id, owner_id).await?;
+            attributes.insert("resource.owner_managed_by_principal".into(), json!(managed));
+        }
+        Ok(())
+    }
+}
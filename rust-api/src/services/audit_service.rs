@@ -0,0 +1,48 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::audit_log::AuditLogEntry;
+use crate::models::user_role::Decision;
+
+pub async fn record_decision(
+    pool: &PgPool,
+    user_id: Uuid,
+    action: &str,
+    resource: &str,
+    decision: &Decision,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_log (user_id, action, resource, allowed, reason, policy_id, break_glass_activation_id, on_behalf_of)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#
+    )
+    .bind(user_id)
+    .bind(action)
+    .bind(resource)
+    .bind(decision.allowed)
+    .bind(&decision.reason)
+    .bind(decision.policy_id)
+    .bind(decision.break_glass_activation_id)
+    .bind(decision.delegated_by)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn list_for_break_glass_activation(
+    pool: &PgPool,
+    activation_id: Uuid,
+) -> sqlx::Result<Vec<AuditLogEntry>> {
+    sqlx::query_as::<_, AuditLogEntry>(
+        r#"
+        SELECT id, user_id, action, resource, allowed, reason, policy_id, break_glass_activation_id, on_behalf_of, created_at
+        FROM audit_log
+        WHERE break_glass_activation_id = $1
+        ORDER BY created_at ASC
+        "#
+    )
+    .bind(activation_id)
+    .fetch_all(pool)
+    .await
+}
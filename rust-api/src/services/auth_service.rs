@@ -3,13 +3,15 @@ use uuid::Uuid;
 use chrono::{Utc, Duration};
 use crate::models::user::User;
 use crate::models::user_role::{Session, AuthContext, Decision, PolicyRule};
+use crate::models::row_filter::{RecordScope, RowFilter, RuleConditions};
+use crate::services::{org_service, record_share_service};
 use rand::{thread_rng, Rng};
 use rand::distributions::Alphanumeric;
 
 pub async fn find_user_by_identify(pool: &PgPool, identity: &str) -> sqlx::Result<Option<User>> {
     sqlx::query_as::<_, User>(
         r#"
-        SELECT id, username, email, password_hash, role_id, created_at, updated_at
+        SELECT id, username, email, password_hash, role_id, user_status(id) AS status, created_at, updated_at
         FROM users
         WHERE username = $1 OR email = $1
         "#
@@ -48,6 +50,7 @@ pub async fn validate_session(pool: &PgPool, token: &str) -> sqlx::Result<Option
         SELECT id, user_id, token, expires_at, created_at
         FROM sessions
         WHERE token = $1 AND expires_at > CURRENT_TIMESTAMP
+        AND user_status(user_id) = 'active'
         "#
     )
     .bind(token)
@@ -69,69 +72,662 @@ pub async fn delete_session(pool: &PgPool, token: &str) -> sqlx::Result<u64> {
     Ok(result.rows_affected())
 }
 
-/// Central authorization engine (PBAC)
-/// Evaluates policies bound to the user or their role.
-/// Priority: User Deny > User Allow > Role Deny > Role Allow > Default Deny
-pub async fn authorize(
+/// Fetches the rules of every policy bound to the user or their role.
+/// Only active policies count, plus `include_policy` when evaluating a draft as if it were active,
+/// plus the policy of any break-glass window the user currently has open, if that policy is active.
+/// Permissions delegated to the user come back as the delegator's own rules narrowed to the
+/// delegated action and resource, tagged with `delegator_id`.
+pub async fn fetch_rules(
     pool: &PgPool,
-    user: &User,
-    action: &str,
-    resource: &str,
-    _context: &AuthContext,
-) -> sqlx::Result<Decision> {
-    // 1. Fetch rules from active policies bound to user or role
-    let rules = sqlx::query_as::<_, PolicyRule>(
+    user_id: Option<Uuid>,
+    role_id: Option<Uuid>,
+    include_policy: Option<Uuid>,
+) -> sqlx::Result<Vec<PolicyRule>> {
+    sqlx::query_as::<_, PolicyRule>(
         r#"
-        SELECT pr.* 
+        SELECT pr.id, pr.policy_id, pr.effect, pr.resource, pr.action, pr.conditions, pr.created_at,
+               NULL::UUID AS break_glass_activation_id, NULL::UUID AS delegator_id
         FROM policy_rules pr
         JOIN policies p ON pr.policy_id = p.id
         JOIN policy_bindings pb ON pb.policy_id = p.id
-        WHERE p.status = 'active'
+        WHERE (p.status = 'active' OR p.id = $3)
         AND (
             (pb.subject_type = 'user' AND pb.subject_id = $1)
             OR (pb.subject_type = 'role' AND pb.subject_id = $2)
         )
+        UNION ALL
+        SELECT pr.id, pr.policy_id, pr.effect, pr.resource, pr.action, pr.conditions, pr.created_at,
+               a.id AS break_glass_activation_id, NULL::UUID AS delegator_id
+        FROM policy_rules pr
+        JOIN policies p ON pr.policy_id = p.id AND p.status = 'active'
+        JOIN break_glass_grants g ON g.policy_id = p.id AND g.revoked_at IS NULL
+        JOIN break_glass_activations a ON a.grant_id = g.id
+        WHERE a.user_id = $1
+        AND a.ended_at IS NULL
+        AND a.expires_at > CURRENT_TIMESTAMP
+        UNION ALL
+        SELECT pr.id, pr.policy_id, pr.effect, d.resource, d.action, pr.conditions, pr.created_at,
+               NULL::UUID AS break_glass_activation_id, d.delegator_id
+        FROM permission_delegations d
+        JOIN users du ON du.id = d.delegator_id
+        JOIN policy_bindings pb ON (
+            (pb.subject_type = 'user' AND pb.subject_id = du.id)
+            OR (pb.subject_type = 'role' AND pb.subject_id = du.role_id)
+        )
+        JOIN policies p ON p.id = pb.policy_id AND p.status = 'active'
+        JOIN policy_rules pr ON pr.policy_id = p.id
+        WHERE d.delegate_id = $1
+        AND d.revoked_at IS NULL
+        AND user_status(du.id) = 'active'
+        AND CURRENT_DATE BETWEEN d.starts_on AND d.ends_on
+        AND (pr.action = '*' OR pr.action = d.action)
+        AND (pr.resource = '*' OR pr.resource = d.resource)
         "#
     )
-    .bind(user.id)
-    .bind(user.role_id)
+    .bind(user_id)
+    .bind(role_id)
+    .bind(include_policy)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(sqlx::FromRow)]
+struct SubjectRule {
+    subject_id: Uuid,
+    #[sqlx(flatten)]
+    rule: PolicyRule,
+}
+
+/// `fetch_rules` for several users in one query. Each rule comes with the user
+/// it reaches.
+pub async fn fetch_rules_for_users(
+    pool: &PgPool,
+    user_ids: &[Uuid],
+    include_policy: Option<Uuid>,
+) -> sqlx::Result<Vec<(Uuid, PolicyRule)>> {
+    let rows = sqlx::query_as::<_, SubjectRule>(
+        r#"
+        SELECT u.id AS subject_id, pr.id, pr.policy_id, pr.effect, pr.resource, pr.action, pr.conditions, pr.created_at,
+               NULL::UUID AS break_glass_activation_id, NULL::UUID AS delegator_id
+        FROM users u
+        JOIN policy_bindings pb ON (
+            (pb.subject_type = 'user' AND pb.subject_id = u.id)
+            OR (pb.subject_type = 'role' AND pb.subject_id = u.role_id)
+        )
+        JOIN policies p ON p.id = pb.policy_id AND (p.status = 'active' OR p.id = $2)
+        JOIN policy_rules pr ON pr.policy_id = p.id
+        WHERE u.id = ANY($1)
+        UNION ALL
+        SELECT a.user_id, pr.id, pr.policy_id, pr.effect, pr.resource, pr.action, pr.conditions, pr.created_at,
+               a.id AS break_glass_activation_id, NULL::UUID AS delegator_id
+        FROM policy_rules pr
+        JOIN policies p ON pr.policy_id = p.id AND p.status = 'active'
+        JOIN break_glass_grants g ON g.policy_id = p.id AND g.revoked_at IS NULL
+        JOIN break_glass_activations a ON a.grant_id = g.id
+        WHERE a.user_id = ANY($1)
+        AND a.ended_at IS NULL
+        AND a.expires_at > CURRENT_TIMESTAMP
+        UNION ALL
+        SELECT d.delegate_id, pr.id, pr.policy_id, pr.effect, d.resource, d.action, pr.conditions, pr.created_at,
+               NULL::UUID AS break_glass_activation_id, d.delegator_id
+        FROM permission_delegations d
+        JOIN users du ON du.id = d.delegator_id
+        JOIN policy_bindings pb ON (
+            (pb.subject_type = 'user' AND pb.subject_id = du.id)
+            OR (pb.subject_type = 'role' AND pb.subject_id = du.role_id)
+        )
+        JOIN policies p ON p.id = pb.policy_id AND p.status = 'active'
+        JOIN policy_rules pr ON pr.policy_id = p.id
+        WHERE d.delegate_id = ANY($1)
+        AND d.revoked_at IS NULL
+        AND user_status(du.id) = 'active'
+        AND CURRENT_DATE BETWEEN d.starts_on AND d.ends_on
+        AND (pr.action = '*' OR pr.action = d.action)
+        AND (pr.resource = '*' OR pr.resource = d.resource)
+        "#
+    )
+    .bind(user_ids)
+    .bind(include_policy)
     .fetch_all(pool)
     .await?;
 
+    Ok(rows.into_iter().map(|row| (row.subject_id, row.rule)).collect())
+}
+
+/// `fetch_rules` for every user at once, limited to rules that can apply to
+/// `resource`. Each rule comes with the user it reaches.
+pub async fn fetch_rules_for_resource(pool: &PgPool, resource: &str) -> sqlx::Result<Vec<(Uuid, PolicyRule)>> {
+    let rows = sqlx::query_as::<_, SubjectRule>(
+        r#"
+        SELECT u.id AS subject_id, pr.id, pr.policy_id, pr.effect, pr.resource, pr.action, pr.conditions, pr.created_at,
+               NULL::UUID AS break_glass_activation_id, NULL::UUID AS delegator_id
+        FROM users u
+        JOIN policy_bindings pb ON (
+            (pb.subject_type = 'user' AND pb.subject_id = u.id)
+            OR (pb.subject_type = 'role' AND pb.subject_id = u.role_id)
+        )
+        JOIN policies p ON p.id = pb.policy_id AND p.status = 'active'
+        JOIN policy_rules pr ON pr.policy_id = p.id
+        WHERE pr.resource IN ('*', $1)
+        UNION ALL
+        SELECT a.user_id, pr.id, pr.policy_id, pr.effect, pr.resource, pr.action, pr.conditions, pr.created_at,
+               a.id AS break_glass_activation_id, NULL::UUID AS delegator_id
+        FROM policy_rules pr
+        JOIN policies p ON pr.policy_id = p.id AND p.status = 'active'
+        JOIN break_glass_grants g ON g.policy_id = p.id AND g.revoked_at IS NULL
+        JOIN break_glass_activations a ON a.grant_id = g.id
+        WHERE a.ended_at IS NULL
+        AND a.expires_at > CURRENT_TIMESTAMP
+        AND pr.resource IN ('*', $1)
+        UNION ALL
+        SELECT d.delegate_id, pr.id, pr.policy_id, pr.effect, d.resource, d.action, pr.conditions, pr.created_at,
+               NULL::UUID AS break_glass_activation_id, d.delegator_id
+        FROM permission_delegations d
+        JOIN users du ON du.id = d.delegator_id
+        JOIN policy_bindings pb ON (
+            (pb.subject_type = 'user' AND pb.subject_id = du.id)
+            OR (pb.subject_type = 'role' AND pb.subject_id = du.role_id)
+        )
+        JOIN policies p ON p.id = pb.policy_id AND p.status = 'active'
+        JOIN policy_rules pr ON pr.policy_id = p.id
+        WHERE d.resource = $1
+        AND d.revoked_at IS NULL
+        AND user_status(du.id) = 'active'
+        AND CURRENT_DATE BETWEEN d.starts_on AND d.ends_on
+        AND (pr.action = '*' OR pr.action = d.action)
+        AND (pr.resource = '*' OR pr.resource = d.resource)
+        "#
+    )
+    .bind(resource)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| (row.subject_id, row.rule)).collect())
+}
+
+/// Whether the rule's context conditions hold for this request. Rules whose
+/// conditions don't parse are left to the caller (see `rule_scope`).
+fn context_matches(rule: &PolicyRule, context: &AuthContext) -> bool {
+    match &rule.conditions {
+        Some(value) if !value.is_null() => RuleConditions::parse(value)
+            .map(|c| c.context_holds(context))
+            .unwrap_or(true),
+        _ => true,
+    }
+}
+
+/// Whether the rule applies to a yes/no decision: its context conditions hold
+/// and, for rules scoped by owner or department, the record is in scope for
+/// the subject the rule belongs to. Without a record scoped rules don't apply.
+/// Conditions that no longer parse fail closed: an allow never applies and a
+/// deny always does.
+fn rule_applies(rule: &PolicyRule, context: &AuthContext, record: Option<&RecordScope>) -> bool {
+    let conditions = match &rule.conditions {
+        None | Some(serde_json::Value::Null) => return true,
+        Some(value) => RuleConditions::parse(value),
+    };
+    let Ok(conditions) = conditions else {
+        return rule.effect == "deny";
+    };
+    if !conditions.context_holds(context) {
+        return false;
+    }
+    if !conditions.is_scoped() {
+        return true;
+    }
+    match record {
+        Some(record) => conditions.record_in_scope(rule.delegator_id.unwrap_or(record.subject_id), record),
+        None => false,
+    }
+}
+
+/// Evaluates already-fetched rules. Deny always wins, Allow is cumulative.
+/// Delegated rules only add grants: they are consulted when the user's own rules
+/// neither allow nor deny, and a delegator's own deny cancels their delegation.
+/// Rules whose context conditions don't hold are skipped, and so are rules
+/// scoped by owner or department since no record is given (see `evaluate_record`).
+pub fn evaluate(
+    rules: &[PolicyRule],
+    action: &str,
+    resource: &str,
+    context: &AuthContext,
+) -> Decision {
+    decide(rules, action, resource, context, None)
+}
+
+/// `evaluate` for one record: scoped rules apply when the record is within
+/// their scope. Build `record` with `record_scope`.
+pub fn evaluate_record(
+    rules: &[PolicyRule],
+    action: &str,
+    resource: &str,
+    context: &AuthContext,
+    record: &RecordScope,
+) -> Decision {
+    decide(rules, action, resource, context, Some(record))
+}
+
+fn decide(
+    rules: &[PolicyRule],
+    action: &str,
+    resource: &str,
+    context: &AuthContext,
+    record: Option<&RecordScope>,
+) -> Decision {
     let mut allowed = false;
     let mut matching_policy_id = None;
+    // Any decision made while a break-glass window is open gets tagged with it.
+    let break_glass_activation_id = rules.iter().find_map(|r| r.break_glass_activation_id);
 
-    // 2. Evaluation logic: Deny always wins, Allow is cumulative.
-    for rule in &rules {
+    let matches = |rule: &&PolicyRule| {
         let action_match = rule.action == "*" || rule.action == action;
         let resource_match = rule.resource == "*" || rule.resource == resource;
+        action_match && resource_match && rule_applies(rule, context, record)
+    };
 
-        if action_match && resource_match {
-            if rule.effect == "deny" {
-                return Ok(Decision {
-                    allowed: false,
-                    reason: format!("Explicitly denied by policy {}", rule.policy_id),
-                    policy_id: Some(rule.policy_id),
-                });
-            }
-            if rule.effect == "allow" {
-                allowed = true;
-                matching_policy_id = Some(rule.policy_id);
-            }
+    for rule in rules.iter().filter(|r| r.delegator_id.is_none()).filter(matches) {
+        if rule.effect == "deny" {
+            return Decision {
+                allowed: false,
+                reason: format!("Explicitly denied by policy {}", rule.policy_id),
+                policy_id: Some(rule.policy_id),
+                break_glass_activation_id,
+                delegated_by: None,
+            };
+        }
+        if rule.effect == "allow" {
+            allowed = true;
+            matching_policy_id = Some(rule.policy_id);
         }
     }
 
     if allowed {
-        Ok(Decision {
+        return Decision {
             allowed: true,
             reason: "Access granted via policy".to_string(),
             policy_id: matching_policy_id,
-        })
-    } else {
-        Ok(Decision {
-            allowed: false,
-            reason: "No matching allow policy found (Default Deny)".to_string(),
+            break_glass_activation_id,
+            delegated_by: None,
+        };
+    }
+
+    let delegated: Vec<&PolicyRule> = rules.iter().filter(|r| r.delegator_id.is_some()).filter(matches).collect();
+    for delegator_id in delegated.iter().filter_map(|r| r.delegator_id) {
+        let theirs = || delegated.iter().filter(move |r| r.delegator_id == Some(delegator_id));
+        if theirs().any(|r| r.effect == "deny") {
+            continue;
+        }
+        if let Some(rule) = theirs().find(|r| r.effect == "allow") {
+            return Decision {
+                allowed: true,
+                reason: format!("Access granted via delegation from user {}", delegator_id),
+                policy_id: Some(rule.policy_id),
+                break_glass_activation_id,
+                delegated_by: Some(delegator_id),
+            };
+        }
+    }
+
+    Decision {
+        allowed: false,
+        reason: "No matching allow policy found (Default Deny)".to_string(),
+        policy_id: None,
+        break_glass_activation_id,
+        delegated_by: None,
+    }
+}
+
+/// Loads what `evaluate_record` needs to know about the owner of a record.
+/// Only queries the database when one of the rules is scoped.
+pub async fn record_scope(
+    pool: &PgPool,
+    rules: &[PolicyRule],
+    user_id: Uuid,
+    owner_id: Option<Uuid>,
+) -> sqlx::Result<RecordScope> {
+    let mut scope = RecordScope { subject_id: user_id, owner_id, ..RecordScope::default() };
+    let Some(owner_id) = owner_id else {
+        return Ok(scope);
+    };
+
+    let scoped = |rule: &&PolicyRule| {
+        rule.conditions
+            .as_ref()
+            .and_then(|value| RuleConditions::parse(value).ok())
+            .is_some_and(|c| c.is_scoped())
+    };
+    let mut subjects: Vec<Uuid> = rules.iter().filter(scoped).map(|r| r.delegator_id.unwrap_or(user_id)).collect();
+    if subjects.is_empty() {
+        return Ok(scope);
+    }
+    subjects.sort();
+    subjects.dedup();
+
+    let mut users = subjects.clone();
+    users.push(owner_id);
+    let departments = sqlx::query_as::<_, (Uuid, Option<String>)>("SELECT id, department FROM users WHERE id = ANY($1)")
+        .bind(&users)
+        .fetch_all(pool)
+        .await?;
+    for (id, department) in departments {
+        if id == owner_id {
+            scope.owner_department = department.clone();
+        }
+        if let Some(department) = department
+            && subjects.contains(&id)
+        {
+            scope.departments.insert(id, department);
+        }
+    }
+
+    for subject in subjects {
+        if org_service::is_in_management_chain(pool, subject, owner_id).await? {
+            scope.managers.push(subject);
+        }
+    }
+    Ok(scope)
+}
+
+/// Rows a single rule applies to. Conditions that no longer parse make the rule
+/// fail closed: an allow grants nothing and a deny covers everything.
+fn rule_scope(rule: &PolicyRule, subject_id: Uuid) -> RowFilter {
+    let parsed = match &rule.conditions {
+        None | Some(serde_json::Value::Null) => Ok(RuleConditions::default()),
+        Some(value) => RuleConditions::parse(value),
+    };
+    match parsed {
+        Ok(conditions) => conditions.scope(subject_id),
+        Err(_) if rule.effect == "deny" => RowFilter::All,
+        Err(_) => RowFilter::Nothing,
+    }
+}
+
+/// Rows granted by one subject's rules: any allow, minus any deny.
+fn granted_rows(rules: &[&PolicyRule], subject_id: Uuid) -> (RowFilter, RowFilter) {
+    let scopes = |effect: &str| {
+        rules
+            .iter()
+            .filter(|r| r.effect == effect)
+            .map(|r| rule_scope(r, subject_id))
+            .collect::<Vec<_>>()
+    };
+    (RowFilter::or(scopes("allow")), RowFilter::or(scopes("deny")))
+}
+
+/// Partial evaluation: instead of a yes/no answer for one record, returns the
+/// predicate describing every record of `resource` the user may `action`.
+/// Follows `evaluate`: own denies always win, delegated rules are interpreted
+/// relative to the delegator and cancelled by the delegator's own denies.
+pub fn partial_evaluate(
+    rules: &[PolicyRule],
+    action: &str,
+    resource: &str,
+    subject_id: Uuid,
+    context: &AuthContext,
+) -> RowFilter {
+    let matches = |rule: &&PolicyRule| {
+        let action_match = rule.action == "*" || rule.action == action;
+        let resource_match = rule.resource == "*" || rule.resource == resource;
+        action_match && resource_match && context_matches(rule, context)
+    };
+
+    let own: Vec<&PolicyRule> = rules.iter().filter(|r| r.delegator_id.is_none()).filter(matches).collect();
+    let (own_allow, own_deny) = granted_rows(&own, subject_id);
+
+    let mut grants = vec![own_allow];
+    let mut delegators: Vec<Uuid> = rules.iter().filter(matches).filter_map(|r| r.delegator_id).collect();
+    delegators.sort();
+    delegators.dedup();
+    for delegator_id in delegators {
+        let theirs: Vec<&PolicyRule> = rules
+            .iter()
+            .filter(|r| r.delegator_id == Some(delegator_id))
+            .filter(matches)
+            .collect();
+        let (allow, deny) = granted_rows(&theirs, delegator_id);
+        grants.push(RowFilter::and(vec![allow, RowFilter::not(deny)]));
+    }
+
+    RowFilter::and(vec![RowFilter::or(grants), RowFilter::not(own_deny)])
+}
+
+/// Suspended and terminated users are denied everything, whatever their policies say.
+fn inactive_decision(user: &User) -> Option<Decision> {
+    (user.status != "active").then(|| Decision {
+        allowed: false,
+        reason: format!("User is {}", user.status),
+        policy_id: None,
+        break_glass_activation_id: None,
+        delegated_by: None,
+    })
+}
+
+/// List-level authorization: the decision to record in the audit log plus the
+/// rows the user may see. The decision is allowed unless no row could match.
+pub async fn authorize_rows(
+    pool: &PgPool,
+    user: &User,
+    action: &str,
+    resource: &str,
+    context: &AuthContext,
+) -> sqlx::Result<(Decision, RowFilter)> {
+    if let Some(decision) = inactive_decision(user) {
+        return Ok((decision, RowFilter::Nothing));
+    }
+    let rules = fetch_rules(pool, Some(user.id), user.role_id, None).await?;
+    let filter = partial_evaluate(&rules, action, resource, user.id, context);
+
+    let mut decision = evaluate(&rules, action, resource, context);
+    match filter {
+        RowFilter::All => {}
+        RowFilter::Nothing => {
+            decision.allowed = false;
+            if decision.reason.starts_with("Access granted") {
+                decision.reason = "Rule conditions match no records".to_string();
+            }
+        }
+        _ => {
+            decision.allowed = true;
+            decision.reason = "Access limited to records matching rule conditions".to_string();
+        }
+    }
+
+    Ok((decision, filter))
+}
+
+/// Central authorization engine (PBAC)
+/// Evaluates policies bound to the user or their role.
+/// Priority: User Deny > User Allow > Role Deny > Role Allow > Default Deny
+pub async fn authorize(
+    pool: &PgPool,
+    user: &User,
+    action: &str,
+    resource: &str,
+    context: &AuthContext,
+) -> sqlx::Result<Decision> {
+    if let Some(decision) = inactive_decision(user) {
+        return Ok(decision);
+    }
+    let rules = fetch_rules(pool, Some(user.id), user.role_id, None).await?;
+    Ok(evaluate(&rules, action, resource, context))
+}
+
+/// `authorize` for one record, owned by `context.resource_owner_id`: scoped
+/// rules apply when the record is within their scope. When policies don't grant
+/// access, an active share of that record can. An explicit deny is never
+/// overridden by a share.
+pub async fn authorize_record(
+    pool: &PgPool,
+    user: &User,
+    action: &str,
+    resource: &str,
+    resource_id: Uuid,
+    context: &AuthContext,
+) -> sqlx::Result<Decision> {
+    if let Some(decision) = inactive_decision(user) {
+        return Ok(decision);
+    }
+    let rules = fetch_rules(pool, Some(user.id), user.role_id, None).await?;
+    authorize_record_with_rules(pool, user, &rules, action, resource, resource_id, context).await
+}
+
+/// `authorize` over rules already fetched for `user`, for callers making
+/// several decisions at once.
+pub fn authorize_with_rules(user: &User, rules: &[PolicyRule], action: &str, resource: &str, context: &AuthContext) -> Decision {
+    inactive_decision(user).unwrap_or_else(|| evaluate(rules, action, resource, context))
+}
+
+/// `authorize_record` over rules already fetched for `user`.
+pub async fn authorize_record_with_rules(
+    pool: &PgPool,
+    user: &User,
+    rules: &[PolicyRule],
+    action: &str,
+    resource: &str,
+    resource_id: Uuid,
+    context: &AuthContext,
+) -> sqlx::Result<Decision> {
+    // Inactive users lose shared records along with everything else
+    if let Some(decision) = inactive_decision(user) {
+        return Ok(decision);
+    }
+
+    let record = record_scope(pool, rules, user.id, context.resource_owner_id).await?;
+    let decision = evaluate_record(rules, action, resource, context, &record);
+    if decision.allowed || decision.policy_id.is_some() {
+        return Ok(decision);
+    }
+
+    match record_share_service::find_active_share(pool, user.id, resource, resource_id, action).await? {
+        Some(share) => Ok(Decision {
+            allowed: true,
+            reason: format!("Record shared with you (share {})", share.id),
             policy_id: None,
-        })
+            break_glass_activation_id: decision.break_glass_activation_id,
+            delegated_by: None,
+        }),
+        None => Ok(decision),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::auth::request_context;
+    use serde_json::json;
+
+    fn rule(effect: &str, action: &str, resource: &str, conditions: Option<serde_json::Value>) -> PolicyRule {
+        PolicyRule {
+            id: Uuid::new_v4(),
+            policy_id: Uuid::new_v4(),
+            effect: effect.to_string(),
+            resource: resource.to_string(),
+            action: action.to_string(),
+            conditions,
+            created_at: Utc::now().naive_utc(),
+            break_glass_activation_id: None,
+            delegator_id: None,
+        }
+    }
+
+    fn record(subject_id: Uuid, owner_id: Uuid, managed: bool) -> RecordScope {
+        RecordScope {
+            subject_id,
+            owner_id: Some(owner_id),
+            managers: if managed { vec![subject_id] } else { Vec::new() },
+            ..RecordScope::default()
+        }
+    }
+
+    #[test]
+    fn scoped_allow_only_grants_records_in_scope() {
+        let lead = Uuid::new_v4();
+        let rules = vec![rule("allow", "update", "leave_request", Some(json!({"owner": "reports"})))];
+        let context = request_context();
+
+        let report = record(lead, Uuid::new_v4(), true);
+        let stranger = record(lead, Uuid::new_v4(), false);
+        assert!(evaluate_record(&rules, "update", "leave_request", &context, &report).allowed);
+        assert!(!evaluate_record(&rules, "update", "leave_request", &context, &stranger).allowed);
+        // Without a record a scoped rule doesn't apply
+        assert!(!evaluate(&rules, "update", "leave_request", &context).allowed);
+    }
+
+    #[test]
+    fn scoped_deny_only_denies_records_in_scope() {
+        let user = Uuid::new_v4();
+        let rules = vec![
+            rule("allow", "read", "report", None),
+            rule("deny", "read", "report", Some(json!({"owner": "self"}))),
+        ];
+        let context = request_context();
+
+        let own = record(user, user, false);
+        let other = record(user, Uuid::new_v4(), false);
+        assert!(!evaluate_record(&rules, "read", "report", &context, &own).allowed);
+        assert!(evaluate_record(&rules, "read", "report", &context, &other).allowed);
+        assert!(evaluate(&rules, "read", "report", &context).allowed);
+    }
+
+    #[test]
+    fn department_scope_compares_the_subject_and_owner_departments() {
+        let user = Uuid::new_v4();
+        let rules = vec![rule("allow", "read", "report", Some(json!({"department": "mine"})))];
+        let context = request_context();
+
+        let mut same = record(user, Uuid::new_v4(), false);
+        same.departments.insert(user, "Ops".into());
+        same.owner_department = Some("Ops".into());
+        let mut other = same.clone();
+        other.owner_department = Some("Sales".into());
+        let mut unknown = same.clone();
+        unknown.owner_department = None;
+
+        assert!(evaluate_record(&rules, "read", "report", &context, &same).allowed);
+        assert!(!evaluate_record(&rules, "read", "report", &context, &other).allowed);
+        assert!(!evaluate_record(&rules, "read", "report", &context, &unknown).allowed);
+    }
+
+    #[test]
+    fn records_without_an_owner_are_outside_every_scope() {
+        let user = Uuid::new_v4();
+        let rules = vec![rule("allow", "read", "report", Some(json!({"owner": "self"})))];
+        let scope = RecordScope { subject_id: user, ..RecordScope::default() };
+        assert!(!evaluate_record(&rules, "read", "report", &request_context(), &scope).allowed);
+    }
+
+    #[test]
+    fn delegated_scopes_are_relative_to_the_delegator() {
+        let delegate = Uuid::new_v4();
+        let delegator = Uuid::new_v4();
+        let mut delegated = rule("allow", "update", "leave_request", Some(json!({"owner": "reports"})));
+        delegated.delegator_id = Some(delegator);
+        let rules = vec![delegated];
+        let context = request_context();
+
+        let mut theirs = record(delegate, Uuid::new_v4(), false);
+        theirs.managers.push(delegator);
+        let mine = record(delegate, Uuid::new_v4(), true);
+
+        let decision = evaluate_record(&rules, "update", "leave_request", &context, &theirs);
+        assert!(decision.allowed);
+        assert_eq!(decision.delegated_by, Some(delegator));
+        assert!(!evaluate_record(&rules, "update", "leave_request", &context, &mine).allowed);
+    }
+
+    #[test]
+    fn unreadable_conditions_fail_closed() {
+        let user = Uuid::new_v4();
+        let bad = json!({"owner": "everyone"});
+        let context = request_context();
+        let scope = record(user, user, true);
+
+        let allow = vec![rule("allow", "read", "report", Some(bad.clone()))];
+        assert!(!evaluate(&allow, "read", "report", &context).allowed);
+        assert!(!evaluate_record(&allow, "read", "report", &context, &scope).allowed);
+
+        let deny = vec![rule("allow", "read", "report", None), rule("deny", "read", "report", Some(bad))];
+        assert!(!evaluate(&deny, "read", "report", &context).allowed);
+        assert!(!evaluate_record(&deny, "read", "report", &context, &scope).allowed);
     }
 }
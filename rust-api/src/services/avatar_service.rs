@@ -0,0 +1,180 @@
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, Limits, Rgb, RgbImage};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::avatar::{avatar_key, AVATAR_SIZES};
+use crate::services::storage_service::Storage;
+
+/// Largest upload accepted
+pub const MAX_AVATAR_BYTES: usize = 5 * 1024 * 1024;
+
+/// Formats accepted for upload, detected from the file content
+pub const AVATAR_FORMATS: &[ImageFormat] = &[ImageFormat::Jpeg, ImageFormat::Png, ImageFormat::WebP];
+
+/// Larger sources are refused before decoding
+const MAX_SOURCE_DIMENSION: u32 = 6000;
+const JPEG_QUALITY: u8 = 85;
+
+#[derive(Debug)]
+pub enum AvatarError {
+    /// Not one of `AVATAR_FORMATS`
+    UnsupportedFormat,
+    /// Corrupt or oversized image
+    Invalid(String),
+    Storage(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for AvatarError {
+    fn from(e: sqlx::Error) -> Self {
+        AvatarError::Database(e)
+    }
+}
+
+/// Decodes the upload and renders every size in `AVATAR_SIZES`. The output is
+/// re-encoded from pixels only, so EXIF and any other metadata of the source is
+/// dropped; its orientation tag is applied first so photos stay upright.
+/// CPU-bound: call it from a blocking task.
+pub fn render_thumbnails(bytes: &[u8]) -> Result<Vec<(&'static str, Vec<u8>)>, AvatarError> {
+    let mut reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| AvatarError::Invalid(e.to_string()))?;
+    if !reader.format().is_some_and(|f| AVATAR_FORMATS.contains(&f)) {
+        return Err(AvatarError::UnsupportedFormat);
+    }
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_DIMENSION);
+    limits.max_image_height = Some(MAX_SOURCE_DIMENSION);
+    reader.limits(limits);
+
+    let invalid = |e: image::ImageError| AvatarError::Invalid(e.to_string());
+    let mut decoder = reader.into_decoder().map_err(invalid)?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let mut source = DynamicImage::from_decoder(decoder).map_err(invalid)?;
+    source.apply_orientation(orientation);
+
+    // JPEG has no alpha channel; transparent areas become white
+    let rgba = source.to_rgba8();
+    let flattened = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let [r, g, b, a] = rgba.get_pixel(x, y).0;
+        let blend = |c: u8| ((c as u16 * a as u16 + 255 * (255 - a as u16)) / 255) as u8;
+        Rgb([blend(r), blend(g), blend(b)])
+    });
+    let source = DynamicImage::ImageRgb8(flattened);
+
+    AVATAR_SIZES
+        .iter()
+        .map(|(name, edge)| {
+            let thumbnail = source.resize_to_fill(*edge, *edge, FilterType::Lanczos3);
+            let mut encoded = Vec::new();
+            JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY)
+                .encode_image(&thumbnail)
+                .map_err(invalid)?;
+            Ok((*name, encoded))
+        })
+        .collect()
+}
+
+/// Stores the thumbnails under a new version, points the user at it and removes
+/// the previous version. Returns the new version.
+pub async fn replace_avatar(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    user_id: Uuid,
+    thumbnails: &[(&'static str, Vec<u8>)],
+) -> Result<String, AvatarError> {
+    // Fail early for unknown users, before anything is written to storage
+    sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+    let version = Uuid::new_v4().simple().to_string();
+    for (size, bytes) in thumbnails {
+        storage
+            .put(&avatar_key(user_id, &version, size), bytes)
+            .await
+            .map_err(AvatarError::Storage)?;
+    }
+
+    let previous = sqlx::query_scalar::<_, Option<String>>(
+        r#"
+        UPDATE users u SET avatar_version = $2
+        FROM (SELECT id, avatar_version FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.avatar_version
+        "#
+    )
+    .bind(user_id)
+    .bind(&version)
+    .fetch_one(pool)
+    .await?;
+
+    if let Some(previous) = previous {
+        remove_version(storage, user_id, &previous).await;
+    }
+    Ok(version)
+}
+
+/// Clears the user's avatar. Returns whether they had one.
+pub async fn remove_avatar(pool: &PgPool, storage: &dyn Storage, user_id: Uuid) -> Result<bool, AvatarError> {
+    let previous = sqlx::query_scalar::<_, Option<String>>(
+        r#"
+        UPDATE users u SET avatar_version = NULL
+        FROM (SELECT id, avatar_version FROM users WHERE id = $1 FOR UPDATE) old
+        WHERE u.id = old.id
+        RETURNING old.avatar_version
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    match previous {
+        Some(previous) => {
+            remove_version(storage, user_id, &previous).await;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Old images are only garbage once no URL points at them, so failures are
+/// logged rather than returned.
+async fn remove_version(storage: &dyn Storage, user_id: Uuid, version: &str) {
+    let prefix = format!("avatars/{}/{}", user_id, version);
+    if let Err(e) = storage.delete_prefix(&prefix).await {
+        eprintln!("Storage '{}' failed to delete {}: {}", storage.name(), prefix, e);
+    }
+}
+
+/// The stored thumbnail, if `version` is still the user's current avatar.
+pub async fn get_thumbnail(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    user_id: Uuid,
+    version: &str,
+    size: &str,
+) -> Result<Option<Vec<u8>>, AvatarError> {
+    if !AVATAR_SIZES.iter().any(|(name, _)| *name == size) {
+        return Ok(None);
+    }
+    let current = sqlx::query_scalar::<_, Option<String>>("SELECT avatar_version FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .flatten();
+    if current.as_deref() != Some(version) {
+        return Ok(None);
+    }
+    storage
+        .get(&avatar_key(user_id, version, size))
+        .await
+        .map_err(AvatarError::Storage)
+}
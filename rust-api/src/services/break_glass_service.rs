@@ -0,0 +1,155 @@
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::models::break_glass::{BreakGlassActivation, BreakGlassGrant, CreateBreakGlassGrantPayload};
+
+/// The policy must be active: a draft would otherwise take effect without going
+/// through its tests and approval. Nobody grants themselves break-glass access.
+pub async fn check_grant(
+    executor: impl PgExecutor<'_>,
+    created_by: Uuid,
+    payload: &CreateBreakGlassGrantPayload,
+) -> sqlx::Result<()> {
+    if payload.user_id == created_by {
+        return Err(sqlx::Error::Protocol("Break-glass access can't be granted to oneself".into()));
+    }
+
+    let status = sqlx::query_scalar::<_, String>("SELECT status::TEXT FROM policies WHERE id = $1")
+        .bind(payload.policy_id)
+        .fetch_optional(executor)
+        .await?;
+    match status.as_deref() {
+        None => Err(sqlx::Error::Protocol("Unknown policy".into())),
+        Some("active") => Ok(()),
+        Some(status) => Err(sqlx::Error::Protocol(format!("Break-glass grants need an active policy, not a {} one", status))),
+    }
+}
+
+pub async fn create_grant(
+    conn: &mut PgConnection,
+    created_by: Uuid,
+    payload: &CreateBreakGlassGrantPayload,
+) -> sqlx::Result<BreakGlassGrant> {
+    check_grant(&mut *conn, created_by, payload).await?;
+
+    sqlx::query_as::<_, BreakGlassGrant>(
+        r#"
+        INSERT INTO break_glass_grants (user_id, policy_id, max_duration_minutes, created_by)
+        VALUES ($1, $2, COALESCE($3, 60), $4)
+        RETURNING id, user_id, policy_id, max_duration_minutes, created_by, created_at, revoked_at
+        "#
+    )
+    .bind(payload.user_id)
+    .bind(payload.policy_id)
+    .bind(payload.max_duration_minutes)
+    .bind(created_by)
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn list_grants(pool: &PgPool) -> sqlx::Result<Vec<BreakGlassGrant>> {
+    sqlx::query_as::<_, BreakGlassGrant>(
+        r#"
+        SELECT id, user_id, policy_id, max_duration_minutes, created_by, created_at, revoked_at
+        FROM break_glass_grants
+        ORDER BY created_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn list_grants_for_user(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<BreakGlassGrant>> {
+    sqlx::query_as::<_, BreakGlassGrant>(
+        r#"
+        SELECT id, user_id, policy_id, max_duration_minutes, created_by, created_at, revoked_at
+        FROM break_glass_grants
+        WHERE user_id = $1 AND revoked_at IS NULL
+        ORDER BY created_at DESC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_grant(pool: &PgPool, id: Uuid) -> sqlx::Result<BreakGlassGrant> {
+    sqlx::query_as::<_, BreakGlassGrant>(
+        r#"
+        SELECT id, user_id, policy_id, max_duration_minutes, created_by, created_at, revoked_at
+        FROM break_glass_grants
+        WHERE id = $1
+        "#
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Revokes a grant and closes any window opened with it.
+pub async fn revoke_grant(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        "UPDATE break_glass_grants SET revoked_at = CURRENT_TIMESTAMP WHERE id = $1 AND revoked_at IS NULL"
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "UPDATE break_glass_activations SET ended_at = CURRENT_TIMESTAMP WHERE grant_id = $1 AND ended_at IS NULL"
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn activate(
+    pool: &PgPool,
+    grant: &BreakGlassGrant,
+    justification: &str,
+    duration_minutes: i32,
+) -> sqlx::Result<BreakGlassActivation> {
+    sqlx::query_as::<_, BreakGlassActivation>(
+        r#"
+        INSERT INTO break_glass_activations (grant_id, user_id, justification, expires_at)
+        VALUES ($1, $2, $3, CURRENT_TIMESTAMP + make_interval(mins => $4))
+        RETURNING id, grant_id, user_id, justification, activated_at, expires_at, ended_at
+        "#
+    )
+    .bind(grant.id)
+    .bind(grant.user_id)
+    .bind(justification)
+    .bind(duration_minutes)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn list_activations(pool: &PgPool) -> sqlx::Result<Vec<BreakGlassActivation>> {
+    sqlx::query_as::<_, BreakGlassActivation>(
+        r#"
+        SELECT id, grant_id, user_id, justification, activated_at, expires_at, ended_at
+        FROM break_glass_activations
+        ORDER BY activated_at DESC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Ends the user's own open window early.
+pub async fn end_activation(pool: &PgPool, id: Uuid, user_id: Uuid) -> sqlx::Result<Option<BreakGlassActivation>> {
+    sqlx::query_as::<_, BreakGlassActivation>(
+        r#"
+        UPDATE break_glass_activations
+        SET ended_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND user_id = $2 AND ended_at IS NULL AND expires_at > CURRENT_TIMESTAMP
+        RETURNING id, grant_id, user_id, justification, activated_at, expires_at, ended_at
+        "#
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,122 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::delegation::{CreateDelegationPayload, PermissionDelegation};
+use crate::models::row_filter::RowFilter;
+use crate::models::user::User;
+use crate::services::auth_service;
+use crate::utils::auth::request_context;
+use crate::utils::permissions;
+
+const DELEGATION_COLUMNS: &str = "id, delegator_id, delegate_id, action, resource, starts_on, ends_on, created_at, revoked_at";
+
+/// Creates one delegation row per permission. Every permission must be concrete,
+/// registered, and held by the delegator through their own policies.
+pub async fn create_delegations(
+    pool: &PgPool,
+    delegator: &User,
+    payload: CreateDelegationPayload,
+) -> sqlx::Result<Vec<PermissionDelegation>> {
+    if payload.delegate_id == delegator.id {
+        return Err(sqlx::Error::Protocol("Cannot delegate to yourself".into()));
+    }
+    if payload.ends_on < payload.starts_on {
+        return Err(sqlx::Error::Protocol("Delegation ends before it starts".into()));
+    }
+    if payload.permissions.is_empty() {
+        return Err(sqlx::Error::Protocol("No permissions to delegate".into()));
+    }
+
+    // Only the delegator's own grants count: nothing received by delegation or break-glass.
+    let own_rules: Vec<_> = auth_service::fetch_rules(pool, Some(delegator.id), delegator.role_id, None)
+        .await?
+        .into_iter()
+        .filter(|r| r.delegator_id.is_none() && r.break_glass_activation_id.is_none())
+        .collect();
+    let context = request_context();
+
+    for p in &payload.permissions {
+        let concrete = p.action != permissions::WILDCARD && p.resource != permissions::WILDCARD;
+        if !concrete || !permissions::is_known_action(&p.action) || !permissions::is_known_resource(&p.resource) {
+            return Err(sqlx::Error::Protocol(format!("Unknown permission '{} {}'", p.action, p.resource)));
+        }
+        // Held for at least some records; scoped rules stay scoped to the delegator
+        if auth_service::partial_evaluate(&own_rules, &p.action, &p.resource, delegator.id, &context) == RowFilter::Nothing {
+            return Err(sqlx::Error::Protocol(format!("Delegator does not hold '{} {}'", p.action, p.resource)));
+        }
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut created = Vec::with_capacity(payload.permissions.len());
+    for p in &payload.permissions {
+        let delegation = sqlx::query_as::<_, PermissionDelegation>(&format!(
+            r#"
+            INSERT INTO permission_delegations (delegator_id, delegate_id, action, resource, starts_on, ends_on)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING {}
+            "#,
+            DELEGATION_COLUMNS
+        ))
+        .bind(delegator.id)
+        .bind(payload.delegate_id)
+        .bind(&p.action)
+        .bind(&p.resource)
+        .bind(payload.starts_on)
+        .bind(payload.ends_on)
+        .fetch_one(&mut *tx)
+        .await?;
+        created.push(delegation);
+    }
+    tx.commit().await?;
+
+    Ok(created)
+}
+
+pub async fn list_delegations(pool: &PgPool) -> sqlx::Result<Vec<PermissionDelegation>> {
+    sqlx::query_as::<_, PermissionDelegation>(&format!(
+        "SELECT {} FROM permission_delegations ORDER BY created_at DESC",
+        DELEGATION_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn list_given(pool: &PgPool, delegator_id: Uuid) -> sqlx::Result<Vec<PermissionDelegation>> {
+    sqlx::query_as::<_, PermissionDelegation>(&format!(
+        "SELECT {} FROM permission_delegations WHERE delegator_id = $1 ORDER BY starts_on DESC",
+        DELEGATION_COLUMNS
+    ))
+    .bind(delegator_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn list_received(pool: &PgPool, delegate_id: Uuid) -> sqlx::Result<Vec<PermissionDelegation>> {
+    sqlx::query_as::<_, PermissionDelegation>(&format!(
+        "SELECT {} FROM permission_delegations WHERE delegate_id = $1 ORDER BY starts_on DESC",
+        DELEGATION_COLUMNS
+    ))
+    .bind(delegate_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_delegation(pool: &PgPool, id: Uuid) -> sqlx::Result<PermissionDelegation> {
+    sqlx::query_as::<_, PermissionDelegation>(&format!(
+        "SELECT {} FROM permission_delegations WHERE id = $1",
+        DELEGATION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn revoke_delegation(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        "UPDATE permission_delegations SET revoked_at = CURRENT_TIMESTAMP WHERE id = $1 AND revoked_at IS NULL"
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,160 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::employee_profile::{EmployeeProfile, EmployeeProfileChange, UpdateEmployeeProfilePayload};
+use crate::services::org_service;
+
+pub const EMPLOYMENT_TYPES: &[&str] = &["full_time", "part_time", "contractor", "intern"];
+
+const PROFILE_COLUMNS: &str = "u.id AS user_id, u.department, u.location, u.job_title, u.manager_id, m.username AS manager_name, u.hire_date, u.employment_type, u.phone";
+
+const MAX_TEXT_LEN: usize = 100;
+const MAX_PHONE_LEN: usize = 30;
+
+pub async fn get_profile(pool: &PgPool, user_id: Uuid) -> sqlx::Result<EmployeeProfile> {
+    sqlx::query_as::<_, EmployeeProfile>(&format!(
+        "SELECT {} FROM users u LEFT JOIN users m ON m.id = u.manager_id WHERE u.id = $1",
+        PROFILE_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Trimmed value, `None` when blank. Errors when longer than `max` characters.
+pub fn clean(field: &str, value: &Option<String>, max: usize) -> sqlx::Result<Option<String>> {
+    let value = value.as_deref().map(str::trim).filter(|v| !v.is_empty());
+    if value.is_some_and(|v| v.chars().count() > max) {
+        return Err(sqlx::Error::Protocol(format!("{} is longer than {} characters", field, max)));
+    }
+    Ok(value.map(str::to_string))
+}
+
+/// Like `clean`, and only digits, spaces and + - ( ) . are allowed.
+pub fn clean_phone(field: &str, value: &Option<String>) -> sqlx::Result<Option<String>> {
+    let phone = clean(field, value, MAX_PHONE_LEN)?;
+    if phone
+        .as_deref()
+        .is_some_and(|p| !p.chars().all(|c| c.is_ascii_digit() || " +-().".contains(c)))
+    {
+        return Err(sqlx::Error::Protocol(format!("{} may only contain digits, spaces and + - ( ) .", field)));
+    }
+    Ok(phone)
+}
+
+/// Replaces the profile and records one history entry per field that changed,
+/// in the same transaction. Managers that would create a reporting loop are refused. Invalid values come back as `sqlx::Error::Protocol`.
+pub async fn update_profile(
+    pool: &PgPool,
+    user_id: Uuid,
+    payload: &UpdateEmployeeProfilePayload,
+    changed_by: Uuid,
+) -> sqlx::Result<EmployeeProfile> {
+    let department = clean("Department", &payload.department, MAX_TEXT_LEN)?;
+    let location = clean("Location", &payload.location, MAX_TEXT_LEN)?;
+    let job_title = clean("Job title", &payload.job_title, MAX_TEXT_LEN)?;
+    let phone = clean_phone("Phone", &payload.phone)?;
+    let employment_type = clean("Employment type", &payload.employment_type, MAX_TEXT_LEN)?;
+    if let Some(kind) = &employment_type
+        && !EMPLOYMENT_TYPES.contains(&kind.as_str())
+    {
+        return Err(sqlx::Error::Protocol(format!("Unknown employment type '{}'", kind)));
+    }
+    if payload.manager_id == Some(user_id) {
+        return Err(sqlx::Error::Protocol("A user can't be their own manager".into()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let before = sqlx::query_as::<_, EmployeeProfile>(&format!(
+        "SELECT {} FROM users u LEFT JOIN users m ON m.id = u.manager_id WHERE u.id = $1 FOR UPDATE OF u",
+        PROFILE_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    // A new manager must not already report to this user, directly or not.
+    // The lock serializes manager changes so two of them can't close a loop together.
+    if let Some(manager_id) = payload.manager_id
+        && before.manager_id != Some(manager_id)
+    {
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('users.manager_id'))")
+            .execute(&mut *tx)
+            .await?;
+        if org_service::is_in_management_chain(&mut *tx, user_id, manager_id).await? {
+            return Err(sqlx::Error::Protocol("The new manager reports to this user; that would make a loop".into()));
+        }
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET department = $2, location = $3, job_title = $4, manager_id = $5,
+            hire_date = $6, employment_type = $7, phone = $8, updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        "#
+    )
+    .bind(user_id)
+    .bind(&department)
+    .bind(&location)
+    .bind(&job_title)
+    .bind(payload.manager_id)
+    .bind(payload.hire_date)
+    .bind(&employment_type)
+    .bind(&phone)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            sqlx::Error::Protocol("Unknown manager".into())
+        }
+        other => other,
+    })?;
+
+    let changes = [
+        ("department", before.department, department),
+        ("location", before.location, location),
+        ("job_title", before.job_title, job_title),
+        ("manager_id", before.manager_id.map(|v| v.to_string()), payload.manager_id.map(|v| v.to_string())),
+        ("hire_date", before.hire_date.map(|v| v.to_string()), payload.hire_date.map(|v| v.to_string())),
+        ("employment_type", before.employment_type, employment_type),
+        ("phone", before.phone, phone),
+    ];
+    for (field, old_value, new_value) in changes {
+        if old_value == new_value {
+            continue;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO employee_profile_changes (user_id, field, old_value, new_value, changed_by)
+            VALUES ($1, $2, $3, $4, $5)
+            "#
+        )
+        .bind(user_id)
+        .bind(field)
+        .bind(old_value)
+        .bind(new_value)
+        .bind(changed_by)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    get_profile(pool, user_id).await
+}
+
+/// Profile changes of the user, newest first.
+pub async fn list_history(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<EmployeeProfileChange>> {
+    sqlx::query_as::<_, EmployeeProfileChange>(
+        r#"
+        SELECT id, user_id, field, old_value, new_value, changed_by, changed_at
+        FROM employee_profile_changes
+        WHERE user_id = $1
+        ORDER BY changed_at DESC, field ASC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
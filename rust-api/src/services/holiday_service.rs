@@ -0,0 +1,841 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Datelike, Days, NaiveDate};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::holiday::{
+    Holiday, HolidayCalendar, HolidayCalendarPayload, HolidayImportQuery, HolidayImportReport, HolidayPayload,
+    ImportedHoliday, SkippedEvent, WorkingDays,
+};
+use crate::services::employee_profile_service::clean;
+
+const MAX_NAME_LEN: usize = 100;
+const MAX_DESCRIPTION_LEN: usize = 500;
+const MAX_LOCATION_LEN: usize = 100;
+const MAX_HOLIDAY_NAME_LEN: usize = 200;
+
+/// Longest event an import turns into holidays, one per day
+const MAX_EVENT_DAYS: u64 = 31;
+
+/// Longest range the working-day calculator counts
+const MAX_RANGE_DAYS: i64 = 366;
+
+/// Saturday and Sunday, for users no calendar applies to
+const DEFAULT_WEEKEND: [i32; 2] = [6, 7];
+
+const CALENDAR_COLUMNS: &str = "c.id, c.name, c.description, c.locations, c.weekend_days, c.is_default, \
+    (SELECT COUNT(*) FROM holidays h WHERE h.calendar_id = c.id) AS holiday_count, c.created_at, c.updated_at";
+
+const HOLIDAY_COLUMNS: &str = "id, calendar_id, holiday_date, name, source_uid, created_at";
+
+fn invalid<T>(msg: impl Into<String>) -> sqlx::Result<T> {
+    Err(sqlx::Error::Protocol(msg.into()))
+}
+
+pub async fn list_calendars(pool: &PgPool) -> sqlx::Result<Vec<HolidayCalendar>> {
+    sqlx::query_as::<_, HolidayCalendar>(&format!(
+        "SELECT {} FROM holiday_calendars c ORDER BY c.is_default DESC, c.name",
+        CALENDAR_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_calendar(pool: &PgPool, id: Uuid) -> sqlx::Result<HolidayCalendar> {
+    sqlx::query_as::<_, HolidayCalendar>(&format!("SELECT {} FROM holiday_calendars c WHERE c.id = $1", CALENDAR_COLUMNS))
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Checks the payload of calendar `id` (`None` for a new one). Returns it with
+/// the text cleaned, locations de-duplicated and weekend days sorted.
+async fn check_calendar(pool: &PgPool, id: Option<Uuid>, payload: &HolidayCalendarPayload) -> sqlx::Result<HolidayCalendarPayload> {
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return invalid(format!("Name must be 1 to {} characters", MAX_NAME_LEN));
+    }
+
+    let mut locations: Vec<String> = Vec::new();
+    for location in payload.locations.iter().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+        if location.chars().count() > MAX_LOCATION_LEN {
+            return invalid(format!("Locations can't be longer than {} characters", MAX_LOCATION_LEN));
+        }
+        if !locations.iter().any(|l| l.to_lowercase() == location.to_lowercase()) {
+            locations.push(location.to_string());
+        }
+    }
+    let lowered: Vec<String> = locations.iter().map(|l| l.to_lowercase()).collect();
+    let taken = sqlx::query_as::<_, (String, String)>(
+        r#"
+        SELECT l, c.name FROM holiday_calendars c, unnest(c.locations) l
+        WHERE c.id IS DISTINCT FROM $1 AND lower(l) = ANY($2)
+        LIMIT 1
+        "#,
+    )
+    .bind(id)
+    .bind(&lowered)
+    .fetch_optional(pool)
+    .await?;
+    if let Some((location, calendar)) = taken {
+        return invalid(format!("'{}' already belongs to the {} calendar", location, calendar));
+    }
+
+    let mut weekend_days = payload.weekend_days.clone().unwrap_or_else(|| DEFAULT_WEEKEND.to_vec());
+    if weekend_days.iter().any(|d| !(1..=7).contains(d)) {
+        return invalid("weekend_days must be ISO weekdays, 1 (Monday) to 7 (Sunday)");
+    }
+    weekend_days.sort_unstable();
+    weekend_days.dedup();
+    if weekend_days.len() == 7 {
+        return invalid("weekend_days can't cover the whole week");
+    }
+
+    Ok(HolidayCalendarPayload {
+        name: name.to_string(),
+        description: clean("description", &payload.description, MAX_DESCRIPTION_LEN)?,
+        locations,
+        weekend_days: Some(weekend_days),
+        is_default: payload.is_default,
+    })
+}
+
+pub async fn create_calendar(pool: &PgPool, payload: &HolidayCalendarPayload) -> sqlx::Result<HolidayCalendar> {
+    let payload = check_calendar(pool, None, payload).await?;
+
+    let mut tx = pool.begin().await?;
+    if payload.is_default {
+        sqlx::query("UPDATE holiday_calendars SET is_default = FALSE, updated_at = CURRENT_TIMESTAMP WHERE is_default")
+            .execute(&mut *tx)
+            .await?;
+    }
+    let id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO holiday_calendars (name, description, locations, weekend_days, is_default)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(&payload.name)
+    .bind(&payload.description)
+    .bind(&payload.locations)
+    .bind(&payload.weekend_days)
+    .bind(payload.is_default)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    get_calendar(pool, id).await
+}
+
+/// Replaces every setting. Requests already submitted keep their day counts.
+/// Clearing `is_default` on the default calendar leaves users without a
+/// calendar of their own on a Saturday/Sunday weekend with no holidays.
+pub async fn update_calendar(pool: &PgPool, id: Uuid, payload: &HolidayCalendarPayload) -> sqlx::Result<HolidayCalendar> {
+    let payload = check_calendar(pool, Some(id), payload).await?;
+
+    let mut tx = pool.begin().await?;
+    if payload.is_default {
+        sqlx::query(
+            "UPDATE holiday_calendars SET is_default = FALSE, updated_at = CURRENT_TIMESTAMP WHERE is_default AND id <> $1",
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    let result = sqlx::query(
+        r#"
+        UPDATE holiday_calendars
+        SET name = $2, description = $3, locations = $4, weekend_days = $5, is_default = $6,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&payload.name)
+    .bind(&payload.description)
+    .bind(&payload.locations)
+    .bind(&payload.weekend_days)
+    .bind(payload.is_default)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    tx.commit().await?;
+
+    get_calendar(pool, id).await
+}
+
+/// Deletes the calendar and its holidays; its locations fall back to the default calendar.
+pub async fn delete_calendar(pool: &PgPool, id: Uuid) -> sqlx::Result<()> {
+    let result = sqlx::query("DELETE FROM holiday_calendars WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+pub async fn list_holidays(pool: &PgPool, calendar_id: Uuid, year: Option<i32>) -> sqlx::Result<Vec<Holiday>> {
+    get_calendar(pool, calendar_id).await?;
+
+    sqlx::query_as::<_, Holiday>(&format!(
+        r#"
+        SELECT {} FROM holidays
+        WHERE calendar_id = $1 AND ($2::INTEGER IS NULL OR EXTRACT(YEAR FROM holiday_date) = $2)
+        ORDER BY holiday_date
+        "#,
+        HOLIDAY_COLUMNS
+    ))
+    .bind(calendar_id)
+    .bind(year)
+    .fetch_all(pool)
+    .await
+}
+
+fn check_holiday_name(name: &str) -> sqlx::Result<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_HOLIDAY_NAME_LEN {
+        return invalid(format!("Holiday names must be 1 to {} characters", MAX_HOLIDAY_NAME_LEN));
+    }
+    Ok(name.to_string())
+}
+
+/// Fails with a unique violation when the calendar already has a holiday that day.
+pub async fn add_holiday(pool: &PgPool, calendar_id: Uuid, payload: &HolidayPayload) -> sqlx::Result<Holiday> {
+    let name = check_holiday_name(&payload.name)?;
+    get_calendar(pool, calendar_id).await?;
+
+    sqlx::query_as::<_, Holiday>(&format!(
+        "INSERT INTO holidays (calendar_id, holiday_date, name) VALUES ($1, $2, $3) RETURNING {}",
+        HOLIDAY_COLUMNS
+    ))
+    .bind(calendar_id)
+    .bind(payload.holiday_date)
+    .bind(&name)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn update_holiday(pool: &PgPool, calendar_id: Uuid, id: Uuid, payload: &HolidayPayload) -> sqlx::Result<Holiday> {
+    let name = check_holiday_name(&payload.name)?;
+
+    sqlx::query_as::<_, Holiday>(&format!(
+        "UPDATE holidays SET holiday_date = $3, name = $4 WHERE id = $1 AND calendar_id = $2 RETURNING {}",
+        HOLIDAY_COLUMNS
+    ))
+    .bind(id)
+    .bind(calendar_id)
+    .bind(payload.holiday_date)
+    .bind(&name)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete_holiday(pool: &PgPool, calendar_id: Uuid, id: Uuid) -> sqlx::Result<()> {
+    let result = sqlx::query("DELETE FROM holidays WHERE id = $1 AND calendar_id = $2")
+        .bind(id)
+        .bind(calendar_id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+/// Content lines of an iCalendar file, with folded lines joined back (RFC 5545 3.1).
+/// Each comes with its line number in the file.
+fn unfold(text: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        if let Some(rest) = line.strip_prefix([' ', '\t'])
+            && let Some((_, last)) = lines.last_mut()
+        {
+            last.push_str(rest);
+        } else if !line.trim().is_empty() {
+            lines.push((number + 1, line.to_string()));
+        }
+    }
+    lines
+}
+
+/// Upper-cased name and value of a content line; parameters are dropped.
+fn split_property(line: &str) -> (String, &str) {
+    // The value starts at the first colon outside a quoted parameter value
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    });
+    let (head, value) = match colon {
+        Some(i) => (&line[..i], &line[i + 1..]),
+        None => (line, ""),
+    };
+    let name = head.split(';').next().unwrap_or_default();
+    (name.trim().to_ascii_uppercase(), value.trim())
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => text.push(' '),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Date of a DATE (`20261225`) or DATE-TIME (`20261225T090000Z`) value, and
+/// whether it is a DATE-TIME at midnight. Time zones are ignored.
+fn parse_ical_date(value: &str) -> Option<(NaiveDate, bool)> {
+    let date = NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()?;
+    let midnight = value.get(8..).is_some_and(|time| time.starts_with("T000000"));
+    Some((date, midnight))
+}
+
+/// Whole days of a DURATION such as `P1D` or `P2W`.
+fn parse_duration_days(value: &str) -> Option<u64> {
+    let value = value.strip_prefix('+').unwrap_or(value).strip_prefix('P')?;
+    if let Some(weeks) = value.strip_suffix('W') {
+        return weeks.parse::<u64>().ok().map(|w| w * 7);
+    }
+    value.split('T').next()?.strip_suffix('D')?.parse().ok()
+}
+
+#[derive(Default)]
+struct Event {
+    line: usize,
+    summary: Option<String>,
+    uid: Option<String>,
+    start: Option<String>,
+    end: Option<String>,
+    duration: Option<String>,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDate>,
+    cancelled: bool,
+}
+
+impl Event {
+    /// Every day the event covers, recurrences up to `through` included.
+    /// `Err` explains why it gives none.
+    fn days(&self, through: NaiveDate) -> Result<Vec<NaiveDate>, String> {
+        if self.cancelled {
+            return Err("Cancelled".into());
+        }
+        let start = self.start.as_deref().ok_or("No DTSTART")?;
+        let (first, _) = parse_ical_date(start).ok_or("Unreadable DTSTART")?;
+        let all_day = !start.contains('T');
+
+        // Number of days covered by one occurrence. DTEND is exclusive for
+        // all-day events, and for timed ones ending at midnight.
+        let length = match (&self.end, &self.duration) {
+            (Some(end), _) => {
+                let (last, midnight) = parse_ical_date(end).ok_or("Unreadable DTEND")?;
+                let exclusive = all_day || (midnight && last > first);
+                let span = (last - first).num_days() + if exclusive { 0 } else { 1 };
+                u64::try_from(span).map_err(|_| "DTEND is before DTSTART")?.max(1)
+            }
+            (None, Some(duration)) => parse_duration_days(duration).ok_or("Unsupported DURATION")?.max(1),
+            (None, None) => 1,
+        };
+        if length > MAX_EVENT_DAYS {
+            return Err(format!("Longer than {} days", MAX_EVENT_DAYS));
+        }
+
+        let mut starts = vec![first];
+        if let Some(rule) = &self.rrule {
+            starts = yearly_occurrences(first, rule, through)?;
+        }
+
+        let mut days = Vec::new();
+        for start in starts.into_iter().filter(|s| !self.exdates.contains(s)) {
+            for offset in 0..length {
+                days.extend(start.checked_add_days(Days::new(offset)));
+            }
+        }
+        Ok(days)
+    }
+}
+
+/// Start dates of a `FREQ=YEARLY` rule on the date of `first`, the only kind
+/// holiday feeds commonly use. Open-ended rules stop at `through`.
+fn yearly_occurrences(first: NaiveDate, rule: &str, through: NaiveDate) -> Result<Vec<NaiveDate>, String> {
+    let unsupported = || "Only yearly recurrence on a fixed date is supported".to_string();
+    let mut yearly = false;
+    let mut interval = 1;
+    let mut count: Option<usize> = None;
+    let mut until = through;
+    for part in rule.split(';').filter(|p| !p.is_empty()) {
+        let (key, value) = part.split_once('=').ok_or_else(unsupported)?;
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => yearly = value.eq_ignore_ascii_case("YEARLY"),
+            "INTERVAL" => interval = value.parse::<i32>().ok().filter(|i| *i > 0).ok_or_else(unsupported)?,
+            "COUNT" => count = Some(value.parse().map_err(|_| unsupported())?),
+            "UNTIL" => until = parse_ical_date(value).ok_or_else(unsupported)?.0.min(through),
+            "WKST" => {}
+            _ => return Err(unsupported()),
+        }
+    }
+    if !yearly {
+        return Err(unsupported());
+    }
+
+    let mut starts = Vec::new();
+    let mut year = first.year();
+    while count.is_none_or(|c| starts.len() < c) {
+        // 29 February only recurs in leap years
+        if let Some(date) = first.with_year(year) {
+            if date > until {
+                break;
+            }
+            starts.push(date);
+        } else if year > until.year() {
+            break;
+        }
+        year += interval;
+    }
+    Ok(starts)
+}
+
+struct ParsedCalendar {
+    events: usize,
+    holidays: Vec<ImportedHoliday>,
+    skipped: Vec<SkippedEvent>,
+}
+
+/// Reads the holidays of an iCalendar file: one per day of each event. Yearly
+/// recurring events are expanded up to `through`. When two events share a
+/// day the first one keeps it.
+fn parse_ical(text: &str, through: NaiveDate) -> sqlx::Result<ParsedCalendar> {
+    let lines = unfold(text.trim_start_matches('\u{feff}'));
+    if !lines.first().is_some_and(|(_, l)| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return invalid("Not an iCalendar file");
+    }
+
+    let mut parsed = ParsedCalendar { events: 0, holidays: Vec::new(), skipped: Vec::new() };
+    let mut taken: HashMap<NaiveDate, String> = HashMap::new();
+    let mut event: Option<Event> = None;
+    // Components nested in the event, such as alarms
+    let mut nested = 0;
+
+    for (number, line) in &lines {
+        let (name, value) = split_property(line);
+        match (name.as_str(), event.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                event = Some(Event { line: *number, ..Event::default() });
+            }
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let Some(done) = event.take() else { continue };
+                parsed.events += 1;
+                let summary = done.summary.clone().filter(|s| !s.is_empty());
+                let skip = |reason: String| SkippedEvent { line: done.line, summary: summary.clone(), reason };
+                let Some(holiday_name) = &summary else {
+                    parsed.skipped.push(skip("No SUMMARY".into()));
+                    continue;
+                };
+                let holiday_name: String = holiday_name.chars().take(MAX_HOLIDAY_NAME_LEN).collect();
+                match done.days(through) {
+                    Err(reason) => parsed.skipped.push(skip(reason)),
+                    Ok(days) => {
+                        for day in days {
+                            if let Some(other) = taken.get(&day) {
+                                parsed.skipped.push(skip(format!("{} is already taken by {}", day, other)));
+                                continue;
+                            }
+                            taken.insert(day, holiday_name.clone());
+                            parsed.holidays.push(ImportedHoliday {
+                                holiday_date: day,
+                                name: holiday_name.clone(),
+                                uid: done.uid.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+            (_, Some(_)) if nested > 0 => {}
+            ("SUMMARY", Some(e)) => e.summary = Some(unescape_text(value)),
+            ("UID", Some(e)) => e.uid = Some(value.to_string()).filter(|u| !u.is_empty()),
+            ("DTSTART", Some(e)) => e.start = Some(value.to_string()),
+            ("DTEND", Some(e)) => e.end = Some(value.to_string()),
+            ("DURATION", Some(e)) => e.duration = Some(value.to_string()),
+            ("RRULE", Some(e)) => e.rrule = Some(value.to_string()),
+            ("EXDATE", Some(e)) => e.exdates.extend(value.split(',').filter_map(|d| parse_ical_date(d.trim())).map(|(d, _)| d)),
+            ("STATUS", Some(e)) => e.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+
+    parsed.holidays.sort_by_key(|h| h.holiday_date);
+    Ok(parsed)
+}
+
+/// Adds the holidays of an iCalendar file to the calendar. Dates it already
+/// has take the imported name; with `replace`, dates missing from the file
+/// are removed. Open-ended yearly events are expanded through the end of next year.
+pub async fn import_ical(
+    pool: &PgPool,
+    calendar_id: Uuid,
+    text: &str,
+    query: &HolidayImportQuery,
+    today: NaiveDate,
+) -> sqlx::Result<HolidayImportReport> {
+    get_calendar(pool, calendar_id).await?;
+
+    let through = NaiveDate::from_ymd_opt(today.year() + 1, 12, 31).unwrap_or(today);
+    let parsed = parse_ical(text, through)?;
+
+    let existing: HashMap<NaiveDate, String> =
+        sqlx::query_as::<_, (NaiveDate, String)>("SELECT holiday_date, name FROM holidays WHERE calendar_id = $1")
+            .bind(calendar_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+    let imported: HashSet<NaiveDate> = parsed.holidays.iter().map(|h| h.holiday_date).collect();
+
+    let mut report = HolidayImportReport {
+        dry_run: query.dry_run,
+        committed: false,
+        events: parsed.events,
+        created: 0,
+        updated: 0,
+        unchanged: 0,
+        removed: 0,
+        holidays: Vec::new(),
+        skipped: parsed.skipped,
+    };
+    for holiday in &parsed.holidays {
+        match existing.get(&holiday.holiday_date) {
+            None => report.created += 1,
+            Some(name) if *name != holiday.name => report.updated += 1,
+            Some(_) => report.unchanged += 1,
+        }
+    }
+    if query.replace {
+        report.removed = existing.keys().filter(|d| !imported.contains(d)).count();
+    }
+    report.holidays = parsed.holidays;
+
+    if query.dry_run {
+        return Ok(report);
+    }
+    if report.holidays.is_empty() {
+        return invalid("The file has no usable events");
+    }
+
+    let dates: Vec<NaiveDate> = report.holidays.iter().map(|h| h.holiday_date).collect();
+    let names: Vec<String> = report.holidays.iter().map(|h| h.name.clone()).collect();
+    let uids: Vec<Option<String>> = report.holidays.iter().map(|h| h.uid.clone()).collect();
+
+    let mut tx = pool.begin().await?;
+    if query.replace {
+        sqlx::query("DELETE FROM holidays WHERE calendar_id = $1 AND NOT (holiday_date = ANY($2))")
+            .bind(calendar_id)
+            .bind(&dates)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query(
+        r#"
+        INSERT INTO holidays (calendar_id, holiday_date, name, source_uid)
+        SELECT $1, d, n, u FROM UNNEST($2::DATE[], $3::TEXT[], $4::TEXT[]) AS i(d, n, u)
+        ON CONFLICT (calendar_id, holiday_date) DO UPDATE SET name = EXCLUDED.name, source_uid = EXCLUDED.source_uid
+        "#,
+    )
+    .bind(calendar_id)
+    .bind(&dates)
+    .bind(&names)
+    .bind(&uids)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE holiday_calendars SET updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+        .bind(calendar_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    report.committed = true;
+    Ok(report)
+}
+
+/// The calendar of users at `location`: the one listing it, or else the default one.
+pub async fn calendar_for_location(pool: &PgPool, location: Option<&str>) -> sqlx::Result<Option<HolidayCalendar>> {
+    sqlx::query_as::<_, HolidayCalendar>(&format!(
+        r#"
+        SELECT {} FROM holiday_calendars c
+        WHERE c.is_default OR EXISTS (SELECT 1 FROM unnest(c.locations) l WHERE lower(l) = lower(trim($1)))
+        ORDER BY c.is_default
+        LIMIT 1
+        "#,
+        CALENDAR_COLUMNS
+    ))
+    .bind(location)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Days a leave request of the user between these dates (inclusive) takes:
+/// every day except the weekend days and holidays of their calendar.
+pub async fn working_days(pool: &PgPool, user_id: Uuid, start_date: NaiveDate, end_date: NaiveDate) -> sqlx::Result<WorkingDays> {
+    if end_date < start_date {
+        return invalid("end_date is before start_date");
+    }
+    let calendar_days = (end_date - start_date).num_days() + 1;
+    if calendar_days > MAX_RANGE_DAYS {
+        return invalid(format!("Ranges are limited to {} days", MAX_RANGE_DAYS));
+    }
+
+    let location = sqlx::query_scalar::<_, Option<String>>("SELECT location FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+    let calendar = calendar_for_location(pool, location.as_deref()).await?;
+
+    let (weekend, holidays) = match &calendar {
+        Some(calendar) => {
+            let holidays = sqlx::query_as::<_, Holiday>(&format!(
+                "SELECT {} FROM holidays WHERE calendar_id = $1 AND holiday_date BETWEEN $2 AND $3 ORDER BY holiday_date",
+                HOLIDAY_COLUMNS
+            ))
+            .bind(calendar.id)
+            .bind(start_date)
+            .bind(end_date)
+            .fetch_all(pool)
+            .await?;
+            (calendar.weekend_days.clone(), holidays)
+        }
+        None => (DEFAULT_WEEKEND.to_vec(), Vec::new()),
+    };
+
+    let mut counted = count_working_days(start_date, end_date, &weekend, holidays);
+    counted.calendar_id = calendar.as_ref().map(|c| c.id);
+    counted.calendar = calendar.map(|c| c.name);
+    Ok(counted)
+}
+
+/// Working days between two dates (inclusive) given the weekend days (ISO,
+/// Monday = 1) and the holidays in the range. No calendar is set on the result.
+fn count_working_days(start_date: NaiveDate, end_date: NaiveDate, weekend: &[i32], holidays: Vec<Holiday>) -> WorkingDays {
+    let calendar_days = (end_date - start_date).num_days() + 1;
+    let is_weekend = |d: &NaiveDate| weekend.contains(&(d.weekday().number_from_monday() as i32));
+    let weekend_days = start_date.iter_days().take_while(|d| *d <= end_date).filter(is_weekend).count() as i64;
+    // A holiday on a weekend day isn't counted twice
+    let holidays: Vec<Holiday> = holidays.into_iter().filter(|h| !is_weekend(&h.holiday_date)).collect();
+
+    WorkingDays {
+        start_date,
+        end_date,
+        calendar_id: None,
+        calendar: None,
+        calendar_days,
+        weekend_days,
+        days: (calendar_days - weekend_days - holidays.len() as i64) as f64,
+        holidays,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    fn holiday(day: NaiveDate) -> Holiday {
+        Holiday {
+            id: Uuid::new_v4(),
+            calendar_id: Uuid::nil(),
+            holiday_date: day,
+            name: "Holiday".to_string(),
+            source_uid: None,
+            created_at: Default::default(),
+        }
+    }
+
+    fn dates(parsed: &ParsedCalendar) -> Vec<NaiveDate> {
+        parsed.holidays.iter().map(|h| h.holiday_date).collect()
+    }
+
+    #[test]
+    fn unfold_joins_continuation_lines_and_keeps_line_numbers() {
+        let lines = unfold("BEGIN:VEVENT\r\nSUMMARY:Long\r\n  holiday\r\n\tname\r\n\r\nEND:VEVENT\r\n");
+        assert_eq!(
+            lines,
+            vec![
+                (1, "BEGIN:VEVENT".to_string()),
+                (2, "SUMMARY:Long holidayname".to_string()),
+                (6, "END:VEVENT".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn yearly_occurrences_follow_count_interval_and_until() {
+        let first = date(2026, 12, 25);
+        let through = date(2030, 12, 31);
+
+        assert_eq!(
+            yearly_occurrences(first, "FREQ=YEARLY", through).unwrap(),
+            vec![date(2026, 12, 25), date(2027, 12, 25), date(2028, 12, 25), date(2029, 12, 25), date(2030, 12, 25)]
+        );
+        assert_eq!(
+            yearly_occurrences(first, "FREQ=YEARLY;COUNT=2", through).unwrap(),
+            vec![date(2026, 12, 25), date(2027, 12, 25)]
+        );
+        assert_eq!(
+            yearly_occurrences(first, "FREQ=YEARLY;INTERVAL=2", through).unwrap(),
+            vec![date(2026, 12, 25), date(2028, 12, 25), date(2030, 12, 25)]
+        );
+        assert_eq!(
+            yearly_occurrences(first, "FREQ=YEARLY;UNTIL=20271231T000000Z", through).unwrap(),
+            vec![date(2026, 12, 25), date(2027, 12, 25)]
+        );
+    }
+
+    #[test]
+    fn leap_day_only_recurs_in_leap_years() {
+        let starts = yearly_occurrences(date(2028, 2, 29), "FREQ=YEARLY", date(2033, 12, 31)).unwrap();
+        assert_eq!(starts, vec![date(2028, 2, 29), date(2032, 2, 29)]);
+    }
+
+    #[test]
+    fn yearly_occurrences_reject_other_rules() {
+        assert!(yearly_occurrences(date(2026, 1, 1), "FREQ=MONTHLY", date(2027, 1, 1)).is_err());
+        assert!(yearly_occurrences(date(2026, 1, 1), "FREQ=YEARLY;BYDAY=MO", date(2027, 1, 1)).is_err());
+        assert!(yearly_occurrences(date(2026, 1, 1), "FREQ=YEARLY;INTERVAL=0", date(2027, 1, 1)).is_err());
+    }
+
+    #[test]
+    fn all_day_events_end_the_day_before_dtend() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nUID:xmas\r\nSUMMARY:Christmas break\r\nDTSTART;VALUE=DATE:20261224\r\n\
+             DTEND;VALUE=DATE:20261227\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Good Friday\r\nDTSTART;VALUE=DATE:20260403\r\nEND:VEVENT\r\n",
+        );
+        let parsed = parse_ical(&text, date(2027, 12, 31)).unwrap();
+
+        assert_eq!(parsed.events, 2);
+        assert!(parsed.skipped.is_empty());
+        assert_eq!(dates(&parsed), vec![date(2026, 4, 3), date(2026, 12, 24), date(2026, 12, 25), date(2026, 12, 26)]);
+        assert_eq!(parsed.holidays[1].name, "Christmas break");
+        assert_eq!(parsed.holidays[1].uid.as_deref(), Some("xmas"));
+    }
+
+    #[test]
+    fn timed_events_and_durations_cover_whole_days() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nSUMMARY:Offsite\r\nDTSTART:20260601T090000Z\r\nDTEND:20260602T170000Z\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Shutdown\r\nDTSTART;VALUE=DATE:20260810\r\nDURATION:P3D\r\nEND:VEVENT\r\n",
+        );
+        let parsed = parse_ical(&text, date(2026, 12, 31)).unwrap();
+
+        assert_eq!(
+            dates(&parsed),
+            vec![date(2026, 6, 1), date(2026, 6, 2), date(2026, 8, 10), date(2026, 8, 11), date(2026, 8, 12)]
+        );
+    }
+
+    #[test]
+    fn recurring_events_skip_exdates() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nSUMMARY:New Year\r\nDTSTART;VALUE=DATE:20260101\r\nRRULE:FREQ=YEARLY\r\n\
+             EXDATE;VALUE=DATE:20270101\r\nEND:VEVENT\r\n",
+        );
+        let parsed = parse_ical(&text, date(2028, 12, 31)).unwrap();
+
+        assert_eq!(dates(&parsed), vec![date(2026, 1, 1), date(2028, 1, 1)]);
+    }
+
+    #[test]
+    fn unusable_events_are_reported_with_their_line() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260101\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Cancelled\r\nDTSTART;VALUE=DATE:20260102\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Sabbatical\r\nDTSTART;VALUE=DATE:20260301\r\nDTEND;VALUE=DATE:20260601\r\nEND:VEVENT\r\n",
+        );
+        let parsed = parse_ical(&text, date(2026, 12, 31)).unwrap();
+
+        assert_eq!(parsed.events, 3);
+        assert!(parsed.holidays.is_empty());
+        let reasons: Vec<(usize, &str)> = parsed.skipped.iter().map(|s| (s.line, s.reason.as_str())).collect();
+        assert_eq!(reasons, vec![(3, "No SUMMARY"), (6, "Cancelled"), (11, "Longer than 31 days")]);
+    }
+
+    #[test]
+    fn the_first_event_keeps_a_shared_day() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nSUMMARY:First\r\nDTSTART;VALUE=DATE:20260501\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Second\r\nDTSTART;VALUE=DATE:20260501\r\nEND:VEVENT\r\n",
+        );
+        let parsed = parse_ical(&text, date(2026, 12, 31)).unwrap();
+
+        assert_eq!(parsed.holidays.len(), 1);
+        assert_eq!(parsed.holidays[0].name, "First");
+        assert_eq!(parsed.skipped[0].reason, "2026-05-01 is already taken by First");
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_calendars() {
+        assert!(parse_ical("hello", date(2026, 12, 31)).is_err());
+        assert!(parse_ical("\u{feff}BEGIN:VCALENDAR\r\nEND:VCALENDAR\r\n", date(2026, 12, 31)).is_ok());
+    }
+
+    #[test]
+    fn working_days_skip_weekends_and_holidays() {
+        // Monday 21 to Sunday 27 December 2026; Christmas is a Friday
+        let counted = count_working_days(date(2026, 12, 21), date(2026, 12, 27), &DEFAULT_WEEKEND, vec![holiday(date(2026, 12, 25))]);
+
+        assert_eq!(counted.calendar_days, 7);
+        assert_eq!(counted.weekend_days, 2);
+        assert_eq!(counted.holidays.len(), 1);
+        assert_eq!(counted.days, 4.0);
+    }
+
+    #[test]
+    fn a_holiday_on_a_weekend_is_not_counted_twice() {
+        // Boxing Day 2026 is a Saturday
+        let counted = count_working_days(
+            date(2026, 12, 21),
+            date(2026, 12, 27),
+            &DEFAULT_WEEKEND,
+            vec![holiday(date(2026, 12, 25)), holiday(date(2026, 12, 26))],
+        );
+
+        assert_eq!(counted.weekend_days, 2);
+        assert_eq!(counted.holidays.iter().map(|h| h.holiday_date).collect::<Vec<_>>(), vec![date(2026, 12, 25)]);
+        assert_eq!(counted.days, 4.0);
+    }
+
+    #[test]
+    fn working_days_use_the_calendar_weekend() {
+        // With a Friday and Saturday weekend, Christmas falls on the weekend
+        let counted = count_working_days(date(2026, 12, 21), date(2026, 12, 27), &[5, 6], vec![holiday(date(2026, 12, 25))]);
+
+        assert_eq!(counted.weekend_days, 2);
+        assert!(counted.holidays.is_empty());
+        assert_eq!(counted.days, 5.0);
+    }
+}
@@ -0,0 +1,222 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::invite::{InviteDetails, UserInvite};
+use crate::models::user::User;
+use crate::services::mail_service::OutgoingMail;
+
+/// How long an invite can be used
+pub const INVITE_TTL_DAYS: i64 = 7;
+
+pub const MIN_PASSWORD_LEN: usize = 8;
+
+const INVITE_COLUMNS: &str = "id, user_id, expires_at, created_by, created_at, accepted_at, revoked_at";
+
+/// Random 64-character token for links sent by mail.
+pub fn generate_token() -> String {
+    thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(64)
+        .map(char::from)
+        .collect()
+}
+
+/// Puts a freshly created user in the invited state.
+pub async fn mark_invited(conn: &mut PgConnection, user_id: Uuid, invited_by: Uuid) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_status_changes (user_id, status, effective_at, reason, changed_by)
+        VALUES ($1, 'invited', CURRENT_TIMESTAMP, 'Invited', $2)
+        "#
+    )
+    .bind(user_id)
+    .bind(invited_by)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Revokes any pending invite of the user and issues a new one. Returns the
+/// invite and its token.
+pub async fn issue_invite(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    invited_by: Uuid,
+) -> sqlx::Result<(UserInvite, String)> {
+    sqlx::query(
+        "UPDATE user_invites SET revoked_at = CURRENT_TIMESTAMP WHERE user_id = $1 AND accepted_at IS NULL AND revoked_at IS NULL"
+    )
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await?;
+
+    let token = generate_token();
+    let invite = sqlx::query_as::<_, UserInvite>(&format!(
+        r#"
+        INSERT INTO user_invites (user_id, token, expires_at, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {}
+        "#,
+        INVITE_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(&token)
+    .bind(Utc::now().naive_utc() + Duration::days(INVITE_TTL_DAYS))
+    .bind(invited_by)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok((invite, token))
+}
+
+/// Creates the user in the invited state with an invite, in one transaction.
+/// `password_hash` should be of a secret nobody knows; the invitee replaces it.
+pub async fn create_invited_user(
+    pool: &PgPool,
+    username: &str,
+    email: &str,
+    password_hash: &str,
+    invited_by: Uuid,
+) -> sqlx::Result<(User, UserInvite, String)> {
+    let mut tx = pool.begin().await?;
+
+    let user_id = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING id"
+    )
+    .bind(username)
+    .bind(email)
+    .bind(password_hash)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            sqlx::Error::Protocol("Username or email is already taken".into())
+        }
+        other => other,
+    })?;
+
+    mark_invited(&mut tx, user_id, invited_by).await?;
+    let (invite, token) = issue_invite(&mut tx, user_id, invited_by).await?;
+
+    tx.commit().await?;
+
+    let user = crate::services::user_service::get_user(pool, user_id).await?;
+    Ok((user, invite, token))
+}
+
+/// New invite for a user who hasn't activated yet; earlier ones stop working.
+pub async fn resend_invite(pool: &PgPool, user_id: Uuid, invited_by: Uuid) -> sqlx::Result<(UserInvite, String)> {
+    let mut tx = pool.begin().await?;
+
+    let status = sqlx::query_scalar::<_, String>("SELECT user_status(id) FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if status != "invited" {
+        return Err(sqlx::Error::Protocol(format!("User is {}, not invited", status)));
+    }
+
+    let issued = issue_invite(&mut tx, user_id, invited_by).await?;
+    tx.commit().await?;
+    Ok(issued)
+}
+
+/// Revokes the user's pending invites. Returns how many there were.
+pub async fn revoke_invites(pool: &PgPool, user_id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        "UPDATE user_invites SET revoked_at = CURRENT_TIMESTAMP WHERE user_id = $1 AND accepted_at IS NULL AND revoked_at IS NULL"
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn list_invites(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<UserInvite>> {
+    sqlx::query_as::<_, UserInvite>(&format!(
+        "SELECT {} FROM user_invites WHERE user_id = $1 ORDER BY created_at DESC",
+        INVITE_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// The invitee's details, if the token is still usable.
+pub async fn get_invite_details(pool: &PgPool, token: &str) -> sqlx::Result<Option<InviteDetails>> {
+    sqlx::query_as::<_, InviteDetails>(
+        r#"
+        SELECT u.username, u.email, i.expires_at
+        FROM user_invites i
+        JOIN users u ON u.id = i.user_id
+        WHERE i.token = $1
+        AND i.accepted_at IS NULL AND i.revoked_at IS NULL
+        AND i.expires_at > CURRENT_TIMESTAMP
+        AND user_status(u.id) = 'invited'
+        "#
+    )
+    .bind(token)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Sets the invitee's password and activates them. The invite is used up.
+/// `RowNotFound` if the token is unknown, used, revoked or expired.
+pub async fn accept_invite(pool: &PgPool, token: &str, password_hash: &str) -> sqlx::Result<Uuid> {
+    let mut tx = pool.begin().await?;
+
+    let (invite_id, user_id) = sqlx::query_as::<_, (Uuid, Uuid)>(
+        r#"
+        SELECT i.id, i.user_id
+        FROM user_invites i
+        WHERE i.token = $1
+        AND i.accepted_at IS NULL AND i.revoked_at IS NULL
+        AND i.expires_at > CURRENT_TIMESTAMP
+        AND user_status(i.user_id) = 'invited'
+        FOR UPDATE
+        "#
+    )
+    .bind(token)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    sqlx::query("UPDATE user_invites SET accepted_at = CURRENT_TIMESTAMP WHERE id = $1")
+        .bind(invite_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE users SET password_hash = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+        .bind(user_id)
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO user_status_changes (user_id, status, effective_at, reason, changed_by)
+        VALUES ($1, 'active', CURRENT_TIMESTAMP, 'Accepted invite', $1)
+        "#
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(user_id)
+}
+
+pub fn invite_mail(base_url: &str, username: &str, email: &str, token: &str, expires_at: NaiveDateTime) -> OutgoingMail {
+    OutgoingMail {
+        to: email.to_string(),
+        subject: "You have been invited".to_string(),
+        body: format!(
+            "Hello {},\n\nAn account has been created for you. Choose your password here:\n\n{}/invite/{}\n\nThe link can be used once and expires on {} UTC.\n",
+            username,
+            base_url.trim_end_matches('/'),
+            token,
+            expires_at.format("%Y-%m-%d %H:%M")
+        ),
+    }
+}
@@ -0,0 +1,651 @@
+use chrono::{Datelike, Days, Months, NaiveDate};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::leave_balance::{AccrualRunResult, LeaveAdjustmentPayload, LeaveBalance, LeaveLedgerEntry};
+use crate::models::leave_type::LeaveType;
+use crate::services::employee_profile_service::clean;
+use crate::services::leave_type_service::LEAVE_TYPE_COLUMNS;
+
+const MAX_ADJUSTMENT_DAYS: f64 = 999.0;
+const MAX_NOTE_LEN: usize = 500;
+
+const LEDGER_COLUMNS: &str = "e.id, e.user_id, e.leave_type_id, t.name AS leave_type, e.entry_type, \
+    e.days::FLOAT8 AS days, e.effective_date, e.period, e.leave_request_id, e.note, e.created_by, e.created_at";
+
+fn invalid<T>(msg: impl Into<String>) -> sqlx::Result<T> {
+    Err(sqlx::Error::Protocol(msg.into()))
+}
+
+fn round_days(days: f64) -> f64 {
+    (days * 100.0).round() / 100.0
+}
+
+fn first_of_year(year: i32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, 1, 1).unwrap_or_default()
+}
+
+/// Inclusive number of days from `from` to `to`.
+fn span(from: NaiveDate, to: NaiveDate) -> f64 {
+    ((to - from).num_days() + 1) as f64
+}
+
+/// Accrual engine steps, in the order they apply on the same day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Step {
+    /// Cut the balance of the year before down to the carry-over cap
+    Forfeit(i32),
+    /// Whole yearly entitlement, pro-rated in the year the employee starts
+    Grant(i32),
+    /// A twelfth of the entitlement, pro-rated in the month the employee starts
+    Accrual(NaiveDate),
+    /// Carried days of the year before that weren't used in time
+    Expiry(i32),
+}
+
+impl Step {
+    fn rank(&self) -> u8 {
+        match self {
+            Step::Forfeit(_) => 0,
+            Step::Grant(_) | Step::Accrual(_) => 1,
+            Step::Expiry(_) => 2,
+        }
+    }
+}
+
+/// Steps due between `from` and `to` (inclusive) for an employee whose accrual
+/// begins on `start`, sorted by the day they apply.
+fn schedule(leave_type: &LeaveType, start: NaiveDate, from: NaiveDate, to: NaiveDate) -> Vec<(NaiveDate, Step)> {
+    let in_range = |d: NaiveDate| d >= from && d <= to;
+    let mut steps = Vec::new();
+
+    for year in from.year()..=to.year() {
+        let new_year = first_of_year(year);
+        if new_year > start {
+            if leave_type.carry_over_cap.is_some() && in_range(new_year) {
+                steps.push((new_year, Step::Forfeit(year - 1)));
+            }
+            if let Some(months) = leave_type.carry_over_expiry_months
+                && let Some(expires) = new_year.checked_add_months(Months::new(months as u32))
+                && in_range(expires)
+            {
+                steps.push((expires, Step::Expiry(year)));
+            }
+        }
+
+        match leave_type.accrual_method.as_str() {
+            "annual" => {
+                let granted = new_year.max(start);
+                if granted.year() == year && in_range(granted) {
+                    steps.push((granted, Step::Grant(year)));
+                }
+            }
+            "monthly" => {
+                for month in 1..=12 {
+                    let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else { continue };
+                    let accrued = first.max(start);
+                    if accrued.month() == month && accrued.year() == year && in_range(accrued) {
+                        steps.push((accrued, Step::Accrual(first)));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    steps.sort_by_key(|(day, step)| (*day, step.rank(), *step));
+    steps
+}
+
+/// Days of a yearly grant made on `day`; a grant after the new year is pro-rated
+/// over the rest of the year.
+fn grant_days(annual_days: f64, year: i32, day: NaiveDate) -> f64 {
+    let new_year = first_of_year(year);
+    let year_end = first_of_year(year + 1) - Days::new(1);
+    let share = if day == new_year { 1.0 } else { span(day, year_end) / span(new_year, year_end) };
+    round_days(annual_days * share)
+}
+
+/// Days accrued for the month starting on `first` when accrual begins on `day`;
+/// a month entered part-way is pro-rated over its remaining days.
+fn accrual_days(annual_days: f64, first: NaiveDate, day: NaiveDate) -> f64 {
+    let month_end = first
+        .checked_add_months(Months::new(1))
+        .and_then(|d| d.pred_opt())
+        .unwrap_or(first);
+    let share = if day == first { 1.0 } else { span(day, month_end) / span(first, month_end) };
+    round_days(annual_days / 12.0 * share)
+}
+
+/// Days above the carry-over cap at the end of a year.
+fn forfeited_days(year_end_balance: f64, cap: f64) -> f64 {
+    round_days(year_end_balance - cap).max(0.0)
+}
+
+/// Carried days still unused when they expire. Leave taken since the new year
+/// uses the carried days first, and no more than the balance can expire.
+fn expired_days(carried: f64, used: f64, balance: f64) -> f64 {
+    round_days((carried.max(0.0) - used.max(0.0)).min(balance.max(0.0))).max(0.0)
+}
+
+/// Ledger sum up to and including `day`.
+async fn balance_at(conn: &mut PgConnection, user_id: Uuid, leave_type_id: Uuid, day: NaiveDate) -> sqlx::Result<f64> {
+    sqlx::query_scalar::<_, f64>(
+        r#"
+        SELECT COALESCE(SUM(days), 0)::FLOAT8 FROM leave_ledger
+        WHERE user_id = $1 AND leave_type_id = $2 AND effective_date <= $3
+        "#
+    )
+    .bind(user_id)
+    .bind(leave_type_id)
+    .bind(day)
+    .fetch_one(conn)
+    .await
+}
+
+/// Posts an engine entry; a period already posted is left alone. Returns whether it was new.
+async fn post_period(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    leave_type_id: Uuid,
+    entry_type: &str,
+    days: f64,
+    effective_date: NaiveDate,
+    period: &str,
+) -> sqlx::Result<bool> {
+    if days == 0.0 {
+        return Ok(false);
+    }
+    let result = sqlx::query(
+        r#"
+        INSERT INTO leave_ledger (user_id, leave_type_id, entry_type, days, effective_date, period)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (user_id, leave_type_id, entry_type, period) WHERE period IS NOT NULL DO NOTHING
+        "#
+    )
+    .bind(user_id)
+    .bind(leave_type_id)
+    .bind(entry_type)
+    .bind(days)
+    .bind(effective_date)
+    .bind(period)
+    .execute(conn)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+/// Applies one step. Returns whether a ledger entry was posted.
+async fn apply_step(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    leave_type: &LeaveType,
+    day: NaiveDate,
+    step: Step,
+) -> sqlx::Result<bool> {
+    let annual_days = leave_type.annual_days.unwrap_or(0.0);
+    let type_id = leave_type.id;
+
+    match step {
+        Step::Grant(year) => {
+            let days = grant_days(annual_days, year, day);
+            post_period(conn, user_id, type_id, "grant", days, day, &year.to_string()).await
+        }
+        Step::Accrual(first) => {
+            let days = accrual_days(annual_days, first, day);
+            let period = first.format("%Y-%m").to_string();
+            post_period(conn, user_id, type_id, "accrual", days, day, &period).await
+        }
+        Step::Forfeit(year) => {
+            let Some(cap) = leave_type.carry_over_cap else { return Ok(false) };
+            let year_end = first_of_year(year + 1) - Days::new(1);
+            let balance = balance_at(&mut *conn, user_id, type_id, year_end).await?;
+            let excess = forfeited_days(balance, cap);
+            if excess == 0.0 {
+                return Ok(false);
+            }
+            post_period(conn, user_id, type_id, "forfeit", -excess, year_end, &year.to_string()).await
+        }
+        Step::Expiry(year) => {
+            let new_year = first_of_year(year);
+            let carried = balance_at(&mut *conn, user_id, type_id, new_year - Days::new(1)).await?;
+            let used = sqlx::query_scalar::<_, f64>(
+                r#"
+                SELECT COALESCE(-SUM(days), 0)::FLOAT8 FROM leave_ledger
+                WHERE user_id = $1 AND leave_type_id = $2 AND entry_type IN ('debit', 'credit')
+                  AND effective_date >= $3 AND effective_date < $4
+                "#
+            )
+            .bind(user_id)
+            .bind(type_id)
+            .bind(new_year)
+            .bind(day)
+            .fetch_one(&mut *conn)
+            .await?;
+            let balance = balance_at(&mut *conn, user_id, type_id, day).await?;
+            let expired = expired_days(carried, used, balance);
+            if expired == 0.0 {
+                return Ok(false);
+            }
+            post_period(conn, user_id, type_id, "expiry", -expired, day, &year.to_string()).await
+        }
+    }
+}
+
+/// Posts the accruals, grants, forfeits and expiries due up to `today` and
+/// locks the balance until the transaction ends, so callers can check and
+/// change it safely. Types without a balance are skipped. Returns the number
+/// of entries posted.
+pub async fn catch_up(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    leave_type: &LeaveType,
+    today: NaiveDate,
+) -> sqlx::Result<u64> {
+    if !leave_type.tracks_balance() {
+        return Ok(0);
+    }
+
+    sqlx::query("INSERT INTO leave_balances (user_id, leave_type_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(user_id)
+        .bind(leave_type.id)
+        .execute(&mut *conn)
+        .await?;
+    let accrued_through = sqlx::query_scalar::<_, Option<NaiveDate>>(
+        "SELECT accrued_through FROM leave_balances WHERE user_id = $1 AND leave_type_id = $2 FOR UPDATE",
+    )
+    .bind(user_id)
+    .bind(leave_type.id)
+    .fetch_one(&mut *conn)
+    .await?;
+    if accrued_through.is_some_and(|d| d >= today) {
+        return Ok(0);
+    }
+
+    let (started, status, role_id, employment_type) = sqlx::query_as::<_, (NaiveDate, String, Option<Uuid>, Option<String>)>(
+        r#"
+        SELECT COALESCE(hire_date, created_at::DATE), user_status(id), role_id, employment_type
+        FROM users WHERE id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    // Invited users haven't started: their periods are posted once they have.
+    // Terminated or ineligible users accrue nothing for the days skipped here.
+    if status == "invited" {
+        return Ok(0);
+    }
+    let mut posted = 0;
+    if status != "terminated" && leave_type.is_eligible(role_id, employment_type.as_deref()) {
+        let start = started.max(leave_type.accrual_start);
+        let from = accrued_through.and_then(|d| d.succ_opt()).unwrap_or(start).max(start);
+        for (day, step) in schedule(leave_type, start, from, today) {
+            if apply_step(&mut *conn, user_id, leave_type, day, step).await? {
+                posted += 1;
+            }
+        }
+    }
+
+    sqlx::query("UPDATE leave_balances SET accrued_through = $3 WHERE user_id = $1 AND leave_type_id = $2")
+        .bind(user_id)
+        .bind(leave_type.id)
+        .bind(today)
+        .execute(&mut *conn)
+        .await?;
+    Ok(posted)
+}
+
+async fn get_type(conn: &mut PgConnection, id: Uuid) -> sqlx::Result<LeaveType> {
+    sqlx::query_as::<_, LeaveType>(&format!("SELECT {} FROM leave_types WHERE id = $1", LEAVE_TYPE_COLUMNS))
+        .bind(id)
+        .fetch_one(conn)
+        .await
+}
+
+/// Ledger balance minus the days of requests still pending.
+pub async fn available(conn: &mut PgConnection, user_id: Uuid, leave_type_id: Uuid) -> sqlx::Result<f64> {
+    sqlx::query_scalar::<_, f64>(
+        r#"
+        SELECT (
+            (SELECT COALESCE(SUM(days), 0) FROM leave_ledger WHERE user_id = $1 AND leave_type_id = $2)
+            - (SELECT COALESCE(SUM(days), 0) FROM leave_requests
+               WHERE user_id = $1 AND leave_type_id = $2 AND status = 'Pending')
+        )::FLOAT8
+        "#
+    )
+    .bind(user_id)
+    .bind(leave_type_id)
+    .fetch_one(conn)
+    .await
+}
+
+/// Debits the balance when a request becomes approved and credits it back
+/// when an approved request is rejected or cancelled. Pending requests only
+/// hold days through `available`, so other changes post nothing.
+#[allow(clippy::too_many_arguments)]
+pub async fn record_status_change(
+    conn: &mut PgConnection,
+    request_id: Uuid,
+    user_id: Uuid,
+    leave_type_id: Uuid,
+    days: f64,
+    start_date: NaiveDate,
+    old_status: &str,
+    new_status: &str,
+    today: NaiveDate,
+) -> sqlx::Result<()> {
+    let (entry_type, signed_days) = match (old_status, new_status) {
+        (old, "Approved") if old != "Approved" => ("debit", -days),
+        ("Approved", new) if new != "Approved" => ("credit", days),
+        _ => return Ok(()),
+    };
+    let leave_type = get_type(&mut *conn, leave_type_id).await?;
+    if !leave_type.tracks_balance() {
+        return Ok(());
+    }
+    catch_up(&mut *conn, user_id, &leave_type, today).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO leave_ledger (user_id, leave_type_id, entry_type, days, effective_date, leave_request_id, note)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#
+    )
+    .bind(user_id)
+    .bind(leave_type_id)
+    .bind(entry_type)
+    .bind(signed_days)
+    .bind(start_date)
+    .bind(request_id)
+    .bind(format!("Request {}", new_status.to_lowercase()))
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Brings one balance up to date in its own transaction.
+async fn catch_up_one(pool: &PgPool, user_id: Uuid, leave_type: &LeaveType, today: NaiveDate) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+    let posted = catch_up(&mut tx, user_id, leave_type, today).await?;
+    tx.commit().await?;
+    Ok(posted)
+}
+
+/// Balances of the types the user may request and of any other type they
+/// have ledger entries for, brought up to `today` first.
+pub async fn list_balances(pool: &PgPool, user_id: Uuid, today: NaiveDate) -> sqlx::Result<Vec<LeaveBalance>> {
+    let types = sqlx::query_as::<_, LeaveType>(&format!(
+        r#"
+        SELECT {} FROM leave_types t
+        WHERE t.accrual_method <> 'none' AND (
+            (t.active AND EXISTS (
+                SELECT 1 FROM users u
+                WHERE u.id = $1
+                  AND (t.eligible_role_ids IS NULL OR u.role_id = ANY(t.eligible_role_ids))
+                  AND (t.eligible_employment_types IS NULL OR u.employment_type = ANY(t.eligible_employment_types))
+            ))
+            OR EXISTS (SELECT 1 FROM leave_ledger e WHERE e.user_id = $1 AND e.leave_type_id = t.id)
+        )
+        "#,
+        LEAVE_TYPE_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    for leave_type in &types {
+        catch_up_one(pool, user_id, leave_type, today).await?;
+    }
+    let ids: Vec<Uuid> = types.iter().map(|t| t.id).collect();
+
+    sqlx::query_as::<_, LeaveBalance>(
+        r#"
+        SELECT *, balance - pending_days AS available FROM (
+            SELECT t.id AS leave_type_id, t.name AS leave_type, t.accrual_method, t.allow_negative,
+                (SELECT COALESCE(SUM(e.days), 0) FROM leave_ledger e
+                 WHERE e.user_id = $1 AND e.leave_type_id = t.id)::FLOAT8 AS balance,
+                (SELECT COALESCE(SUM(l.days), 0) FROM leave_requests l
+                 WHERE l.user_id = $1 AND l.leave_type_id = t.id AND l.status = 'Pending')::FLOAT8 AS pending_days,
+                b.accrued_through
+            FROM leave_types t
+            LEFT JOIN leave_balances b ON b.leave_type_id = t.id AND b.user_id = $1
+            WHERE t.id = ANY($2)
+        ) balances
+        ORDER BY leave_type
+        "#
+    )
+    .bind(user_id)
+    .bind(&ids)
+    .fetch_all(pool)
+    .await
+}
+
+/// The user's ledger, newest first, optionally for one type only.
+pub async fn list_ledger(pool: &PgPool, user_id: Uuid, leave_type: Option<&str>) -> sqlx::Result<Vec<LeaveLedgerEntry>> {
+    sqlx::query_as::<_, LeaveLedgerEntry>(&format!(
+        r#"
+        SELECT {} FROM leave_ledger e
+        JOIN leave_types t ON e.leave_type_id = t.id
+        WHERE e.user_id = $1 AND ($2::TEXT IS NULL OR t.name = $2)
+        ORDER BY e.effective_date DESC, e.created_at DESC
+        "#,
+        LEDGER_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(leave_type)
+    .fetch_all(pool)
+    .await
+}
+
+/// Records a manual correction after bringing the balance up to date.
+pub async fn adjust(
+    pool: &PgPool,
+    user_id: Uuid,
+    payload: &LeaveAdjustmentPayload,
+    created_by: Uuid,
+    today: NaiveDate,
+) -> sqlx::Result<LeaveLedgerEntry> {
+    if payload.days == 0.0 || payload.days.abs() > MAX_ADJUSTMENT_DAYS || !payload.days.is_finite() {
+        return invalid(format!("days must be non-zero and at most {} either way", MAX_ADJUSTMENT_DAYS));
+    }
+    let Some(note) = clean("note", &Some(payload.note.clone()), MAX_NOTE_LEN)? else {
+        return invalid("Adjustments need a note");
+    };
+
+    let mut tx = pool.begin().await?;
+    let leave_type = sqlx::query_as::<_, LeaveType>(&format!(
+        "SELECT {} FROM leave_types WHERE name = $1",
+        LEAVE_TYPE_COLUMNS
+    ))
+    .bind(payload.leave_type.trim())
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(leave_type) = leave_type else {
+        return invalid("Unknown leave type");
+    };
+    if !leave_type.tracks_balance() {
+        return invalid(format!("{} leave keeps no balance", leave_type.name));
+    }
+    // Checks the user exists before anything is posted
+    sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    catch_up(&mut tx, user_id, &leave_type, today).await?;
+
+    let id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO leave_ledger (user_id, leave_type_id, entry_type, days, effective_date, note, created_by)
+        VALUES ($1, $2, 'adjustment', $3, $4, $5, $6)
+        RETURNING id
+        "#
+    )
+    .bind(user_id)
+    .bind(leave_type.id)
+    .bind(round_days(payload.days))
+    .bind(payload.effective_date.unwrap_or(today))
+    .bind(&note)
+    .bind(created_by)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let entry = sqlx::query_as::<_, LeaveLedgerEntry>(&format!(
+        "SELECT {} FROM leave_ledger e JOIN leave_types t ON e.leave_type_id = t.id WHERE e.id = $1",
+        LEDGER_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(entry)
+}
+
+/// Posts everything due up to `as_of` for every user and type with a balance.
+pub async fn run_accruals(pool: &PgPool, as_of: NaiveDate) -> sqlx::Result<AccrualRunResult> {
+    let types = sqlx::query_as::<_, LeaveType>(&format!(
+        "SELECT {} FROM leave_types WHERE accrual_method <> 'none' AND active",
+        LEAVE_TYPE_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+    let users = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE user_status(id) NOT IN ('invited', 'terminated')")
+        .fetch_all(pool)
+        .await?;
+
+    let mut result = AccrualRunResult { as_of, balances: 0, entries: 0 };
+    for user_id in &users {
+        for leave_type in &types {
+            result.entries += catch_up_one(pool, *user_id, leave_type, as_of).await?;
+            result.balances += 1;
+        }
+    }
+    Ok(result)
+}
+
+/// Runs `run_accruals` at startup and then once a day, so balances shown to
+/// approvers are current without read endpoints having to post accruals.
+pub async fn run_daily_accruals(pool: PgPool) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(24 * 60 * 60));
+    loop {
+        interval.tick().await;
+        let today = chrono::Utc::now().date_naive();
+        match run_accruals(&pool, today).await {
+            Ok(result) => println!("Leave accruals posted: {} entries over {} balances", result.entries, result.balances),
+            Err(e) => eprintln!("Scheduled leave accrual run failed: {:?}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn leave_type(accrual_method: &str, carry_over_cap: Option<f64>, carry_over_expiry_months: Option<i32>) -> LeaveType {
+        LeaveType {
+            id: Uuid::new_v4(),
+            name: "Annual".to_string(),
+            description: None,
+            paid: true,
+            requires_attachment: false,
+            min_notice_days: 0,
+            max_consecutive_days: None,
+            eligible_role_ids: None,
+            eligible_employment_types: None,
+            active: true,
+            accrual_method: accrual_method.to_string(),
+            annual_days: Some(24.0),
+            carry_over_cap,
+            carry_over_expiry_months,
+            allow_negative: false,
+            accrual_start: date(2000, 1, 1),
+            created_at: Default::default(),
+            updated_at: Default::default(),
+        }
+    }
+
+    #[test]
+    fn monthly_schedule_starts_in_the_month_of_hire() {
+        let monthly = leave_type("monthly", None, None);
+        let steps = schedule(&monthly, date(2025, 3, 15), date(2025, 3, 15), date(2025, 12, 31));
+
+        assert_eq!(steps.len(), 10);
+        assert_eq!(steps[0], (date(2025, 3, 15), Step::Accrual(date(2025, 3, 1))));
+        assert_eq!(steps[1], (date(2025, 4, 1), Step::Accrual(date(2025, 4, 1))));
+        assert_eq!(steps[9], (date(2025, 12, 1), Step::Accrual(date(2025, 12, 1))));
+    }
+
+    #[test]
+    fn schedule_only_covers_the_requested_range() {
+        let monthly = leave_type("monthly", None, None);
+        let steps = schedule(&monthly, date(2024, 1, 1), date(2025, 2, 2), date(2025, 4, 1));
+
+        let days: Vec<NaiveDate> = steps.iter().map(|(day, _)| *day).collect();
+        assert_eq!(days, vec![date(2025, 3, 1), date(2025, 4, 1)]);
+    }
+
+    #[test]
+    fn new_year_forfeits_before_granting_and_expires_carried_days_later() {
+        let annual = leave_type("annual", Some(5.0), Some(3));
+        let steps = schedule(&annual, date(2024, 7, 1), date(2024, 7, 1), date(2025, 12, 31));
+
+        assert_eq!(
+            steps,
+            vec![
+                (date(2024, 7, 1), Step::Grant(2024)),
+                (date(2025, 1, 1), Step::Forfeit(2024)),
+                (date(2025, 1, 1), Step::Grant(2025)),
+                (date(2025, 4, 1), Step::Expiry(2025)),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_carry_over_steps_before_the_first_new_year() {
+        let annual = leave_type("annual", Some(5.0), Some(3));
+        let steps = schedule(&annual, date(2025, 1, 1), date(2025, 1, 1), date(2025, 12, 31));
+
+        assert_eq!(steps, vec![(date(2025, 1, 1), Step::Grant(2025))]);
+    }
+
+    #[test]
+    fn grants_are_pro_rated_over_the_rest_of_the_year() {
+        assert_eq!(grant_days(24.0, 2025, date(2025, 1, 1)), 24.0);
+        // 184 of 365 days left
+        assert_eq!(grant_days(24.0, 2025, date(2025, 7, 1)), 12.1);
+        assert_eq!(grant_days(24.0, 2025, date(2025, 12, 31)), 0.07);
+    }
+
+    #[test]
+    fn partial_first_month_accrues_its_remaining_days() {
+        assert_eq!(accrual_days(24.0, date(2025, 3, 1), date(2025, 3, 1)), 2.0);
+        // 14 of 28 days in February 2025
+        assert_eq!(accrual_days(24.0, date(2025, 2, 1), date(2025, 2, 15)), 1.0);
+        // 15 of 29 days in February 2024
+        assert_eq!(accrual_days(24.0, date(2024, 2, 1), date(2024, 2, 15)), 1.03);
+        assert_eq!(accrual_days(24.0, date(2025, 1, 1), date(2025, 1, 31)), 0.06);
+    }
+
+    #[test]
+    fn only_days_above_the_cap_are_forfeited() {
+        assert_eq!(forfeited_days(12.5, 5.0), 7.5);
+        assert_eq!(forfeited_days(5.0, 5.0), 0.0);
+        assert_eq!(forfeited_days(3.0, 5.0), 0.0);
+        assert_eq!(forfeited_days(-2.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn leave_taken_since_the_new_year_uses_carried_days_first() {
+        assert_eq!(expired_days(5.0, 2.0, 20.0), 3.0);
+        assert_eq!(expired_days(5.0, 8.0, 20.0), 0.0);
+        // Never more than what is left in the balance
+        assert_eq!(expired_days(5.0, 0.0, 2.0), 2.0);
+        assert_eq!(expired_days(-3.0, 0.0, 20.0), 0.0);
+        assert_eq!(expired_days(5.0, 0.0, -1.0), 0.0);
+    }
+}
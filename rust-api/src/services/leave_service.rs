@@ -1,29 +1,92 @@
-use sqlx::PgPool;
+use chrono::NaiveDate;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 use crate::models::leave_request::{LeaveRequest, CreateLeaveRequestPayload};
+use crate::models::leave_type::LeaveType;
+use crate::models::row_filter::RowFilter;
+use crate::services::{holiday_service, leave_balance_service};
 
+/// Columns of `LeaveRequest`, over `leave_requests l` joined with its type and attachment.
+const LEAVE_REQUEST_SELECT: &str = r#"
+    SELECT l.id, l.user_id, t.name AS leave_type, l.start_date, l.end_date, l.days::FLOAT8 AS days, l.reason, l.status::TEXT,
+        l.approved_by, l.approved_on_behalf_of, a.id AS attachment_id, l.created_at, l.updated_at
+    FROM leave_requests l
+    JOIN leave_types t ON l.leave_type_id = t.id
+    LEFT JOIN leave_attachments a ON a.leave_request_id = l.id
+"#;
+
+/// Inserts the request, already checked against the rules of `leave_type`,
+/// and links the attachment, which must be the user's and not used before.
+/// Its days skip the weekend days and holidays of the user's calendar.
+/// Refused when it takes more than the available balance, unless the type
+/// allows negative balances.
 pub async fn create_leave_request(
     pool: &PgPool,
     user_id: Uuid,
+    leave_type: &LeaveType,
     payload: CreateLeaveRequestPayload,
+    today: NaiveDate,
 ) -> sqlx::Result<LeaveRequest> {
+    let days = holiday_service::working_days(pool, user_id, payload.start_date, payload.end_date)
+        .await?
+        .days;
+    if days == 0.0 {
+        return Err(sqlx::Error::Protocol("The request covers no working days".into()));
+    }
+
     let id = Uuid::new_v4();
-    sqlx::query_as::<_, LeaveRequest>(
+    let mut tx = pool.begin().await?;
+
+    if leave_type.tracks_balance() {
+        // Also locks the balance until the request is inserted
+        leave_balance_service::catch_up(&mut tx, user_id, leave_type, today).await?;
+        let available = leave_balance_service::available(&mut tx, user_id, leave_type.id).await?;
+        if !leave_type.allow_negative && days > available {
+            return Err(sqlx::Error::Protocol(format!(
+                "{} days requested but only {} days of {} leave available",
+                days, available, leave_type.name
+            )));
+        }
+    }
+
+    sqlx::query(
         r#"
-        INSERT INTO leave_requests (id, user_id, leave_type, start_date, end_date, reason, status)
-        VALUES ($1, $2, $3::leave_type, $4, $5, $6, 'Pending')
-        RETURNING id, user_id, leave_type::TEXT, start_date, end_date, reason, status::TEXT, approved_by, created_at, updated_at
+        INSERT INTO leave_requests (id, user_id, leave_type_id, start_date, end_date, days, reason, status)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, 'Pending')
         "#
     )
     .bind(id)
     .bind(user_id)
-    .bind(&payload.leave_type)
+    .bind(leave_type.id)
     .bind(payload.start_date)
     .bind(payload.end_date)
+    .bind(days)
     .bind(&payload.reason)
-    .fetch_one(pool)
-    .await
+    .execute(&mut *tx)
+    .await?;
+
+    if let Some(attachment_id) = payload.attachment_id {
+        let linked = sqlx::query(
+            "UPDATE leave_attachments SET leave_request_id = $1 WHERE id = $2 AND user_id = $3 AND leave_request_id IS NULL"
+        )
+        .bind(id)
+        .bind(attachment_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        if linked.rows_affected() == 0 {
+            return Err(sqlx::Error::Protocol("Unknown or already used attachment".into()));
+        }
+    }
+
+    let request = sqlx::query_as::<_, LeaveRequest>(&format!("{} WHERE l.id = $1", LEAVE_REQUEST_SELECT))
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(request)
 }
 
 pub async fn list_leave_requests_for_user(
@@ -33,10 +96,14 @@ pub async fn list_leave_requests_for_user(
     sqlx::query_as::<_, crate::models::leave_request::LeaveRequestWithApprover>(
         r#"
         SELECT 
-            l.id, l.user_id, l.leave_type::TEXT, l.start_date, l.end_date, l.reason, l.status::TEXT, 
-            l.approved_by, u.username as approver_name, l.created_at, l.updated_at
+            l.id, l.user_id, t.name AS leave_type, l.start_date, l.end_date, l.days::FLOAT8 AS days, l.reason, l.status::TEXT, 
+            l.approved_by, u.username as approver_name, l.approved_on_behalf_of, ob.username as on_behalf_of_name,
+            a.id AS attachment_id, l.created_at, l.updated_at
         FROM leave_requests l
+        JOIN leave_types t ON l.leave_type_id = t.id
+        LEFT JOIN leave_attachments a ON a.leave_request_id = l.id
         LEFT JOIN users u ON l.approved_by = u.id
+        LEFT JOIN users ob ON l.approved_on_behalf_of = ob.id
         WHERE l.user_id = $1
         ORDER BY l.created_at DESC
         "#
@@ -46,51 +113,113 @@ pub async fn list_leave_requests_for_user(
     .await
 }
 
-pub async fn list_all_leave_requests(pool: &PgPool) -> sqlx::Result<Vec<crate::models::leave_request::LeaveRequestWithUser>> {
-    sqlx::query_as::<_, crate::models::leave_request::LeaveRequestWithUser>(
+/// Leave requests visible through `filter`, as produced by partial evaluation.
+pub async fn list_all_leave_requests(
+    pool: &PgPool,
+    filter: &RowFilter,
+) -> sqlx::Result<Vec<crate::models::leave_request::LeaveRequestWithUser>> {
+    let mut qb = QueryBuilder::<Postgres>::new(
         r#"
-        SELECT l.id, l.user_id, u.username, u.email, l.leave_type::TEXT, l.start_date, l.end_date, l.reason, l.status::TEXT, l.approved_by, l.created_at, l.updated_at
+        SELECT l.id, l.user_id, u.username, u.email, t.name AS leave_type, l.start_date, l.end_date, l.days::FLOAT8 AS days, l.reason, l.status::TEXT, l.approved_by, l.approved_on_behalf_of, a.id AS attachment_id,
+            CASE WHEN t.accrual_method <> 'none' THEN
+                (SELECT COALESCE(SUM(e.days), 0) FROM leave_ledger e WHERE e.user_id = l.user_id AND e.leave_type_id = l.leave_type_id)::FLOAT8
+            END AS balance,
+            l.created_at, l.updated_at
         FROM leave_requests l
         JOIN users u ON l.user_id = u.id
-        ORDER BY l.created_at DESC
-        "#
-    )
-    .fetch_all(pool)
-    .await
+        JOIN leave_types t ON l.leave_type_id = t.id
+        LEFT JOIN leave_attachments a ON a.leave_request_id = l.id
+        WHERE "#
+    );
+    filter.push_sql(&mut qb, "l.user_id");
+    qb.push(" ORDER BY l.created_at DESC");
+
+    qb.build_query_as::<crate::models::leave_request::LeaveRequestWithUser>()
+        .fetch_all(pool)
+        .await
 }
 
 pub async fn get_leave_request(pool: &PgPool, id: Uuid) -> sqlx::Result<LeaveRequest> {
-    sqlx::query_as::<_, LeaveRequest>(
-        r#"
-        SELECT id, user_id, leave_type::TEXT, start_date, end_date, reason, status::TEXT, approved_by, created_at, updated_at
-        FROM leave_requests
-        WHERE id = $1
-        "#
-    )
-    .bind(id)
-    .fetch_one(pool)
-    .await
+    sqlx::query_as::<_, LeaveRequest>(&format!("{} WHERE l.id = $1", LEAVE_REQUEST_SELECT))
+        .bind(id)
+        .fetch_one(pool)
+        .await
 }
 
-pub async fn update_leave_status(
+/// Status changes a request may go through; Rejected and Cancelled are final.
+const STATUS_TRANSITIONS: &[(&str, &str)] = &[
+    ("Pending", "Approved"),
+    ("Pending", "Rejected"),
+    ("Pending", "Cancelled"),
+    ("Approved", "Cancelled"),
+];
+
+/// Sets the status and posts the matching debit or credit in the same
+/// transaction. Moves `STATUS_TRANSITIONS` doesn't list are rejected.
+async fn change_status(
     pool: &PgPool,
     id: Uuid,
     status: &str,
-    approved_by: Uuid,
-) -> sqlx::Result<Option<LeaveRequest>> {
-    sqlx::query_as::<_, LeaveRequest>(
+    approval: Option<(Uuid, Option<Uuid>)>,
+    today: NaiveDate,
+) -> sqlx::Result<LeaveRequest> {
+    let mut tx = pool.begin().await?;
+
+    let (user_id, leave_type_id, days, start_date, old_status) = sqlx::query_as::<_, (Uuid, Uuid, f64, NaiveDate, String)>(
+        "SELECT user_id, leave_type_id, days::FLOAT8, start_date, status::TEXT FROM leave_requests WHERE id = $1 FOR UPDATE"
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    if !STATUS_TRANSITIONS.contains(&(old_status.as_str(), status)) {
+        return Err(sqlx::Error::Protocol(format!(
+            "Cannot move a leave request from {} to {}",
+            old_status, status
+        )));
+    }
+
+    sqlx::query(
         r#"
         UPDATE leave_requests
-        SET status = $1::leave_status, approved_by = $2, updated_at = CURRENT_TIMESTAMP
-        WHERE id = $3 AND status::TEXT != $1
-        RETURNING id, user_id, leave_type::TEXT, start_date, end_date, reason, status::TEXT, approved_by, created_at, updated_at
+        SET status = $1::leave_status,
+            approved_by = CASE WHEN $4 THEN $2 ELSE approved_by END,
+            approved_on_behalf_of = CASE WHEN $4 THEN $5 ELSE approved_on_behalf_of END,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $3
         "#
     )
     .bind(status)
-    .bind(approved_by)
+    .bind(approval.map(|(approver, _)| approver))
     .bind(id)
-    .fetch_optional(pool)
-    .await
+    .bind(approval.is_some())
+    .bind(approval.and_then(|(_, on_behalf_of)| on_behalf_of))
+    .execute(&mut *tx)
+    .await?;
+
+    leave_balance_service::record_status_change(
+        &mut tx, id, user_id, leave_type_id, days, start_date, &old_status, status, today,
+    )
+    .await?;
+
+    tx.commit().await?;
+    get_leave_request(pool, id).await
+}
+
+pub async fn update_leave_status(
+    pool: &PgPool,
+    id: Uuid,
+    status: &str,
+    approved_by: Uuid,
+    on_behalf_of: Option<Uuid>,
+    today: NaiveDate,
+) -> sqlx::Result<LeaveRequest> {
+    change_status(pool, id, status, Some((approved_by, on_behalf_of)), today).await
+}
+
+/// Withdraws a request; approved days go back to the balance.
+pub async fn cancel_leave_request(pool: &PgPool, id: Uuid, today: NaiveDate) -> sqlx::Result<LeaveRequest> {
+    change_status(pool, id, "Cancelled", None, today).await
 }
 
 pub async fn delete_leave_request(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
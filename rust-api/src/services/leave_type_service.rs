@@ -0,0 +1,324 @@
+use chrono::NaiveDate;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::leave_type::{LeaveAttachment, LeaveType, LeaveTypePayload};
+use crate::services::employee_profile_service::{clean, EMPLOYMENT_TYPES};
+use crate::services::storage_service::Storage;
+
+const MAX_NAME_LEN: usize = 50;
+const MAX_DESCRIPTION_LEN: usize = 500;
+
+/// Largest supporting document accepted
+pub const MAX_ATTACHMENT_BYTES: usize = 10 * 1024 * 1024;
+const MAX_FILE_NAME_LEN: usize = 255;
+
+pub const ACCRUAL_METHODS: &[&str] = &["none", "monthly", "annual"];
+
+pub const LEAVE_TYPE_COLUMNS: &str = "id, name, description, paid, requires_attachment, min_notice_days, \
+    max_consecutive_days, eligible_role_ids, eligible_employment_types, active, accrual_method, \
+    annual_days::FLOAT8 AS annual_days, carry_over_cap::FLOAT8 AS carry_over_cap, carry_over_expiry_months, \
+    allow_negative, accrual_start, created_at, updated_at";
+
+const ATTACHMENT_COLUMNS: &str = "id, user_id, leave_request_id, file_name, content_type, size_bytes, created_at";
+
+fn invalid<T>(msg: impl Into<String>) -> sqlx::Result<T> {
+    Err(sqlx::Error::Protocol(msg.into()))
+}
+
+/// Every type, including inactive ones.
+pub async fn list_types(pool: &PgPool) -> sqlx::Result<Vec<LeaveType>> {
+    sqlx::query_as::<_, LeaveType>(&format!("SELECT {} FROM leave_types ORDER BY name", LEAVE_TYPE_COLUMNS))
+        .fetch_all(pool)
+        .await
+}
+
+/// Active types the user is eligible for, i.e. what they may submit.
+pub async fn list_requestable_types(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<LeaveType>> {
+    sqlx::query_as::<_, LeaveType>(&format!(
+        r#"
+        SELECT {} FROM leave_types t
+        WHERE t.active AND EXISTS (
+            SELECT 1 FROM users u
+            WHERE u.id = $1
+              AND (t.eligible_role_ids IS NULL OR u.role_id = ANY(t.eligible_role_ids))
+              AND (t.eligible_employment_types IS NULL OR u.employment_type = ANY(t.eligible_employment_types))
+        )
+        ORDER BY t.name
+        "#,
+        LEAVE_TYPE_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Checks the payload. Returns it with the text fields cleaned.
+async fn check_payload(pool: &PgPool, payload: &LeaveTypePayload) -> sqlx::Result<LeaveTypePayload> {
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return invalid(format!("Name must be 1 to {} characters", MAX_NAME_LEN));
+    }
+    if payload.min_notice_days < 0 {
+        return invalid("min_notice_days can't be negative");
+    }
+    if payload.max_consecutive_days.is_some_and(|d| d < 1) {
+        return invalid("max_consecutive_days must be positive");
+    }
+
+    // An empty list would make the type unusable; leave it out to allow everyone
+    if let Some(roles) = &payload.eligible_role_ids {
+        if roles.is_empty() {
+            return invalid("eligible_role_ids can't be empty");
+        }
+        let known = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM roles WHERE id = ANY($1)")
+            .bind(roles)
+            .fetch_one(pool)
+            .await?;
+        if known as usize != roles.len() {
+            return invalid("eligible_role_ids contains unknown or repeated roles");
+        }
+    }
+    if let Some(types) = &payload.eligible_employment_types {
+        if types.is_empty() {
+            return invalid("eligible_employment_types can't be empty");
+        }
+        if let Some(unknown) = types.iter().find(|t| !EMPLOYMENT_TYPES.contains(&t.as_str())) {
+            return invalid(format!("Unknown employment type '{}'", unknown));
+        }
+    }
+
+    let accrual_method = payload.accrual_method.as_deref().map(str::trim).unwrap_or("none");
+    if !ACCRUAL_METHODS.contains(&accrual_method) {
+        return invalid(format!("accrual_method must be one of {}", ACCRUAL_METHODS.join(", ")));
+    }
+    if accrual_method == "none" {
+        if payload.annual_days.is_some() || payload.carry_over_cap.is_some() || payload.carry_over_expiry_months.is_some() {
+            return invalid("annual_days and carry-over settings need an accrual method");
+        }
+        if payload.allow_negative {
+            return invalid("allow_negative needs an accrual method");
+        }
+    } else if !payload.annual_days.is_some_and(|d| (0.0..=366.0).contains(&d)) {
+        return invalid("annual_days must be between 0 and 366");
+    }
+    if payload.carry_over_cap.is_some_and(|c| !(0.0..=999.0).contains(&c)) {
+        return invalid("carry_over_cap must be between 0 and 999");
+    }
+    if payload.carry_over_expiry_months.is_some_and(|m| !(1..=12).contains(&m)) {
+        return invalid("carry_over_expiry_months must be between 1 and 12");
+    }
+
+    Ok(LeaveTypePayload {
+        name: name.to_string(),
+        accrual_method: Some(accrual_method.to_string()),
+        description: clean("description", &payload.description, MAX_DESCRIPTION_LEN)?,
+        ..payload.clone()
+    })
+}
+
+pub async fn create_type(pool: &PgPool, payload: &LeaveTypePayload) -> sqlx::Result<LeaveType> {
+    let payload = check_payload(pool, payload).await?;
+
+    sqlx::query_as::<_, LeaveType>(&format!(
+        r#"
+        INSERT INTO leave_types (name, description, paid, requires_attachment, min_notice_days,
+            max_consecutive_days, eligible_role_ids, eligible_employment_types, active, accrual_method,
+            annual_days, carry_over_cap, carry_over_expiry_months, allow_negative, accrual_start)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14,
+            COALESCE($15, date_trunc('year', CURRENT_DATE)::DATE))
+        RETURNING {}
+        "#,
+        LEAVE_TYPE_COLUMNS
+    ))
+    .bind(&payload.name)
+    .bind(&payload.description)
+    .bind(payload.paid)
+    .bind(payload.requires_attachment)
+    .bind(payload.min_notice_days)
+    .bind(payload.max_consecutive_days)
+    .bind(&payload.eligible_role_ids)
+    .bind(&payload.eligible_employment_types)
+    .bind(payload.active.unwrap_or(true))
+    .bind(&payload.accrual_method)
+    .bind(payload.annual_days)
+    .bind(payload.carry_over_cap)
+    .bind(payload.carry_over_expiry_months)
+    .bind(payload.allow_negative)
+    .bind(payload.accrual_start)
+    .fetch_one(pool)
+    .await
+}
+
+/// Replaces every setting. Existing requests keep the type and show the new name;
+/// the rules only apply to requests submitted afterwards, and accrual changes to
+/// periods not posted yet.
+pub async fn update_type(pool: &PgPool, id: Uuid, payload: &LeaveTypePayload) -> sqlx::Result<LeaveType> {
+    let payload = check_payload(pool, payload).await?;
+
+    sqlx::query_as::<_, LeaveType>(&format!(
+        r#"
+        UPDATE leave_types
+        SET name = $2, description = $3, paid = $4, requires_attachment = $5, min_notice_days = $6,
+            max_consecutive_days = $7, eligible_role_ids = $8, eligible_employment_types = $9,
+            active = $10, accrual_method = $11, annual_days = $12, carry_over_cap = $13,
+            carry_over_expiry_months = $14, allow_negative = $15, accrual_start = COALESCE($16, accrual_start),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        LEAVE_TYPE_COLUMNS
+    ))
+    .bind(id)
+    .bind(&payload.name)
+    .bind(&payload.description)
+    .bind(payload.paid)
+    .bind(payload.requires_attachment)
+    .bind(payload.min_notice_days)
+    .bind(payload.max_consecutive_days)
+    .bind(&payload.eligible_role_ids)
+    .bind(&payload.eligible_employment_types)
+    .bind(payload.active.unwrap_or(true))
+    .bind(&payload.accrual_method)
+    .bind(payload.annual_days)
+    .bind(payload.carry_over_cap)
+    .bind(payload.carry_over_expiry_months)
+    .bind(payload.allow_negative)
+    .bind(payload.accrual_start)
+    .fetch_one(pool)
+    .await
+}
+
+/// Fails with a foreign key violation while requests use the type; deactivate it instead.
+pub async fn delete_type(pool: &PgPool, id: Uuid) -> sqlx::Result<()> {
+    let result = sqlx::query("DELETE FROM leave_types WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+    Ok(())
+}
+
+/// Checks a new request against the rules of its type, as of `today`.
+/// Returns the type on success.
+pub async fn check_request(
+    pool: &PgPool,
+    user_id: Uuid,
+    leave_type: &str,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    attachment_id: Option<Uuid>,
+    today: NaiveDate,
+) -> sqlx::Result<LeaveType> {
+    let leave_type = sqlx::query_as::<_, LeaveType>(&format!(
+        "SELECT {} FROM leave_types WHERE name = $1 AND active",
+        LEAVE_TYPE_COLUMNS
+    ))
+    .bind(leave_type.trim())
+    .fetch_optional(pool)
+    .await?;
+    let Some(leave_type) = leave_type else {
+        return invalid("Unknown or inactive leave type");
+    };
+
+    if end_date < start_date {
+        return invalid("end_date is before start_date");
+    }
+    let days = (end_date - start_date).num_days() + 1;
+    if let Some(max) = leave_type.max_consecutive_days
+        && days > max as i64
+    {
+        return invalid(format!("{} leave is limited to {} consecutive days", leave_type.name, max));
+    }
+    if (start_date - today).num_days() < leave_type.min_notice_days as i64 {
+        return invalid(format!(
+            "{} leave needs {} days notice",
+            leave_type.name, leave_type.min_notice_days
+        ));
+    }
+
+    let (role_id, employment_type) = sqlx::query_as::<_, (Option<Uuid>, Option<String>)>(
+        "SELECT role_id, employment_type FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+    if !leave_type.is_eligible(role_id, employment_type.as_deref()) {
+        return invalid(format!("Not eligible for {} leave", leave_type.name));
+    }
+
+    if leave_type.requires_attachment && attachment_id.is_none() {
+        return invalid(format!("{} leave requires a supporting document", leave_type.name));
+    }
+
+    Ok(leave_type)
+}
+
+pub fn attachment_key(user_id: Uuid, attachment_id: Uuid) -> String {
+    format!("leave-attachments/{}/{}", user_id, attachment_id)
+}
+
+/// Content type of a supported document, detected from its first bytes so the
+/// uploader's claim isn't trusted.
+pub fn detect_attachment_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else {
+        None
+    }
+}
+
+/// Stores an upload for the user's next request. Only PDF, JPEG and PNG are accepted.
+pub async fn create_attachment(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    user_id: Uuid,
+    file_name: &str,
+    bytes: &[u8],
+) -> sqlx::Result<LeaveAttachment> {
+    let Some(content_type) = detect_attachment_type(bytes) else {
+        return invalid("Attachments must be PDF, JPEG or PNG");
+    };
+    // Keep only the last path segment a browser may send
+    let file_name = file_name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    let file_name = if file_name.is_empty() { "attachment" } else { file_name };
+    let file_name: String = file_name.chars().filter(|c| !c.is_control()).take(MAX_FILE_NAME_LEN).collect();
+
+    let id = Uuid::new_v4();
+    storage
+        .put(&attachment_key(user_id, id), bytes)
+        .await
+        .map_err(|e| sqlx::Error::Io(std::io::Error::other(e)))?;
+
+    sqlx::query_as::<_, LeaveAttachment>(&format!(
+        r#"
+        INSERT INTO leave_attachments (id, user_id, file_name, content_type, size_bytes)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING {}
+        "#,
+        ATTACHMENT_COLUMNS
+    ))
+    .bind(id)
+    .bind(user_id)
+    .bind(&file_name)
+    .bind(content_type)
+    .bind(bytes.len() as i32)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_request_attachment(pool: &PgPool, leave_request_id: Uuid) -> sqlx::Result<LeaveAttachment> {
+    sqlx::query_as::<_, LeaveAttachment>(&format!(
+        "SELECT {} FROM leave_attachments WHERE leave_request_id = $1",
+        ATTACHMENT_COLUMNS
+    ))
+    .bind(leave_request_id)
+    .fetch_one(pool)
+    .await
+}
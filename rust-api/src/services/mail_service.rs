@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::models::invite::OutboxMail;
+
+/// A message to deliver.
+#[derive(Debug, Clone)]
+pub struct OutgoingMail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers mail. `AppState` holds one; swap in an SMTP or API-backed
+/// implementation without touching the callers.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    /// Used when logging delivery failures.
+    fn name(&self) -> &'static str;
+
+    async fn send(&self, mail: &OutgoingMail) -> Result<(), String>;
+}
+
+/// Stores every message in `mail_outbox` instead of sending it. Used for local
+/// development and as the default until a real transport is configured.
+pub struct OutboxMailer {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+    fn name(&self) -> &'static str {
+        "outbox"
+    }
+
+    async fn send(&self, mail: &OutgoingMail) -> Result<(), String> {
+        sqlx::query("INSERT INTO mail_outbox (recipient, subject, body) VALUES ($1, $2, $3)")
+            .bind(&mail.to)
+            .bind(&mail.subject)
+            .bind(&mail.body)
+            .execute(&self.pool)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Most recent messages in the local outbox.
+pub async fn list_outbox(pool: &PgPool, limit: i64) -> sqlx::Result<Vec<OutboxMail>> {
+    sqlx::query_as::<_, OutboxMail>(
+        "SELECT id, recipient, subject, body, created_at FROM mail_outbox ORDER BY created_at DESC LIMIT $1"
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
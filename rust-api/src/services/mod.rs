@@ -2,7 +2,28 @@ pub mod user_service;
 pub mod notification_service;
 pub mod auth_service;
 pub mod policy_service;
+pub mod policy_test_service;
+pub mod policy_impact_service;
+pub mod policy_change_service;
 pub mod leave_service;
 pub mod report_service;
 pub mod payslip_service;
 pub mod template_service;
+pub mod audit_service;
+pub mod break_glass_service;
+pub mod delegation_service;
+pub mod attribute_service;
+pub mod record_share_service;
+pub mod access_review_service;
+pub mod user_import_service;
+pub mod employee_profile_service;
+pub mod org_service;
+pub mod mail_service;
+pub mod invite_service;
+pub mod user_attribute_service;
+pub mod self_profile_service;
+pub mod storage_service;
+pub mod avatar_service;
+pub mod leave_type_service;
+pub mod leave_balance_service;
+pub mod holiday_service;
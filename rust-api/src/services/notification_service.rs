@@ -1,8 +1,47 @@
+use std::collections::BTreeMap;
+
 use crate::events::notification_event::NotificationEvent;
 use crate::state::notification_hub::NotificationHub;
+use sqlx::types::Json;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+/// Notification topics users can switch off, with the event types each covers
+pub const NOTIFICATION_TOPICS: &[(&str, &[&str])] = &[
+    ("leave_requests", &["leave_request", "leave_status_update"]),
+    ("access_reviews", &["access_review"]),
+    ("delegations", &["delegation"]),
+    ("record_shares", &["record_share"]),
+    ("break_glass", &["break_glass"]),
+    ("policy_changes", &["policy_change"]),
+    ("users", &["USER_CREATED", "USER_ACTIVATED", "USER_DELETED", "USER_STATUS_CHANGED", "USERS_IMPORTED"]),
+];
+
+pub fn is_topic(name: &str) -> bool {
+    NOTIFICATION_TOPICS.iter().any(|(topic, _)| *topic == name)
+}
+
+/// Whether someone with these preferences gets events of `event_type`. Topics
+/// are on unless switched off; event types outside every topic always go out.
+pub fn wants(preferences: &BTreeMap<String, bool>, event_type: &str) -> bool {
+    NOTIFICATION_TOPICS
+        .iter()
+        .find(|(_, event_types)| event_types.contains(&event_type))
+        .and_then(|(topic, _)| preferences.get(*topic))
+        .copied()
+        .unwrap_or(true)
+}
+
+pub async fn get_preferences(pool: &PgPool, user_id: Uuid) -> sqlx::Result<BTreeMap<String, bool>> {
+    let Json(preferences) = sqlx::query_scalar::<_, Json<BTreeMap<String, bool>>>(
+        "SELECT notification_preferences FROM users WHERE id = $1",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+    Ok(preferences)
+}
+
 pub async fn create_notification(
     pool: &PgPool,
     hub: &NotificationHub,
@@ -61,4 +100,42 @@ pub async fn mark_all_as_read(pool: &PgPool) -> sqlx::Result<()> {
         .execute(pool)
         .await?;
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preferences(entries: &[(&str, bool)]) -> BTreeMap<String, bool> {
+        entries.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn topics_are_on_by_default() {
+        assert!(wants(&preferences(&[]), "leave_request"));
+        assert!(wants(&preferences(&[("leave_requests", true)]), "leave_status_update"));
+    }
+
+    #[test]
+    fn switched_off_topic_covers_all_its_event_types() {
+        let prefs = preferences(&[("leave_requests", false)]);
+        assert!(!wants(&prefs, "leave_request"));
+        assert!(!wants(&prefs, "leave_status_update"));
+        assert!(wants(&prefs, "access_review"));
+    }
+
+    #[test]
+    fn events_outside_every_topic_always_go_out() {
+        assert!(wants(&preferences(&[("users", false)]), "something_new"));
+        assert!(!wants(&preferences(&[("users", false)]), "USER_CREATED"));
+    }
+
+    #[test]
+    fn every_event_type_belongs_to_one_topic() {
+        let mut seen = std::collections::HashSet::new();
+        for (_, event_types) in NOTIFICATION_TOPICS {
+            for event_type in *event_types {
+                assert!(seen.insert(*event_type), "'{}' is in two topics", event_type);
+            }
+        }
+    }
+}
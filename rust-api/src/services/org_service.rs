@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::models::org::{OrgChartNode, OrgMember};
+
+pub const DEFAULT_CHART_DEPTH: i32 = 3;
+pub const MAX_CHART_DEPTH: i32 = 10;
+
+const MEMBER_COLUMNS: &str = "u.id, u.username, u.job_title, u.department, u.manager_id, user_status(u.id) AS status, t.depth";
+
+/// The chart below `root`, or below every user without a manager, down to
+/// `depth` levels. Each recursion tracks its path, so a loop in the data can't
+/// make it run forever.
+pub async fn get_chart(pool: &PgPool, root: Option<Uuid>, depth: i32) -> sqlx::Result<Vec<OrgChartNode>> {
+    let members = sqlx::query_as::<_, OrgMember>(&format!(
+        r#"
+        WITH RECURSIVE tree AS (
+            SELECT u.id, 0 AS depth, ARRAY[u.id] AS path
+            FROM users u
+            WHERE ($1::UUID IS NULL AND u.manager_id IS NULL) OR u.id = $1
+            UNION ALL
+            SELECT u.id, t.depth + 1, t.path || u.id
+            FROM tree t
+            JOIN users u ON u.manager_id = t.id
+            WHERE t.depth < $2 AND NOT u.id = ANY(t.path)
+        )
+        SELECT {}
+        FROM tree t
+        JOIN users u ON u.id = t.id
+        ORDER BY t.depth ASC, u.username ASC
+        "#,
+        MEMBER_COLUMNS
+    ))
+    .bind(root)
+    .bind(depth.clamp(0, MAX_CHART_DEPTH))
+    .fetch_all(pool)
+    .await?;
+
+    if root.is_some() && members.is_empty() {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    let mut by_manager: HashMap<Option<Uuid>, Vec<&OrgMember>> = HashMap::new();
+    for member in members.iter().filter(|m| m.depth > 0) {
+        by_manager.entry(member.manager_id).or_default().push(member);
+    }
+
+    fn build(member: &OrgMember, by_manager: &HashMap<Option<Uuid>, Vec<&OrgMember>>) -> OrgChartNode {
+        OrgChartNode {
+            id: member.id,
+            username: member.username.clone(),
+            job_title: member.job_title.clone(),
+            department: member.department.clone(),
+            status: member.status.clone(),
+            reports: by_manager
+                .get(&Some(member.id))
+                .map(|reports| reports.iter().map(|r| build(r, by_manager)).collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    Ok(members
+        .iter()
+        .filter(|m| m.depth == 0)
+        .map(|m| build(m, &by_manager))
+        .collect())
+}
+
+/// Users reporting to `manager_id`: direct reports only, or every level below.
+pub async fn list_reports(pool: &PgPool, manager_id: Uuid, transitive: bool) -> sqlx::Result<Vec<OrgMember>> {
+    sqlx::query_as::<_, OrgMember>(&format!(
+        r#"
+        WITH RECURSIVE tree AS (
+            SELECT u.id, 1 AS depth, ARRAY[$1, u.id] AS path
+            FROM users u
+            WHERE u.manager_id = $1
+            UNION ALL
+            SELECT u.id, t.depth + 1, t.path || u.id
+            FROM tree t
+            JOIN users u ON u.manager_id = t.id
+            WHERE $2 AND NOT u.id = ANY(t.path)
+        )
+        SELECT {}
+        FROM tree t
+        JOIN users u ON u.id = t.id
+        ORDER BY t.depth ASC, u.username ASC
+        "#,
+        MEMBER_COLUMNS
+    ))
+    .bind(manager_id)
+    .bind(transitive)
+    .fetch_all(pool)
+    .await
+}
+
+/// The user's managers, from the direct manager up to the top.
+pub async fn get_chain(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<OrgMember>> {
+    sqlx::query_as::<_, OrgMember>(&format!(
+        r#"
+        WITH RECURSIVE tree AS (
+            SELECT m.id, 1 AS depth, ARRAY[$1, m.id] AS path
+            FROM users e
+            JOIN users m ON m.id = e.manager_id
+            WHERE e.id = $1
+            UNION ALL
+            SELECT m.id, t.depth + 1, t.path || m.id
+            FROM tree t
+            JOIN users e ON e.id = t.id
+            JOIN users m ON m.id = e.manager_id
+            WHERE NOT m.id = ANY(t.path)
+        )
+        SELECT {}
+        FROM tree t
+        JOIN users u ON u.id = t.id
+        ORDER BY t.depth ASC
+        "#,
+        MEMBER_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Whether `manager_id` is somewhere in `employee_id`'s management chain.
+/// Takes any executor so it can run inside the caller's transaction.
+pub async fn is_in_management_chain(
+    executor: impl PgExecutor<'_>,
+    manager_id: Uuid,
+    employee_id: Uuid,
+) -> sqlx::Result<bool> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        WITH RECURSIVE chain AS (
+            SELECT e.manager_id AS id, ARRAY[e.id] AS path
+            FROM users e
+            WHERE e.id = $2 AND e.manager_id IS NOT NULL
+            UNION ALL
+            SELECT e.manager_id, c.path || e.id
+            FROM chain c
+            JOIN users e ON e.id = c.id
+            WHERE e.manager_id IS NOT NULL AND NOT e.id = ANY(c.path)
+        )
+        SELECT EXISTS (SELECT 1 FROM chain WHERE id = $1)
+        "#
+    )
+    .bind(manager_id)
+    .bind(employee_id)
+    .fetch_one(executor)
+    .await
+}
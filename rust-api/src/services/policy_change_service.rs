@@ -0,0 +1,282 @@
+use sqlx::{Connection, PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::models::break_glass::CreateBreakGlassGrantPayload;
+use crate::models::policy_change::{PolicyChange, PolicyChangeEvent, PolicyChangeRequest};
+use crate::services::{break_glass_service, policy_service, policy_test_service};
+
+const CHANGE_COLUMNS: &str = "id, policy_id, change_type, payload, status, proposed_by, decided_by, decision_comment, created_at, decided_at";
+
+/// Records a pending change. The policy row is locked so the proposal can't
+/// interleave with an edit to its rules or test cases, and a binding is checked
+/// now rather than after someone has approved it.
+pub async fn propose(
+    pool: &PgPool,
+    change: &PolicyChange,
+    proposed_by: Uuid,
+) -> sqlx::Result<PolicyChangeRequest> {
+    let payload = serde_json::to_value(change).map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT id FROM policies WHERE id = $1 FOR UPDATE")
+        .bind(change.policy_id())
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| sqlx::Error::Protocol("Unknown policy".into()))?;
+
+    match change {
+        PolicyChange::Bind { subject_type, subject_id, .. } => {
+            policy_service::check_binding_subject(&mut *tx, subject_type, *subject_id).await?;
+        }
+        PolicyChange::GrantBreakGlass { policy_id, granted_by, user_id, max_duration_minutes } => {
+            let grant = CreateBreakGlassGrantPayload {
+                user_id: *user_id,
+                policy_id: *policy_id,
+                max_duration_minutes: *max_duration_minutes,
+            };
+            break_glass_service::check_grant(&mut *tx, *granted_by, &grant).await?;
+        }
+        _ => {}
+    }
+
+    let request = sqlx::query_as::<_, PolicyChangeRequest>(&format!(
+        r#"
+        INSERT INTO policy_change_requests (policy_id, change_type, payload, proposed_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING {}
+        "#,
+        CHANGE_COLUMNS
+    ))
+    .bind(change.policy_id())
+    .bind(change.change_type())
+    .bind(payload)
+    .bind(proposed_by)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    record_event(&mut *tx, request.id, "proposed", Some(proposed_by), None).await?;
+    tx.commit().await?;
+    Ok(request)
+}
+
+pub async fn list_changes(pool: &PgPool, status: Option<&str>) -> sqlx::Result<Vec<PolicyChangeRequest>> {
+    sqlx::query_as::<_, PolicyChangeRequest>(&format!(
+        r#"
+        SELECT {}
+        FROM policy_change_requests
+        WHERE ($1::VARCHAR IS NULL OR status = $1)
+        ORDER BY created_at DESC
+        "#,
+        CHANGE_COLUMNS
+    ))
+    .bind(status)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_change(pool: &PgPool, id: Uuid) -> sqlx::Result<PolicyChangeRequest> {
+    sqlx::query_as::<_, PolicyChangeRequest>(&format!(
+        "SELECT {} FROM policy_change_requests WHERE id = $1",
+        CHANGE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn list_events(pool: &PgPool, change_request_id: Uuid) -> sqlx::Result<Vec<PolicyChangeEvent>> {
+    sqlx::query_as::<_, PolicyChangeEvent>(
+        r#"
+        SELECT id, change_request_id, event, actor_id, comment, created_at
+        FROM policy_change_events
+        WHERE change_request_id = $1
+        ORDER BY created_at ASC
+        "#
+    )
+    .bind(change_request_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn record_event(
+    executor: impl PgExecutor<'_>,
+    change_request_id: Uuid,
+    event: &str,
+    actor_id: Option<Uuid>,
+    comment: Option<&str>,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO policy_change_events (change_request_id, event, actor_id, comment)
+        VALUES ($1, $2, $3, $4)
+        "#
+    )
+    .bind(change_request_id)
+    .bind(event)
+    .bind(actor_id)
+    .bind(comment)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Moves a pending request to its decided status. The proposer can never decide
+/// their own change; `None` means the request was not pending or was self-proposed.
+async fn decide(
+    conn: &mut PgConnection,
+    id: Uuid,
+    status: &str,
+    decided_by: Uuid,
+    comment: Option<&str>,
+) -> sqlx::Result<Option<PolicyChangeRequest>> {
+    let request = sqlx::query_as::<_, PolicyChangeRequest>(&format!(
+        r#"
+        UPDATE policy_change_requests
+        SET status = $2, decided_by = $3, decision_comment = $4, decided_at = CURRENT_TIMESTAMP
+        WHERE id = $1 AND status = 'pending' AND proposed_by IS DISTINCT FROM $3
+        RETURNING {}
+        "#,
+        CHANGE_COLUMNS
+    ))
+    .bind(id)
+    .bind(status)
+    .bind(decided_by)
+    .bind(comment)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if request.is_some() {
+        let event = if status == "approved" { "approved" } else { "rejected" };
+        record_event(&mut *conn, id, event, Some(decided_by), comment).await?;
+    }
+    Ok(request)
+}
+
+/// What approving a change came to.
+pub enum Approval {
+    Applied(PolicyChangeRequest),
+    /// Approved but refused when applied, e.g. by a failing test suite; the
+    /// request is now `failed` and the reason is recorded on it.
+    Failed(PolicyChangeRequest, String),
+}
+
+/// Approves a pending change and applies it in one transaction, with the
+/// request row locked. A domain failure while applying leaves the request
+/// `failed`; any other error rolls everything back and it stays pending.
+/// `None` means the request was not pending or was self-proposed.
+pub async fn approve(
+    pool: &PgPool,
+    id: Uuid,
+    approver: Uuid,
+    comment: Option<&str>,
+) -> sqlx::Result<Option<Approval>> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT id FROM policy_change_requests WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    let Some(approved) = decide(&mut tx, id, "approved", approver, comment).await? else {
+        return Ok(None);
+    };
+    let change = approved.change().map_err(|e| sqlx::Error::Protocol(e.to_string()))?;
+
+    let mut savepoint = Connection::begin(&mut *tx).await?;
+    let outcome = match apply(&mut savepoint, pool, &change).await {
+        Ok(()) => {
+            savepoint.commit().await?;
+            record_event(&mut *tx, id, "applied", Some(approver), None).await?;
+            Approval::Applied(approved)
+        }
+        Err(sqlx::Error::Protocol(reason)) => {
+            savepoint.rollback().await?;
+            let failed = mark_failed(&mut tx, id, approver, &reason).await?;
+            Approval::Failed(failed, reason)
+        }
+        Err(e) => return Err(e),
+    };
+
+    tx.commit().await?;
+    Ok(Some(outcome))
+}
+
+pub async fn reject(
+    pool: &PgPool,
+    id: Uuid,
+    approver: Uuid,
+    comment: Option<&str>,
+) -> sqlx::Result<Option<PolicyChangeRequest>> {
+    let mut tx = pool.begin().await?;
+    let rejected = decide(&mut tx, id, "rejected", approver, comment).await?;
+    tx.commit().await?;
+    Ok(rejected)
+}
+
+/// Records that an approved change could not be applied.
+async fn mark_failed(
+    conn: &mut PgConnection,
+    id: Uuid,
+    actor_id: Uuid,
+    reason: &str,
+) -> sqlx::Result<PolicyChangeRequest> {
+    let request = sqlx::query_as::<_, PolicyChangeRequest>(&format!(
+        "UPDATE policy_change_requests SET status = 'failed' WHERE id = $1 RETURNING {}",
+        CHANGE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    record_event(&mut *conn, id, "failed", Some(actor_id), Some(reason)).await?;
+    Ok(request)
+}
+
+/// Performs the underlying policy change on `conn`. The test suite of an
+/// activation reads through `pool`; test cases can't change while the request
+/// is pending. Domain failures such as a failing suite come back as
+/// `sqlx::Error::Protocol`.
+async fn apply(conn: &mut PgConnection, pool: &PgPool, change: &PolicyChange) -> sqlx::Result<()> {
+    match change {
+        PolicyChange::Activate { policy_id } => {
+            let suite = policy_test_service::run_suite(pool, *policy_id).await?;
+            if !suite.passed {
+                return Err(sqlx::Error::Protocol(format!(
+                    "Policy test suite failed: {} of {} cases failed",
+                    suite.failed, suite.total
+                )));
+            }
+            if policy_service::mark_active(&mut *conn, *policy_id).await? == 0 {
+                return Err(sqlx::Error::Protocol("Policy is no longer a draft".into()));
+            }
+        }
+        PolicyChange::Archive { policy_id } => {
+            policy_service::archive_policy(&mut *conn, *policy_id).await?;
+        }
+        PolicyChange::Delete { policy_id } => {
+            if policy_service::delete_policy(&mut *conn, *policy_id).await? == 0 {
+                return Err(sqlx::Error::Protocol("Policy no longer exists".into()));
+            }
+        }
+        PolicyChange::Bind { policy_id, subject_type, subject_id } => {
+            policy_service::check_binding_subject(&mut *conn, subject_type, *subject_id).await?;
+            policy_service::bind_policy(&mut *conn, *policy_id, subject_type, *subject_id)
+                .await
+                .map_err(|e| match e {
+                    sqlx::Error::RowNotFound => sqlx::Error::Protocol("The policy is already bound to this subject".into()),
+                    e => e,
+                })?;
+        }
+        PolicyChange::Unbind { binding_id, .. } => {
+            policy_service::unbind_policy(&mut *conn, *binding_id).await?;
+        }
+        PolicyChange::GrantBreakGlass { policy_id, granted_by, user_id, max_duration_minutes } => {
+            let grant = CreateBreakGlassGrantPayload {
+                user_id: *user_id,
+                policy_id: *policy_id,
+                max_duration_minutes: *max_duration_minutes,
+            };
+            break_glass_service::create_grant(conn, *granted_by, &grant).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::policy_impact::{ImpactReport, ImpactScenario, UserImpact};
+use crate::models::row_filter::RowFilter;
+use crate::models::user::User;
+use crate::models::user_role::{AuthContext, PolicyBinding, PolicyRule, PolicyStatus};
+use crate::services::{auth_service, policy_service};
+use crate::utils::permissions::{self, Permission};
+
+/// Active users reached by the policy's bindings, either directly or through
+/// their role. When `binding_id` is set only that binding is considered.
+async fn list_bound_users(
+    pool: &PgPool,
+    policy_id: Uuid,
+    binding_id: Option<Uuid>,
+) -> sqlx::Result<Vec<User>> {
+    sqlx::query_as::<_, User>(
+        r#"
+        SELECT DISTINCT u.id, u.username, u.email, u.password_hash, u.role_id, user_status(u.id) AS status, u.created_at, u.updated_at
+        FROM users u
+        JOIN policy_bindings pb ON pb.policy_id = $1
+        AND (
+            (pb.subject_type = 'user' AND pb.subject_id = u.id)
+            OR (pb.subject_type = 'role' AND pb.subject_id = u.role_id)
+        )
+        WHERE ($2::UUID IS NULL OR pb.id = $2)
+        AND user_status(u.id) = 'active'
+        ORDER BY u.username ASC
+        "#
+    )
+    .bind(policy_id)
+    .bind(binding_id)
+    .fetch_all(pool)
+    .await
+}
+
+fn binding_covers(binding: &PolicyBinding, user: &User) -> bool {
+    match binding.subject_type.as_str() {
+        "user" => binding.subject_id == user.id,
+        "role" => Some(binding.subject_id) == user.role_id,
+        _ => false,
+    }
+}
+
+/// Permissions the user holds for at least some records; rules scoped by owner
+/// or department count.
+fn effective_permissions(rules: &[PolicyRule], user_id: Uuid, context: &AuthContext) -> HashSet<Permission> {
+    permissions::all_permissions()
+        .filter(|p| auth_service::partial_evaluate(rules, p.action, p.resource, user_id, context) != RowFilter::Nothing)
+        .collect()
+}
+
+fn sorted(set: HashSet<&Permission>) -> Vec<Permission> {
+    let mut list: Vec<Permission> = set.into_iter().copied().collect();
+    list.sort_by_key(|p| (p.resource, p.action));
+    list
+}
+
+/// Compares every affected user's effective permissions now against the
+/// hypothetical state described by the scenario.
+pub async fn analyze(pool: &PgPool, scenario: ImpactScenario) -> sqlx::Result<ImpactReport> {
+    let (policy_id, binding) = match &scenario {
+        ImpactScenario::ActivateDraft { policy_id } | ImpactScenario::ArchivePolicy { policy_id } => {
+            (*policy_id, None)
+        }
+        ImpactScenario::RemoveBinding { binding_id } => {
+            let binding = policy_service::get_binding(pool, *binding_id).await?;
+            (binding.policy_id, Some(binding))
+        }
+    };
+
+    let policy = policy_service::get_policy(pool, policy_id).await?;
+    match &scenario {
+        ImpactScenario::ActivateDraft { .. } if policy.status != PolicyStatus::Draft.to_string() => {
+            return Err(sqlx::Error::Protocol("Only draft policies can be activated".into()));
+        }
+        ImpactScenario::ArchivePolicy { .. } if policy.status == PolicyStatus::Archived.to_string() => {
+            return Err(sqlx::Error::Protocol("Policy is already archived".into()));
+        }
+        _ => {}
+    }
+
+    let users = list_bound_users(pool, policy_id, binding.as_ref().map(|b| b.id)).await?;
+    let remaining_bindings: Vec<PolicyBinding> = match &binding {
+        Some(b) => policy_service::list_policy_bindings(pool, policy_id)
+            .await?
+            .into_iter()
+            .filter(|other| other.id != b.id)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let context = AuthContext {
+        department: None,
+        location: None,
+        time: chrono::Utc::now().to_rfc3339(),
+        resource_owner_id: None,
+        attributes: Default::default(),
+    };
+
+    // One fetch for everyone; a draft's rules are only in it when activating it
+    let draft = matches!(scenario, ImpactScenario::ActivateDraft { .. });
+    let user_ids: Vec<Uuid> = users.iter().map(|u| u.id).collect();
+    let mut rules_by_user: HashMap<Uuid, Vec<PolicyRule>> = HashMap::new();
+    for (user_id, rule) in auth_service::fetch_rules_for_users(pool, &user_ids, draft.then_some(policy_id)).await? {
+        rules_by_user.entry(user_id).or_default().push(rule);
+    }
+
+    let mut impacts = Vec::with_capacity(users.len());
+    for user in &users {
+        let fetched = rules_by_user.remove(&user.id).unwrap_or_default();
+        let current_rules: Vec<PolicyRule> = if draft {
+            fetched.iter().filter(|r| r.policy_id != policy_id).cloned().collect()
+        } else {
+            fetched.clone()
+        };
+
+        let hypothetical_rules = match &scenario {
+            ImpactScenario::ActivateDraft { .. } => fetched,
+            ImpactScenario::ArchivePolicy { .. } => current_rules
+                .iter()
+                .filter(|r| r.policy_id != policy_id)
+                .cloned()
+                .collect(),
+            ImpactScenario::RemoveBinding { .. } => {
+                // The policy still applies if another of its bindings reaches the user.
+                if remaining_bindings.iter().any(|b| binding_covers(b, user)) {
+                    current_rules.clone()
+                } else {
+                    current_rules
+                        .iter()
+                        .filter(|r| r.policy_id != policy_id)
+                        .cloned()
+                        .collect()
+                }
+            }
+        };
+
+        let before = effective_permissions(&current_rules, user.id, &context);
+        let after = effective_permissions(&hypothetical_rules, user.id, &context);
+
+        impacts.push(UserImpact {
+            user_id: user.id,
+            username: user.username.clone(),
+            gained: sorted(after.difference(&before).collect()),
+            lost: sorted(before.difference(&after).collect()),
+        });
+    }
+
+    let changed_users = impacts
+        .iter()
+        .filter(|u| !u.gained.is_empty() || !u.lost.is_empty())
+        .count();
+
+    Ok(ImpactReport {
+        scenario,
+        policy_id,
+        evaluated_users: impacts.len(),
+        changed_users,
+        users: impacts,
+    })
+}
@@ -1,6 +1,11 @@
-use sqlx::PgPool;
+use sqlx::{PgExecutor, PgPool};
 use uuid::Uuid;
 use crate::models::user_role::{Policy, PolicyRule, PolicyBinding, PolicyStatus, Role};
+use crate::models::row_filter::RuleConditions;
+use crate::models::policy_test::PolicyTestSuiteResult;
+use crate::services::policy_test_service;
+use crate::utils::permissions;
+use crate::utils::policy_lang::RuleSource;
 
 // Roles
 pub async fn list_roles(pool: &PgPool) -> sqlx::Result<Vec<Role>> {
@@ -32,22 +37,34 @@ pub async fn create_policy(
     .await
 }
 
-pub async fn activate_policy(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+/// Activates a draft policy only if its test suite passes.
+/// The suite result is returned either way; zero rows are affected when it fails.
+pub async fn activate_policy(pool: &PgPool, id: Uuid) -> sqlx::Result<(u64, PolicyTestSuiteResult)> {
+    let suite = policy_test_service::run_suite(pool, id).await?;
+    if !suite.passed {
+        return Ok((0, suite));
+    }
+
+    Ok((mark_active(pool, id).await?, suite))
+}
+
+/// Moves a draft policy to active without running its suite; callers run it first.
+pub async fn mark_active(executor: impl PgExecutor<'_>, id: Uuid) -> sqlx::Result<u64> {
     let result = sqlx::query(
         "UPDATE policies SET status = 'active', updated_at = CURRENT_TIMESTAMP WHERE id = $1 AND status = 'draft'"
     )
     .bind(id)
-    .execute(pool)
+    .execute(executor)
     .await?;
     Ok(result.rows_affected())
 }
 
-pub async fn archive_policy(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+pub async fn archive_policy(executor: impl PgExecutor<'_>, id: Uuid) -> sqlx::Result<u64> {
     let result = sqlx::query(
         "UPDATE policies SET status = 'archived', is_archived = TRUE, updated_at = CURRENT_TIMESTAMP WHERE id = $1"
     )
     .bind(id)
-    .execute(pool)
+    .execute(executor)
     .await?;
     Ok(result.rows_affected())
 }
@@ -66,15 +83,48 @@ pub async fn list_policies(pool: &PgPool) -> sqlx::Result<Vec<Policy>> {
     .await
 }
 
-pub async fn delete_policy(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+pub async fn get_policy(pool: &PgPool, id: Uuid) -> sqlx::Result<Policy> {
+    sqlx::query_as::<_, Policy>("SELECT * FROM policies WHERE id = $1")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn delete_policy(executor: impl PgExecutor<'_>, id: Uuid) -> sqlx::Result<u64> {
     let result = sqlx::query("DELETE FROM policies WHERE id = $1")
         .bind(id)
-        .execute(pool)
+        .execute(executor)
         .await?;
     Ok(result.rows_affected())
 }
 
+/// Fails while a change to the policy awaits approval, so an approver signs off
+/// on the rules and test cases that actually get applied. `what` names the
+/// edit being refused.
+pub async fn check_no_pending_change(executor: impl PgExecutor<'_>, policy_id: Uuid, what: &str) -> sqlx::Result<()> {
+    let pending: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM policy_change_requests WHERE policy_id = $1 AND status = 'pending')"
+    )
+    .bind(policy_id)
+    .fetch_one(executor)
+    .await?;
+
+    if pending {
+        return Err(sqlx::Error::Protocol(format!("Cannot modify {} while a change to the policy awaits approval", what)));
+    }
+    Ok(())
+}
+
 // Rules
+
+/// Rules only change on a draft policy with no change awaiting approval.
+async fn check_rules_editable(executor: impl PgExecutor<'_>, policy: &Policy) -> sqlx::Result<()> {
+    if policy.status != PolicyStatus::Draft.to_string() {
+        return Err(sqlx::Error::Protocol("Cannot modify rules of a non-draft policy".into()));
+    }
+    check_no_pending_change(executor, policy.id, "rules").await
+}
+
 pub async fn add_policy_rule(
     pool: &PgPool,
     policy_id: Uuid,
@@ -89,8 +139,17 @@ pub async fn add_policy_rule(
         .fetch_one(pool)
         .await?;
 
-    if policy.status != PolicyStatus::Draft.to_string() {
-        return Err(sqlx::Error::Protocol("Cannot modify rules of a non-draft policy".into()));
+    check_rules_editable(pool, &policy).await?;
+
+    // Reject names the registry doesn't know; such rules would silently never match.
+    if !permissions::is_known_action(action) {
+        return Err(sqlx::Error::Protocol(format!("Unknown action '{}'", action)));
+    }
+    if !permissions::is_known_resource(resource) {
+        return Err(sqlx::Error::Protocol(format!("Unknown resource '{}'", resource)));
+    }
+    if let Some(value) = conditions.as_ref().filter(|v| !v.is_null()) {
+        RuleConditions::parse(value).map_err(sqlx::Error::Protocol)?;
     }
 
     sqlx::query_as::<_, PolicyRule>(
@@ -109,6 +168,50 @@ pub async fn add_policy_rule(
     .await
 }
 
+/// Replaces every rule of a draft policy with the parsed source, atomically.
+/// Rules keep their source order through `created_at`.
+pub async fn replace_policy_rules(
+    pool: &PgPool,
+    policy_id: Uuid,
+    rules: &[RuleSource],
+) -> sqlx::Result<Vec<PolicyRule>> {
+    let mut tx = pool.begin().await?;
+
+    let policy = sqlx::query_as::<_, Policy>("SELECT * FROM policies WHERE id = $1 FOR UPDATE")
+        .bind(policy_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    check_rules_editable(&mut *tx, &policy).await?;
+
+    sqlx::query("DELETE FROM policy_rules WHERE policy_id = $1")
+        .bind(policy_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut inserted = Vec::with_capacity(rules.len());
+    for rule in rules {
+        let row = sqlx::query_as::<_, PolicyRule>(
+            r#"
+            INSERT INTO policy_rules (policy_id, effect, resource, action, conditions, created_at)
+            VALUES ($1, $2, $3, $4, $5, clock_timestamp())
+            RETURNING id, policy_id, effect, resource, action, conditions, created_at
+            "#
+        )
+        .bind(policy_id)
+        .bind(&rule.effect)
+        .bind(&rule.resource)
+        .bind(&rule.action)
+        .bind(rule.conditions_json())
+        .fetch_one(&mut *tx)
+        .await?;
+        inserted.push(row);
+    }
+
+    tx.commit().await?;
+    Ok(inserted)
+}
+
 pub async fn remove_policy_rule(pool: &PgPool, rule_id: Uuid) -> sqlx::Result<u64> {
     // Check if policy is draft
     let rule = sqlx::query_as::<_, PolicyRule>("SELECT * FROM policy_rules WHERE id = $1")
@@ -121,9 +224,7 @@ pub async fn remove_policy_rule(pool: &PgPool, rule_id: Uuid) -> sqlx::Result<u6
         .fetch_one(pool)
         .await?;
 
-    if policy.status != PolicyStatus::Draft.to_string() {
-        return Err(sqlx::Error::Protocol("Cannot modify rules of a non-draft policy".into()));
-    }
+    check_rules_editable(pool, &policy).await?;
 
     let result = sqlx::query("DELETE FROM policy_rules WHERE id = $1")
         .bind(rule_id)
@@ -134,7 +235,7 @@ pub async fn remove_policy_rule(pool: &PgPool, rule_id: Uuid) -> sqlx::Result<u6
 
 pub async fn list_policy_rules(pool: &PgPool, policy_id: Uuid) -> sqlx::Result<Vec<PolicyRule>> {
     sqlx::query_as::<_, PolicyRule>(
-        "SELECT * FROM policy_rules WHERE policy_id = $1"
+        "SELECT * FROM policy_rules WHERE policy_id = $1 ORDER BY created_at ASC, id ASC"
     )
     .bind(policy_id)
     .fetch_all(pool)
@@ -151,8 +252,27 @@ pub async fn list_policy_bindings(pool: &PgPool, policy_id: Uuid) -> sqlx::Resul
 }
 
 // Bindings
+
+/// A binding names a role or a user that exists.
+pub async fn check_binding_subject(executor: impl PgExecutor<'_>, subject_type: &str, subject_id: Uuid) -> sqlx::Result<()> {
+    let table = match subject_type {
+        "role" => "roles",
+        "user" => "users",
+        other => return Err(sqlx::Error::Protocol(format!("Unknown subject type '{}'", other))),
+    };
+    let exists: bool = sqlx::query_scalar(&format!("SELECT EXISTS (SELECT 1 FROM {} WHERE id = $1)", table))
+        .bind(subject_id)
+        .fetch_one(executor)
+        .await?;
+
+    if !exists {
+        return Err(sqlx::Error::Protocol(format!("Unknown {} {}", subject_type, subject_id)));
+    }
+    Ok(())
+}
+
 pub async fn bind_policy(
-    pool: &PgPool,
+    executor: impl PgExecutor<'_>,
     policy_id: Uuid,
     subject_type: &str,
     subject_id: Uuid,
@@ -168,18 +288,26 @@ pub async fn bind_policy(
     .bind(policy_id)
     .bind(subject_type)
     .bind(subject_id)
-    .fetch_one(pool)
+    .fetch_one(executor)
     .await
 }
 
-pub async fn unbind_policy(pool: &PgPool, binding_id: Uuid) -> sqlx::Result<u64> {
+pub async fn get_binding(pool: &PgPool, binding_id: Uuid) -> sqlx::Result<PolicyBinding> {
+    sqlx::query_as::<_, PolicyBinding>("SELECT * FROM policy_bindings WHERE id = $1")
+        .bind(binding_id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn unbind_policy(executor: impl PgExecutor<'_>, binding_id: Uuid) -> sqlx::Result<u64> {
     let result = sqlx::query("DELETE FROM policy_bindings WHERE id = $1")
         .bind(binding_id)
-        .execute(pool)
+        .execute(executor)
         .await?;
     Ok(result.rows_affected())
 }
 
+#[allow(dead_code)]
 pub async fn list_bindings_for_subject(
     pool: &PgPool,
     subject_type: &str,
@@ -0,0 +1,216 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::policy_test::{
+    CreatePolicyTestCasePayload, PolicyTestCase, PolicyTestCaseResult, PolicyTestSuiteResult,
+};
+use crate::models::user_role::{AuthContext, Decision};
+use crate::services::{auth_service, policy_service, user_service};
+use crate::utils::permissions;
+
+pub async fn list_test_cases(pool: &PgPool, policy_id: Uuid) -> sqlx::Result<Vec<PolicyTestCase>> {
+    sqlx::query_as::<_, PolicyTestCase>(
+        "SELECT * FROM policy_test_cases WHERE policy_id = $1 ORDER BY created_at ASC"
+    )
+    .bind(policy_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Test cases can't change while a change to the policy awaits approval, so an
+/// approved activation runs the suite the approver saw.
+pub async fn add_test_case(
+    pool: &PgPool,
+    policy_id: Uuid,
+    payload: CreatePolicyTestCasePayload,
+) -> sqlx::Result<PolicyTestCase> {
+    if !["role", "user"].contains(&payload.subject_type.as_str()) {
+        return Err(sqlx::Error::Protocol(format!("Unknown subject type '{}'", payload.subject_type)));
+    }
+    if !["allow", "deny"].contains(&payload.expected_effect.as_str()) {
+        return Err(sqlx::Error::Protocol(format!("Unknown effect '{}'", payload.expected_effect)));
+    }
+    if !permissions::is_known_action(&payload.action) || payload.action == permissions::WILDCARD {
+        return Err(sqlx::Error::Protocol(format!("Unknown action '{}'", payload.action)));
+    }
+    if !permissions::is_known_resource(&payload.resource) || payload.resource == permissions::WILDCARD {
+        return Err(sqlx::Error::Protocol(format!("Unknown resource '{}'", payload.resource)));
+    }
+
+    let context = payload
+        .context
+        .map(|c| serde_json::to_value(c).map_err(|e| sqlx::Error::Protocol(e.to_string())))
+        .transpose()?;
+    if let Some(value) = &context {
+        parse_context(value).map_err(sqlx::Error::Protocol)?;
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT id FROM policies WHERE id = $1 FOR UPDATE")
+        .bind(policy_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    policy_service::check_no_pending_change(&mut *tx, policy_id, "test cases").await?;
+
+    let case = sqlx::query_as::<_, PolicyTestCase>(
+        r#"
+        INSERT INTO policy_test_cases (policy_id, name, subject_type, subject_id, action, resource, context, expected_effect)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, policy_id, name, subject_type, subject_id, action, resource, context, expected_effect, created_at
+        "#
+    )
+    .bind(policy_id)
+    .bind(&payload.name)
+    .bind(&payload.subject_type)
+    .bind(payload.subject_id)
+    .bind(&payload.action)
+    .bind(&payload.resource)
+    .bind(context)
+    .bind(&payload.expected_effect)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(case)
+}
+
+pub async fn remove_test_case(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+    let policy_id = sqlx::query_scalar::<_, Uuid>(
+        "SELECT p.id FROM policies p JOIN policy_test_cases c ON c.policy_id = p.id WHERE c.id = $1 FOR UPDATE OF p"
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(policy_id) = policy_id else {
+        return Ok(0);
+    };
+    policy_service::check_no_pending_change(&mut *tx, policy_id, "test cases").await?;
+
+    let result = sqlx::query("DELETE FROM policy_test_cases WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(result.rows_affected())
+}
+
+/// The stored context of a case, as evaluated. `time` must be RFC 3339.
+fn parse_context(value: &serde_json::Value) -> Result<AuthContext, String> {
+    let context = serde_json::from_value::<AuthContext>(value.clone())
+        .map_err(|e| format!("Invalid test context: {}", e))?;
+    chrono::DateTime::parse_from_rfc3339(&context.time)
+        .map_err(|_| format!("Invalid test context: time '{}' is not RFC 3339", context.time))?;
+    Ok(context)
+}
+
+/// Evaluates one case. `Ok(Err(reason))` means the case can't be evaluated
+/// any more, e.g. its subject user was deleted; it then counts as failed.
+async fn evaluate_case(pool: &PgPool, policy_id: Uuid, case: &PolicyTestCase) -> sqlx::Result<Result<Decision, String>> {
+    let (user_id, role_id) = if case.subject_type == "user" {
+        match user_service::get_user(pool, case.subject_id).await {
+            Ok(user) => (Some(user.id), user.role_id),
+            Err(sqlx::Error::RowNotFound) => return Ok(Err("Subject user no longer exists".into())),
+            Err(e) => return Err(e),
+        }
+    } else {
+        (None, Some(case.subject_id))
+    };
+
+    let context = match &case.context {
+        Some(value) => match parse_context(value) {
+            Ok(context) => context,
+            Err(reason) => return Ok(Err(reason)),
+        },
+        None => AuthContext {
+            department: None,
+            location: None,
+            time: chrono::Utc::now().to_rfc3339(),
+            resource_owner_id: None,
+            attributes: Default::default(),
+        },
+    };
+
+    let rules = auth_service::fetch_rules(pool, user_id, role_id, Some(policy_id)).await?;
+    // A case naming a record owner is checked like a single-record request
+    let decision = match (user_id, context.resource_owner_id) {
+        (Some(user_id), Some(owner_id)) => {
+            let record = auth_service::record_scope(pool, &rules, user_id, Some(owner_id)).await?;
+            auth_service::evaluate_record(&rules, &case.action, &case.resource, &context, &record)
+        }
+        _ => auth_service::evaluate(&rules, &case.action, &case.resource, &context),
+    };
+    Ok(Ok(decision))
+}
+
+/// Runs every test case of the policy with `authorize` semantics, treating the
+/// policy as active even if it is still a draft. A case that can't be evaluated
+/// fails with `actual_effect` "error".
+pub async fn run_suite(pool: &PgPool, policy_id: Uuid) -> sqlx::Result<PolicyTestSuiteResult> {
+    let cases = list_test_cases(pool, policy_id).await?;
+    let mut results = Vec::with_capacity(cases.len());
+
+    for case in cases {
+        let (actual_effect, reason, decided_by) = match evaluate_case(pool, policy_id, &case).await? {
+            Ok(decision) => {
+                let effect = if decision.allowed { "allow" } else { "deny" };
+                (effect, decision.reason, decision.policy_id)
+            }
+            Err(reason) => ("error", reason, None),
+        };
+
+        results.push(PolicyTestCaseResult {
+            test_case_id: case.id,
+            name: case.name,
+            passed: actual_effect == case.expected_effect,
+            expected_effect: case.expected_effect,
+            actual_effect: actual_effect.to_string(),
+            reason,
+            policy_id: decided_by,
+        });
+    }
+
+    let failed = results.iter().filter(|r| !r.passed).count();
+    Ok(PolicyTestSuiteResult {
+        policy_id,
+        passed: failed == 0,
+        total: results.len(),
+        failed,
+        results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_stored_context() {
+        let context = parse_context(&serde_json::json!({
+            "department": "Ops",
+            "location": null,
+            "time": "2026-03-02T09:00:00+01:00",
+            "resource_owner_id": null,
+            "attributes": { "request.ip": "10.0.0.1" }
+        }))
+        .unwrap();
+
+        assert_eq!(context.department.as_deref(), Some("Ops"));
+        assert_eq!(context.attributes["request.ip"], "10.0.0.1");
+    }
+
+    #[test]
+    fn rejects_malformed_contexts() {
+        assert!(parse_context(&serde_json::json!({ "department": 3 })).is_err());
+        assert!(parse_context(&serde_json::json!("Ops")).is_err());
+
+        let err = parse_context(&serde_json::json!({
+            "department": null,
+            "location": null,
+            "time": "yesterday",
+            "resource_owner_id": null
+        }))
+        .unwrap_err();
+        assert_eq!(err, "Invalid test context: time 'yesterday' is not RFC 3339");
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::models::record_share::{CreateRecordSharePayload, RecordAccessEntry, RecordShare};
+use crate::models::row_filter::RecordScope;
+use crate::models::user_role::{Decision, PolicyRule};
+use crate::services::{auth_service, org_service};
+use crate::utils::auth::request_context;
+
+/// Resources whose individual records can be shared, with the table holding them.
+pub const SHAREABLE_RESOURCES: &[(&str, &str)] = &[
+    ("report", "reports"),
+];
+
+/// Actions a share can grant on a record. Shares let someone look at a record,
+/// never change it.
+pub const SHAREABLE_ACTIONS: &[&str] = &["read"];
+
+const SHARE_COLUMNS: &str = "id, resource, resource_id, grantee_id, action, expires_at, granted_by, created_at, revoked_at";
+
+pub fn is_shareable(resource: &str) -> bool {
+    SHAREABLE_RESOURCES.iter().any(|(name, _)| *name == resource)
+}
+
+/// The user who owns the record. `RowNotFound` if the record doesn't exist.
+pub async fn get_owner(pool: &PgPool, resource: &str, resource_id: Uuid) -> sqlx::Result<Uuid> {
+    let Some((_, table)) = SHAREABLE_RESOURCES.iter().find(|(name, _)| *name == resource) else {
+        return Err(sqlx::Error::Protocol(format!("Records of '{}' cannot be shared", resource)));
+    };
+
+    sqlx::query_scalar::<_, Uuid>(&format!("SELECT user_id FROM {} WHERE id = $1", table))
+        .bind(resource_id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn create_share(
+    pool: &PgPool,
+    resource: &str,
+    resource_id: Uuid,
+    payload: &CreateRecordSharePayload,
+    granted_by: Uuid,
+) -> sqlx::Result<RecordShare> {
+    if !SHAREABLE_ACTIONS.contains(&payload.action.as_str()) {
+        return Err(sqlx::Error::Protocol(format!("Action '{}' cannot be shared", payload.action)));
+    }
+    if payload.expires_at.is_some_and(|t| t <= chrono::Utc::now().naive_utc()) {
+        return Err(sqlx::Error::Protocol("Share expires in the past".into()));
+    }
+
+    let owner_id = get_owner(pool, resource, resource_id).await?;
+    if payload.grantee_id == owner_id {
+        return Err(sqlx::Error::Protocol("The owner already has access".into()));
+    }
+
+    sqlx::query_as::<_, RecordShare>(&format!(
+        r#"
+        INSERT INTO record_shares (resource, resource_id, grantee_id, action, expires_at, granted_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING {}
+        "#,
+        SHARE_COLUMNS
+    ))
+    .bind(resource)
+    .bind(resource_id)
+    .bind(payload.grantee_id)
+    .bind(&payload.action)
+    .bind(payload.expires_at)
+    .bind(granted_by)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            sqlx::Error::Protocol("Unknown grantee".into())
+        }
+        other => other,
+    })
+}
+
+/// Shares on the record that are neither revoked nor expired.
+pub async fn list_shares(pool: &PgPool, resource: &str, resource_id: Uuid) -> sqlx::Result<Vec<RecordShare>> {
+    sqlx::query_as::<_, RecordShare>(&format!(
+        r#"
+        SELECT {}
+        FROM record_shares
+        WHERE resource = $1 AND resource_id = $2
+        AND revoked_at IS NULL
+        AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+        ORDER BY created_at ASC
+        "#,
+        SHARE_COLUMNS
+    ))
+    .bind(resource)
+    .bind(resource_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_share(pool: &PgPool, id: Uuid) -> sqlx::Result<RecordShare> {
+    sqlx::query_as::<_, RecordShare>(&format!("SELECT {} FROM record_shares WHERE id = $1", SHARE_COLUMNS))
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn revoke_share(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        "UPDATE record_shares SET revoked_at = CURRENT_TIMESTAMP WHERE id = $1 AND revoked_at IS NULL"
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// The active share giving `grantee_id` the action on this record, if any.
+pub async fn find_active_share(
+    pool: &PgPool,
+    grantee_id: Uuid,
+    resource: &str,
+    resource_id: Uuid,
+    action: &str,
+) -> sqlx::Result<Option<RecordShare>> {
+    if !is_shareable(resource) || !SHAREABLE_ACTIONS.contains(&action) {
+        return Ok(None);
+    }
+
+    sqlx::query_as::<_, RecordShare>(&format!(
+        r#"
+        SELECT {}
+        FROM record_shares
+        WHERE grantee_id = $1 AND resource = $2 AND resource_id = $3 AND action = $4
+        AND revoked_at IS NULL
+        AND (expires_at IS NULL OR expires_at > CURRENT_TIMESTAMP)
+        LIMIT 1
+        "#,
+        SHARE_COLUMNS
+    ))
+    .bind(grantee_id)
+    .bind(resource)
+    .bind(resource_id)
+    .bind(action)
+    .fetch_optional(pool)
+    .await
+}
+
+#[derive(FromRow)]
+struct AccessSubject {
+    id: Uuid,
+    username: String,
+    status: String,
+    department: Option<String>,
+    location: Option<String>,
+}
+
+/// Everyone who can act on the record: its owner, active shares, and users
+/// whose policies grant the action. Policies are evaluated as `authorize_record`
+/// does, with each user's department and location as context; conditions on
+/// other request attributes, such as the IP, don't hold here.
+pub async fn list_access(pool: &PgPool, resource: &str, resource_id: Uuid) -> sqlx::Result<Vec<RecordAccessEntry>> {
+    let owner_id = get_owner(pool, resource, resource_id).await?;
+    let shares = list_shares(pool, resource, resource_id).await?;
+
+    let mut rules_by_user: HashMap<Uuid, Vec<PolicyRule>> = HashMap::new();
+    for (user_id, rule) in auth_service::fetch_rules_for_resource(pool, resource).await? {
+        rules_by_user.entry(user_id).or_default().push(rule);
+    }
+
+    // Everyone named: subjects, the delegators behind their rules, grantees and the owner
+    let mut ids: Vec<Uuid> = rules_by_user.keys().copied().collect();
+    ids.extend(rules_by_user.values().flatten().filter_map(|rule| rule.delegator_id));
+    ids.extend(shares.iter().map(|share| share.grantee_id));
+    ids.push(owner_id);
+    ids.sort();
+    ids.dedup();
+
+    let mut subjects = sqlx::query_as::<_, AccessSubject>(
+        "SELECT id, username, user_status(id) AS status, department, location FROM users WHERE id = ANY($1)"
+    )
+    .bind(&ids)
+    .fetch_all(pool)
+    .await?;
+    subjects.sort_by(|a, b| a.username.cmp(&b.username));
+    let username = |id: Uuid| {
+        subjects
+            .iter()
+            .find(|u| u.id == id)
+            .map(|u| u.username.clone())
+            .unwrap_or_default()
+    };
+
+    let mut scope = RecordScope {
+        subject_id: owner_id,
+        owner_id: Some(owner_id),
+        owner_department: subjects.iter().find(|u| u.id == owner_id).and_then(|u| u.department.clone()),
+        managers: org_service::get_chain(pool, owner_id).await?.into_iter().map(|m| m.id).collect(),
+        departments: subjects
+            .iter()
+            .filter_map(|u| Some((u.id, u.department.clone()?)))
+            .collect(),
+    };
+
+    // Inactive users are denied everything, shares included
+    let mut decisions: Vec<(&AccessSubject, &str, Decision)> = Vec::new();
+    for subject in subjects.iter().filter(|u| u.status == "active" && u.id != owner_id) {
+        let rules = rules_by_user.get(&subject.id).map(Vec::as_slice).unwrap_or_default();
+        let mut context = request_context();
+        context.resource_owner_id = Some(owner_id);
+        context.department = subject.department.clone();
+        context.location = subject.location.clone();
+        scope.subject_id = subject.id;
+        for action in SHAREABLE_ACTIONS {
+            let decision = auth_service::evaluate_record(rules, action, resource, &context, &scope);
+            decisions.push((subject, action, decision));
+        }
+    }
+    let decision = |user_id: Uuid, action: &str| {
+        decisions
+            .iter()
+            .find(|(subject, a, _)| subject.id == user_id && *a == action)
+            .map(|(_, _, decision)| decision)
+    };
+
+    let mut entries = vec![RecordAccessEntry {
+        user_id: owner_id,
+        username: username(owner_id),
+        action: "*".to_string(),
+        source: "owner".to_string(),
+        share_id: None,
+        policy_id: None,
+        expires_at: None,
+    }];
+
+    // A share only counts where no policy explicitly denies the action
+    for share in shares {
+        let effective = decision(share.grantee_id, &share.action).is_some_and(|d| d.allowed || d.policy_id.is_none());
+        if !effective {
+            continue;
+        }
+        entries.push(RecordAccessEntry {
+            user_id: share.grantee_id,
+            username: username(share.grantee_id),
+            action: share.action,
+            source: "share".to_string(),
+            share_id: Some(share.id),
+            policy_id: None,
+            expires_at: share.expires_at,
+        });
+    }
+
+    for (subject, action, decision) in decisions.iter().filter(|(_, _, d)| d.allowed) {
+        entries.push(RecordAccessEntry {
+            user_id: subject.id,
+            username: subject.username.clone(),
+            action: action.to_string(),
+            source: "policy".to_string(),
+            share_id: None,
+            policy_id: decision.policy_id,
+            expires_at: None,
+        });
+    }
+
+    Ok(entries)
+}
@@ -1,7 +1,8 @@
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 use crate::models::report::{Report, CreateReportPayload};
+use crate::models::row_filter::RowFilter;
 
 pub async fn create_report(
     pool: &PgPool,
@@ -42,16 +43,20 @@ pub async fn list_reports_for_user(
     .await
 }
 
-pub async fn list_all_reports(pool: &PgPool) -> sqlx::Result<Vec<Report>> {
-    sqlx::query_as::<_, Report>(
+/// Reports visible through `filter`, as produced by partial evaluation.
+pub async fn list_all_reports(pool: &PgPool, filter: &RowFilter) -> sqlx::Result<Vec<Report>> {
+    let mut qb = QueryBuilder::<Postgres>::new(
         r#"
         SELECT id, user_id, title, content, attachment_path, status, created_at
         FROM reports
-        ORDER BY created_at DESC
-        "#
-    )
-    .fetch_all(pool)
-    .await
+        WHERE "#
+    );
+    filter.push_sql(&mut qb, "user_id");
+    qb.push(" ORDER BY created_at DESC");
+
+    qb.build_query_as::<Report>()
+        .fetch_all(pool)
+        .await
 }
 
 pub async fn get_report(pool: &PgPool, id: Uuid) -> sqlx::Result<Report> {
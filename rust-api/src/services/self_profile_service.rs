@@ -0,0 +1,355 @@
+use chrono::{Duration, Utc};
+use sqlx::types::Json;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::avatar::avatar_urls;
+use crate::models::self_profile::{
+    EmergencyContact, PendingEmailChange, SelfProfile, SelfProfileRow, UpdateSelfProfilePayload,
+};
+use crate::services::employee_profile_service::{clean, clean_phone};
+use crate::services::invite_service::generate_token;
+use crate::services::mail_service::OutgoingMail;
+use crate::services::notification_service;
+use crate::services::user_import_service::valid_email;
+
+/// Every field users could be allowed to change themselves. `SELF_EDITABLE_FIELDS`
+/// picks the ones a deployment allows.
+pub const SELF_SERVICE_FIELDS: &[&str] =
+    &["display_name", "phone", "avatar", "emergency_contacts", "notification_preferences", "email"];
+
+/// How long the link confirming a new email address works
+pub const EMAIL_CHANGE_TTL_HOURS: i64 = 24;
+
+const MAX_EMERGENCY_CONTACTS: usize = 5;
+const MAX_TEXT_LEN: usize = 100;
+const MAX_EMAIL_LEN: usize = 100;
+
+/// Reads `SELF_EDITABLE_FIELDS` (comma-separated). Unset means every field in
+/// `SELF_SERVICE_FIELDS`; unknown names are logged and ignored.
+pub fn editable_fields_from_env() -> Vec<String> {
+    let Ok(raw) = std::env::var("SELF_EDITABLE_FIELDS") else {
+        return SELF_SERVICE_FIELDS.iter().map(|f| f.to_string()).collect();
+    };
+    raw.split(',')
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+        .filter(|f| {
+            let known = SELF_SERVICE_FIELDS.contains(f);
+            if !known {
+                eprintln!("Ignoring unknown self-editable field '{}'", f);
+            }
+            known
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+pub async fn get_profile(pool: &PgPool, user_id: Uuid, editable_fields: &[String]) -> sqlx::Result<SelfProfile> {
+    let row = sqlx::query_as::<_, SelfProfileRow>(
+        r#"
+        SELECT id AS user_id, username, email, display_name, phone, emergency_contacts, notification_preferences, avatar_version
+        FROM users
+        WHERE id = $1
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let pending_email = sqlx::query_as::<_, PendingEmailChange>(
+        r#"
+        SELECT new_email, expires_at, created_at
+        FROM email_change_requests
+        WHERE user_id = $1
+        AND confirmed_at IS NULL AND cancelled_at IS NULL
+        AND expires_at > CURRENT_TIMESTAMP
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(SelfProfile {
+        user_id: row.user_id,
+        username: row.username,
+        email: row.email,
+        display_name: row.display_name,
+        phone: row.phone,
+        avatar_urls: row.avatar_version.as_deref().map(|v| avatar_urls(row.user_id, v)),
+        emergency_contacts: row.emergency_contacts.0,
+        notification_preferences: row.notification_preferences.0,
+        pending_email,
+        editable_fields: editable_fields.to_vec(),
+    })
+}
+
+fn clean_contacts(contacts: &[EmergencyContact]) -> sqlx::Result<Vec<EmergencyContact>> {
+    if contacts.len() > MAX_EMERGENCY_CONTACTS {
+        return Err(sqlx::Error::Protocol(format!(
+            "At most {} emergency contacts are allowed",
+            MAX_EMERGENCY_CONTACTS
+        )));
+    }
+    contacts
+        .iter()
+        .map(|c| {
+            let name = clean("Contact name", &Some(c.name.clone()), MAX_TEXT_LEN)?
+                .ok_or_else(|| sqlx::Error::Protocol("Contact name is required".into()))?;
+            let phone = clean_phone("Contact phone", &Some(c.phone.clone()))?
+                .ok_or_else(|| sqlx::Error::Protocol("Contact phone is required".into()))?;
+            let relationship = clean("Relationship", &c.relationship, MAX_TEXT_LEN)?;
+            Ok(EmergencyContact { name, relationship, phone })
+        })
+        .collect()
+}
+
+/// Applies the fields present in the payload. The caller checks they are
+/// editable. Display name, phone and emergency contacts changes are recorded
+/// in the profile history. Invalid values come back as `sqlx::Error::Protocol`.
+pub async fn update_profile(pool: &PgPool, user_id: Uuid, payload: &UpdateSelfProfilePayload) -> sqlx::Result<()> {
+    // Outer `None`: leave unchanged; inner `None`: clear
+    let display_name = payload
+        .display_name
+        .is_some()
+        .then(|| clean("Display name", &payload.display_name, MAX_TEXT_LEN))
+        .transpose()?;
+    let phone = payload.phone.is_some().then(|| clean_phone("Phone", &payload.phone)).transpose()?;
+    let contacts = payload.emergency_contacts.as_deref().map(clean_contacts).transpose()?;
+    if let Some(preferences) = &payload.notification_preferences
+        && let Some(unknown) = preferences.keys().find(|k| !notification_service::is_topic(k))
+    {
+        return Err(sqlx::Error::Protocol(format!("Unknown notification topic '{}'", unknown)));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let before = sqlx::query_as::<_, SelfProfileRow>(
+        r#"
+        SELECT id AS user_id, username, email, display_name, phone, emergency_contacts, notification_preferences, avatar_version
+        FROM users
+        WHERE id = $1
+        FOR UPDATE
+        "#
+    )
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let mut preferences = before.notification_preferences.0.clone();
+    if let Some(changes) = &payload.notification_preferences {
+        preferences.extend(changes.iter().map(|(k, v)| (k.clone(), *v)));
+    }
+    let new_display_name = display_name.unwrap_or_else(|| before.display_name.clone());
+    let new_phone = phone.unwrap_or_else(|| before.phone.clone());
+    let new_contacts = contacts.unwrap_or_else(|| before.emergency_contacts.0.clone());
+
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET display_name = $2, phone = $3, emergency_contacts = $4, notification_preferences = $5,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        "#
+    )
+    .bind(user_id)
+    .bind(&new_display_name)
+    .bind(&new_phone)
+    .bind(Json(&new_contacts))
+    .bind(Json(&preferences))
+    .execute(&mut *tx)
+    .await?;
+
+    let contacts_text = |contacts: &Vec<EmergencyContact>| {
+        (!contacts.is_empty()).then(|| serde_json::to_string(contacts).unwrap_or_default())
+    };
+    let changes = [
+        ("display_name", before.display_name.clone(), new_display_name),
+        ("phone", before.phone.clone(), new_phone),
+        ("emergency_contacts", contacts_text(&before.emergency_contacts.0), contacts_text(&new_contacts)),
+    ];
+    for (field, old_value, new_value) in changes {
+        if old_value == new_value {
+            continue;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO employee_profile_changes (user_id, field, old_value, new_value, changed_by)
+            VALUES ($1, $2, $3, $4, $1)
+            "#
+        )
+        .bind(user_id)
+        .bind(field)
+        .bind(old_value)
+        .bind(new_value)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await
+}
+
+/// Starts an email change: earlier pending changes are cancelled and a new one
+/// is stored with its confirmation token. The address only changes once the
+/// token comes back, and only one account at a time can be waiting for a given
+/// address. Returns the pending change and the token.
+pub async fn request_email_change(
+    pool: &PgPool,
+    user_id: Uuid,
+    new_email: &str,
+) -> sqlx::Result<(PendingEmailChange, String)> {
+    let new_email = new_email.trim();
+    if !valid_email(new_email) || new_email.chars().count() > MAX_EMAIL_LEN {
+        return Err(sqlx::Error::Protocol("Email is not a valid address".into()));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let taken = sqlx::query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM users WHERE LOWER(email) = LOWER($1))")
+        .bind(new_email)
+        .fetch_one(&mut *tx)
+        .await?;
+    if taken {
+        return Err(sqlx::Error::Protocol("Email is already registered".into()));
+    }
+
+    // The user's own earlier request and expired ones for the address make way;
+    // a live request by someone else keeps it
+    sqlx::query(
+        r#"
+        UPDATE email_change_requests SET cancelled_at = CURRENT_TIMESTAMP
+        WHERE confirmed_at IS NULL AND cancelled_at IS NULL
+        AND (user_id = $1 OR (LOWER(new_email) = LOWER($2) AND expires_at <= CURRENT_TIMESTAMP))
+        "#
+    )
+    .bind(user_id)
+    .bind(new_email)
+    .execute(&mut *tx)
+    .await?;
+
+    let token = generate_token();
+    let pending = sqlx::query_as::<_, PendingEmailChange>(
+        r#"
+        INSERT INTO email_change_requests (user_id, new_email, token, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING new_email, expires_at, created_at
+        "#
+    )
+    .bind(user_id)
+    .bind(new_email)
+    .bind(&token)
+    .bind(Utc::now().naive_utc() + Duration::hours(EMAIL_CHANGE_TTL_HOURS))
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_unique_violation() => {
+            sqlx::Error::Protocol("Email is awaiting confirmation by another account".into())
+        }
+        other => other,
+    })?;
+
+    tx.commit().await?;
+    Ok((pending, token))
+}
+
+/// Cancels the user's pending email change. Returns how many there were.
+pub async fn cancel_email_change(pool: &PgPool, user_id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        UPDATE email_change_requests SET cancelled_at = CURRENT_TIMESTAMP
+        WHERE user_id = $1 AND confirmed_at IS NULL AND cancelled_at IS NULL
+        "#
+    )
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Applies the pending change the token belongs to; it must be the user's own.
+/// Returns the old and new address. `RowNotFound` if the token is unknown,
+/// used, cancelled or expired.
+pub async fn confirm_email_change(pool: &PgPool, user_id: Uuid, token: &str) -> sqlx::Result<(String, String)> {
+    let mut tx = pool.begin().await?;
+
+    let (request_id, new_email) = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        SELECT id, new_email
+        FROM email_change_requests
+        WHERE token = $1 AND user_id = $2
+        AND confirmed_at IS NULL AND cancelled_at IS NULL
+        AND expires_at > CURRENT_TIMESTAMP
+        FOR UPDATE
+        "#
+    )
+    .bind(token)
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(sqlx::Error::RowNotFound)?;
+
+    let old_email = sqlx::query_scalar::<_, String>("SELECT email FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+    sqlx::query("UPDATE users SET email = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+        .bind(user_id)
+        .bind(&new_email)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => {
+                sqlx::Error::Protocol("Email is already registered".into())
+            }
+            other => other,
+        })?;
+    sqlx::query("UPDATE email_change_requests SET confirmed_at = CURRENT_TIMESTAMP WHERE id = $1")
+        .bind(request_id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO employee_profile_changes (user_id, field, old_value, new_value, changed_by)
+        VALUES ($1, 'email', $2, $3, $1)
+        "#
+    )
+    .bind(user_id)
+    .bind(&old_email)
+    .bind(&new_email)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok((old_email, new_email))
+}
+
+/// Sent to the new address; the link carries the confirmation token.
+pub fn confirm_email_mail(base_url: &str, username: &str, pending: &PendingEmailChange, token: &str) -> OutgoingMail {
+    OutgoingMail {
+        to: pending.new_email.clone(),
+        subject: "Confirm your new email address".to_string(),
+        body: format!(
+            "Hello {},\n\nConfirm this address for your account while signed in:\n\n{}/verify-email/{}\n\nThe link expires on {} UTC. If you didn't ask for this, ignore this mail.\n",
+            username,
+            base_url.trim_end_matches('/'),
+            token,
+            pending.expires_at.format("%Y-%m-%d %H:%M")
+        ),
+    }
+}
+
+/// Sent to the current address so the owner notices a change they didn't make.
+pub fn email_change_notice(username: &str, old_email: &str, new_email: &str, confirmed: bool) -> OutgoingMail {
+    let what = if confirmed { "has been changed" } else { "is about to change" };
+    OutgoingMail {
+        to: old_email.to_string(),
+        subject: "Your account email address".to_string(),
+        body: format!(
+            "Hello {},\n\nThe email address of your account {} to {}.\nIf this wasn't you, contact your administrator.\n",
+            username, what, new_email
+        ),
+    }
+}
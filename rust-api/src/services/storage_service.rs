@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+/// Stores binary objects such as avatar images under slash-separated keys.
+/// `AppState` holds one; swap in an object-store implementation without
+/// touching the callers.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    /// Used when logging storage failures.
+    fn name(&self) -> &'static str;
+
+    async fn put(&self, key: &str, bytes: &[u8]) -> Result<(), String>;
+
+    /// `None` when nothing is stored under the key.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String>;
+
+    /// Removes the object at `prefix` and every object below it.
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), String>;
+}
+
+/// Keeps objects as files below `root`, one directory level per key segment.
+pub struct LocalDiskStorage {
+    pub root: PathBuf,
+}
+
+impl LocalDiskStorage {
+    /// Keys are built by the API itself, but are still checked so they can't
+    /// leave `root`.
+    fn path(&self, key: &str) -> Result<PathBuf, String> {
+        let safe = !key.is_empty()
+            && key.split('/').all(|segment| {
+                !segment.is_empty()
+                    && segment != "."
+                    && segment != ".."
+                    && segment.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c))
+            });
+        if !safe {
+            return Err(format!("Invalid storage key '{}'", key));
+        }
+        Ok(self.root.join(Path::new(key)))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalDiskStorage {
+    fn name(&self) -> &'static str {
+        "local-disk"
+    }
+
+    async fn put(&self, key: &str, bytes: &[u8]) -> Result<(), String> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(|e| e.to_string())?;
+        }
+        // Write then rename so readers never see a partial file
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, bytes).await.map_err(|e| e.to_string())?;
+        tokio::fs::rename(&partial, &path).await.map_err(|e| e.to_string())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<(), String> {
+        let path = self.path(prefix.trim_end_matches('/'))?;
+        let result = match tokio::fs::metadata(&path).await {
+            Ok(meta) if meta.is_dir() => tokio::fs::remove_dir_all(&path).await,
+            Ok(_) => tokio::fs::remove_file(&path).await,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
+
+/// Reads the local storage directory from `STORAGE_DIR`, defaulting to `./storage`.
+pub fn local_storage_from_env() -> LocalDiskStorage {
+    let root = std::env::var("STORAGE_DIR").unwrap_or_else(|_| "./storage".to_string());
+    LocalDiskStorage { root: PathBuf::from(root) }
+}
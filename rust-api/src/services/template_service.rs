@@ -49,6 +49,7 @@ pub async fn get_template(pool: &PgPool, id: Uuid) -> sqlx::Result<PayslipTempla
     .await
 }
 
+#[allow(dead_code)]
 pub async fn get_active_template(pool: &PgPool) -> sqlx::Result<Option<PayslipTemplate>> {
     sqlx::query_as::<_, PayslipTemplate>(
         r#"
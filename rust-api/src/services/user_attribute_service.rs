@@ -0,0 +1,381 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDate;
+use serde_json::{Map, Number, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::user_attribute::{
+    CreateUserAttributeDefinitionPayload, UpdateUserAttributeDefinitionPayload, UserAttributeDefinition,
+};
+
+pub const DATA_TYPES: &[&str] = &["string", "number", "date", "enum"];
+
+/// Applied to string attributes that don't set their own `max_length`
+const DEFAULT_MAX_LENGTH: usize = 255;
+const MAX_NAME_LEN: usize = 40;
+const MAX_LABEL_LEN: usize = 100;
+
+const DEFINITION_COLUMNS: &str =
+    "id, name, label, data_type, required, max_length, min_value, max_value, options, created_at, updated_at";
+
+pub async fn list_definitions(pool: &PgPool) -> sqlx::Result<Vec<UserAttributeDefinition>> {
+    sqlx::query_as::<_, UserAttributeDefinition>(&format!(
+        "SELECT {} FROM user_attribute_definitions ORDER BY name",
+        DEFINITION_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await
+}
+
+fn valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    name.len() <= MAX_NAME_LEN
+        && chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Checks the label and that only the validation settings of `data_type` are set.
+/// Returns the trimmed label and the cleaned enum options.
+fn check_definition(
+    data_type: &str,
+    label: &str,
+    max_length: Option<i32>,
+    min_value: Option<f64>,
+    max_value: Option<f64>,
+    options: &Option<Vec<String>>,
+) -> sqlx::Result<(String, Option<Vec<String>>)> {
+    let invalid = |msg: String| Err(sqlx::Error::Protocol(msg));
+
+    let label = label.trim();
+    if label.is_empty() || label.chars().count() > MAX_LABEL_LEN {
+        return invalid(format!("Label must be 1 to {} characters", MAX_LABEL_LEN));
+    }
+    if max_length.is_some() && data_type != "string" {
+        return invalid("max_length only applies to string attributes".into());
+    }
+    if max_length.is_some_and(|n| n < 1) {
+        return invalid("max_length must be positive".into());
+    }
+    if (min_value.is_some() || max_value.is_some()) && data_type != "number" {
+        return invalid("min_value and max_value only apply to number attributes".into());
+    }
+    if let (Some(min), Some(max)) = (min_value, max_value)
+        && min > max
+    {
+        return invalid("min_value is greater than max_value".into());
+    }
+
+    let options = match (data_type, options) {
+        ("enum", Some(options)) => {
+            let cleaned: Vec<String> = options.iter().map(|o| o.trim().to_string()).collect();
+            let distinct: HashSet<&String> = cleaned.iter().collect();
+            if cleaned.is_empty() || cleaned.iter().any(String::is_empty) || distinct.len() != cleaned.len() {
+                return invalid("Enum options must be distinct and non-empty".into());
+            }
+            Some(cleaned)
+        }
+        ("enum", None) => return invalid("Enum attributes need options".into()),
+        (_, Some(_)) => return invalid("Options only apply to enum attributes".into()),
+        (_, None) => None,
+    };
+
+    Ok((label.to_string(), options))
+}
+
+/// A name that is already taken comes back as the unique violation.
+pub async fn create_definition(
+    pool: &PgPool,
+    payload: &CreateUserAttributeDefinitionPayload,
+) -> sqlx::Result<UserAttributeDefinition> {
+    if !valid_name(&payload.name) {
+        return Err(sqlx::Error::Protocol(format!(
+            "Name must start with a lowercase letter, use only a-z, 0-9 and _, and be at most {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    if !DATA_TYPES.contains(&payload.data_type.as_str()) {
+        return Err(sqlx::Error::Protocol(format!("Unknown data type '{}'", payload.data_type)));
+    }
+    let (label, options) = check_definition(
+        &payload.data_type,
+        &payload.label,
+        payload.max_length,
+        payload.min_value,
+        payload.max_value,
+        &payload.options,
+    )?;
+
+    sqlx::query_as::<_, UserAttributeDefinition>(&format!(
+        r#"
+        INSERT INTO user_attribute_definitions (name, label, data_type, required, max_length, min_value, max_value, options)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING {}
+        "#,
+        DEFINITION_COLUMNS
+    ))
+    .bind(&payload.name)
+    .bind(label)
+    .bind(&payload.data_type)
+    .bind(payload.required)
+    .bind(payload.max_length)
+    .bind(payload.min_value)
+    .bind(payload.max_value)
+    .bind(options)
+    .fetch_one(pool)
+    .await
+}
+
+/// Name and data type stay as they are. Refused when values already stored
+/// would fail the new validation.
+pub async fn update_definition(
+    pool: &PgPool,
+    id: Uuid,
+    payload: &UpdateUserAttributeDefinitionPayload,
+) -> sqlx::Result<UserAttributeDefinition> {
+    let mut tx = pool.begin().await?;
+
+    let current = sqlx::query_as::<_, UserAttributeDefinition>(&format!(
+        "SELECT {} FROM user_attribute_definitions WHERE id = $1 FOR UPDATE",
+        DEFINITION_COLUMNS
+    ))
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let (label, options) = check_definition(
+        &current.data_type,
+        &payload.label,
+        payload.max_length,
+        payload.min_value,
+        payload.max_value,
+        &payload.options,
+    )?;
+    let updated = UserAttributeDefinition {
+        label,
+        required: payload.required,
+        max_length: payload.max_length,
+        min_value: payload.min_value,
+        max_value: payload.max_value,
+        options,
+        ..current
+    };
+
+    let stored = sqlx::query_scalar::<_, Value>("SELECT attributes -> $1 FROM users WHERE attributes ? $1")
+        .bind(&updated.name)
+        .fetch_all(&mut *tx)
+        .await?;
+    let invalid = stored.iter().filter(|v| validate_value(&updated, v).is_err()).count();
+    if invalid > 0 {
+        return Err(sqlx::Error::Protocol(format!(
+            "{} user(s) have values that the new validation would reject",
+            invalid
+        )));
+    }
+
+    let definition = sqlx::query_as::<_, UserAttributeDefinition>(&format!(
+        r#"
+        UPDATE user_attribute_definitions
+        SET label = $2, required = $3, max_length = $4, min_value = $5, max_value = $6, options = $7,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        DEFINITION_COLUMNS
+    ))
+    .bind(id)
+    .bind(&updated.label)
+    .bind(updated.required)
+    .bind(updated.max_length)
+    .bind(updated.min_value)
+    .bind(updated.max_value)
+    .bind(&updated.options)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(definition)
+}
+
+/// Removes the definition and every user's value for it.
+pub async fn delete_definition(pool: &PgPool, id: Uuid) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let name = sqlx::query_scalar::<_, String>("DELETE FROM user_attribute_definitions WHERE id = $1 RETURNING name")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await?;
+    sqlx::query("UPDATE users SET attributes = attributes - $1 WHERE attributes ? $1")
+        .bind(&name)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+/// Checks a value against its definition and returns it in stored form: numbers
+/// as JSON numbers, dates as "YYYY-MM-DD" strings, everything else as strings.
+pub fn validate_value(definition: &UserAttributeDefinition, value: &Value) -> Result<Value, String> {
+    let label = &definition.label;
+    match (definition.data_type.as_str(), value) {
+        ("string", Value::String(s)) => {
+            let max = definition.max_length.map(|n| n as usize).unwrap_or(DEFAULT_MAX_LENGTH);
+            if s.chars().count() > max {
+                return Err(format!("{} is longer than {} characters", label, max));
+            }
+            Ok(value.clone())
+        }
+        ("number", Value::Number(n)) => {
+            let v = n.as_f64().unwrap_or(f64::NAN);
+            if definition.min_value.is_some_and(|min| v < min) || definition.max_value.is_some_and(|max| v > max) {
+                return Err(format!(
+                    "{} must be between {} and {}",
+                    label,
+                    definition.min_value.map(|v| v.to_string()).unwrap_or("-∞".into()),
+                    definition.max_value.map(|v| v.to_string()).unwrap_or("∞".into())
+                ));
+            }
+            Ok(value.clone())
+        }
+        ("date", Value::String(s)) => NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(|d| Value::String(d.to_string()))
+            .map_err(|_| format!("{} must be a date (YYYY-MM-DD)", label)),
+        ("enum", Value::String(s)) => {
+            if definition.options.as_ref().is_some_and(|o| o.contains(s)) {
+                Ok(value.clone())
+            } else {
+                Err(format!("{} must be one of: {}", label, definition.options.clone().unwrap_or_default().join(", ")))
+            }
+        }
+        ("number", _) => Err(format!("{} must be a number", label)),
+        _ => Err(format!("{} must be a string", label)),
+    }
+}
+
+/// Parses a query-string value the way `validate_value` would store it.
+fn parse_text(definition: &UserAttributeDefinition, text: &str) -> Result<Value, String> {
+    let value = match definition.data_type.as_str() {
+        "number" => text
+            .trim()
+            .parse::<Number>()
+            .map(Value::Number)
+            .map_err(|_| format!("{} must be a number", definition.label))?,
+        _ => Value::String(text.to_string()),
+    };
+    validate_value(definition, &value)
+}
+
+/// Stored value as plain text, for CSV and history entries.
+pub fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// JSON object to match users against (`attributes @> filter`) for `attr.<name>=value`
+/// query parameters. `None` when there are no such parameters.
+pub async fn filter_from_query(pool: &PgPool, params: &[(String, String)]) -> sqlx::Result<Option<Value>> {
+    let wanted: Vec<(&str, &str)> = params
+        .iter()
+        .filter_map(|(key, value)| key.strip_prefix("attr.").map(|name| (name, value.as_str())))
+        .collect();
+    if wanted.is_empty() {
+        return Ok(None);
+    }
+
+    let definitions = list_definitions(pool).await?;
+    let mut filter = Map::new();
+    for (name, text) in wanted {
+        let definition = definitions
+            .iter()
+            .find(|d| d.name == name)
+            .ok_or_else(|| sqlx::Error::Protocol(format!("Unknown attribute '{}'", name)))?;
+        let value = parse_text(definition, text).map_err(sqlx::Error::Protocol)?;
+        filter.insert(name.to_string(), value);
+    }
+    Ok(Some(Value::Object(filter)))
+}
+
+pub async fn get_values(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Map<String, Value>> {
+    let attributes = sqlx::query_scalar::<_, Value>("SELECT attributes FROM users WHERE id = $1")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+    Ok(match attributes {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    })
+}
+
+/// Replaces every attribute value of the user; names left out or set to null are
+/// cleared. Each change is recorded in the profile history as `attributes.<name>`.
+/// Invalid values and missing required attributes come back as `sqlx::Error::Protocol`.
+pub async fn set_values(
+    pool: &PgPool,
+    user_id: Uuid,
+    values: &Map<String, Value>,
+    changed_by: Uuid,
+) -> sqlx::Result<Map<String, Value>> {
+    let definitions = list_definitions(pool).await?;
+
+    if let Some(unknown) = values.keys().find(|name| !definitions.iter().any(|d| &d.name == *name)) {
+        return Err(sqlx::Error::Protocol(format!("Unknown attribute '{}'", unknown)));
+    }
+    let mut cleaned = Map::new();
+    let mut errors = Vec::new();
+    for definition in &definitions {
+        match values.get(&definition.name) {
+            None | Some(Value::Null) if definition.required => errors.push(format!("{} is required", definition.label)),
+            None | Some(Value::Null) => {}
+            Some(value) => match validate_value(definition, value) {
+                Ok(value) => {
+                    cleaned.insert(definition.name.clone(), value);
+                }
+                Err(e) => errors.push(e),
+            },
+        }
+    }
+    if !errors.is_empty() {
+        return Err(sqlx::Error::Protocol(errors.join("; ")));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let before = sqlx::query_scalar::<_, Value>("SELECT attributes FROM users WHERE id = $1 FOR UPDATE")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    let before = before.as_object().cloned().unwrap_or_default();
+
+    sqlx::query("UPDATE users SET attributes = $2, updated_at = CURRENT_TIMESTAMP WHERE id = $1")
+        .bind(user_id)
+        .bind(Value::Object(cleaned.clone()))
+        .execute(&mut *tx)
+        .await?;
+
+    let names: HashSet<&String> = before.keys().chain(cleaned.keys()).collect();
+    for name in names {
+        let (old_value, new_value) = (before.get(name), cleaned.get(name));
+        if old_value == new_value {
+            continue;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO employee_profile_changes (user_id, field, old_value, new_value, changed_by)
+            VALUES ($1, $2, $3, $4, $5)
+            "#
+        )
+        .bind(user_id)
+        .bind(format!("attributes.{}", name))
+        .bind(old_value.map(value_text))
+        .bind(new_value.map(value_text))
+        .bind(changed_by)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+    Ok(cleaned)
+}
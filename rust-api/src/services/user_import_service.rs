@@ -0,0 +1,299 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::invite::UserInvite;
+use crate::models::user_import::{ImportRowResult, ImportUserRow, UserImportReport};
+use crate::services::invite_service;
+
+/// Upper bound on rows per file
+pub const MAX_IMPORT_ROWS: usize = 5000;
+
+const MAX_USERNAME_LEN: usize = 50;
+const MAX_EMAIL_LEN: usize = 100;
+const MAX_DEPARTMENT_LEN: usize = 100;
+
+/// Who a row reports to, once resolved.
+#[derive(Debug, Clone)]
+pub enum ImportManager {
+    None,
+    Existing(Uuid),
+    /// Another row of the file, by lowercased email
+    InFile(String),
+}
+
+/// A row that passed validation, with its role and manager resolved.
+#[derive(Debug, Clone)]
+pub struct ValidatedImportRow {
+    pub row: ImportUserRow,
+    pub role_id: Option<Uuid>,
+    pub manager: ImportManager,
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+pub fn valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => !local.is_empty() && domain.contains('.') && !domain.contains('@'),
+        None => false,
+    }
+}
+
+/// Parses and validates the whole file without writing anything. Every row is
+/// reported; only when no row has errors can the returned rows be committed.
+/// A file that can't be read as CSV at all comes back as `sqlx::Error::Protocol`.
+pub async fn validate_import(
+    pool: &PgPool,
+    csv_text: &str,
+    dry_run: bool,
+) -> sqlx::Result<(UserImportReport, Vec<ValidatedImportRow>)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv_text.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| sqlx::Error::Protocol(format!("Unreadable CSV header: {}", e)))?
+        .clone();
+    for required in ["username", "email"] {
+        if !headers.iter().any(|h| h == required) {
+            return Err(sqlx::Error::Protocol(format!("CSV header must include '{}'", required)));
+        }
+    }
+
+    let mut parsed: Vec<(ImportRowResult, Option<ImportUserRow>)> = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| sqlx::Error::Protocol(format!("Unreadable CSV: {}", e)))?;
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        if parsed.len() == MAX_IMPORT_ROWS {
+            return Err(sqlx::Error::Protocol(format!("At most {} rows can be imported at once", MAX_IMPORT_ROWS)));
+        }
+
+        let result = |username: &str, email: &str| ImportRowResult {
+            line,
+            username: username.to_string(),
+            email: email.to_string(),
+            invite: false,
+            errors: Vec::new(),
+        };
+        match record.deserialize::<ImportUserRow>(Some(&headers)) {
+            Ok(row) => parsed.push((result(&row.username, &row.email), Some(row))),
+            Err(e) => {
+                let mut failed = result(record.get(0).unwrap_or_default(), "");
+                failed.errors.push(format!("Malformed row: {}", e));
+                parsed.push((failed, None));
+            }
+        }
+    }
+    if parsed.is_empty() {
+        return Err(sqlx::Error::Protocol("The file has no rows".into()));
+    }
+
+    let rows: Vec<&ImportUserRow> = parsed.iter().filter_map(|(_, row)| row.as_ref()).collect();
+    let usernames: Vec<String> = rows.iter().map(|r| r.username.to_lowercase()).collect();
+    let emails: Vec<String> = rows
+        .iter()
+        .flat_map(|r| [Some(r.email.as_str()), non_empty(&r.manager_email)])
+        .flatten()
+        .map(str::to_lowercase)
+        .collect();
+
+    let roles: HashMap<String, Uuid> = sqlx::query_as::<_, (String, Uuid)>("SELECT LOWER(name), id FROM roles")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .collect();
+    let existing = sqlx::query_as::<_, (Uuid, String, String)>(
+        r#"
+        SELECT id, LOWER(username), LOWER(email)
+        FROM users
+        WHERE LOWER(username) = ANY($1) OR LOWER(email) = ANY($2)
+        "#
+    )
+    .bind(&usernames)
+    .bind(&emails)
+    .fetch_all(pool)
+    .await?;
+    let taken_usernames: HashSet<&str> = existing.iter().map(|(_, u, _)| u.as_str()).collect();
+    let existing_emails: HashMap<&str, Uuid> = existing.iter().map(|(id, _, e)| (e.as_str(), *id)).collect();
+
+    // First line each username and email appears on, to report duplicates
+    let mut seen_usernames: HashMap<String, u64> = HashMap::new();
+    let mut seen_emails: HashMap<String, u64> = HashMap::new();
+    let file_emails: HashSet<String> = rows.iter().map(|r| r.email.to_lowercase()).collect();
+
+    let mut validated = Vec::new();
+    for (result, row) in parsed.iter_mut() {
+        let Some(row) = row else { continue };
+        let errors = &mut result.errors;
+        let username = row.username.to_lowercase();
+        let email = row.email.to_lowercase();
+
+        if row.username.is_empty() {
+            errors.push("Username is required".into());
+        } else if row.username.chars().count() > MAX_USERNAME_LEN {
+            errors.push(format!("Username is longer than {} characters", MAX_USERNAME_LEN));
+        } else if let Some(first) = seen_usernames.get(&username) {
+            errors.push(format!("Username also used on line {}", first));
+        } else if taken_usernames.contains(username.as_str()) {
+            errors.push("Username is already taken".into());
+        }
+        seen_usernames.entry(username).or_insert(result.line);
+
+        if !valid_email(&row.email) {
+            errors.push("Email is not a valid address".into());
+        } else if row.email.chars().count() > MAX_EMAIL_LEN {
+            errors.push(format!("Email is longer than {} characters", MAX_EMAIL_LEN));
+        } else if let Some(first) = seen_emails.get(&email) {
+            errors.push(format!("Email also used on line {}", first));
+        } else if existing_emails.contains_key(email.as_str()) {
+            errors.push("Email is already registered".into());
+        }
+        seen_emails.entry(email.clone()).or_insert(result.line);
+
+        let role_id = match non_empty(&row.role) {
+            Some(role) => match roles.get(&role.to_lowercase()) {
+                Some(id) => Some(*id),
+                None => {
+                    errors.push(format!("Unknown role '{}'", role));
+                    None
+                }
+            },
+            None => None,
+        };
+
+        if non_empty(&row.department).is_some_and(|d| d.chars().count() > MAX_DEPARTMENT_LEN) {
+            errors.push(format!("Department is longer than {} characters", MAX_DEPARTMENT_LEN));
+        }
+
+        let manager = match non_empty(&row.manager_email).map(str::to_lowercase) {
+            None => ImportManager::None,
+            Some(manager) if manager == email => {
+                errors.push("A user can't be their own manager".into());
+                ImportManager::None
+            }
+            Some(manager) if file_emails.contains(&manager) => ImportManager::InFile(manager),
+            Some(manager) => match existing_emails.get(manager.as_str()) {
+                Some(id) => ImportManager::Existing(*id),
+                None => {
+                    errors.push(format!("Unknown manager '{}'", manager));
+                    ImportManager::None
+                }
+            },
+        };
+
+        if non_empty(&row.password).is_some_and(|p| p.chars().count() < invite_service::MIN_PASSWORD_LEN) {
+            errors.push(format!("Password is shorter than {} characters", invite_service::MIN_PASSWORD_LEN));
+        }
+
+        result.invite = non_empty(&row.password).is_none();
+        validated.push(ValidatedImportRow { row: row.clone(), role_id, manager });
+    }
+
+    // Managers named within the file must not form a loop
+    let in_file_manager: HashMap<String, String> = validated
+        .iter()
+        .filter_map(|v| match &v.manager {
+            ImportManager::InFile(manager) => Some((v.row.email.to_lowercase(), manager.clone())),
+            _ => None,
+        })
+        .collect();
+    for (result, row) in parsed.iter_mut() {
+        let Some(row) = row else { continue };
+        let start = row.email.to_lowercase();
+        let mut visited = HashSet::from([start.clone()]);
+        let mut current = &start;
+        while let Some(next) = in_file_manager.get(current) {
+            if *next == start {
+                result.errors.push("Manager chain loops back to this user".into());
+                break;
+            }
+            if !visited.insert(next.clone()) {
+                break;
+            }
+            current = next;
+        }
+    }
+
+    let invalid_rows = parsed.iter().filter(|(r, _)| !r.errors.is_empty()).count();
+    let report = UserImportReport {
+        dry_run,
+        committed: false,
+        total_rows: parsed.len(),
+        invalid_rows,
+        rows: parsed.into_iter().map(|(r, _)| r).collect(),
+    };
+    Ok((report, validated))
+}
+
+/// Creates every validated user in one transaction, then links managers named
+/// in the file. `password_hashes` is parallel to `rows`. Rows without a password
+/// are created invited, with an invite issued in the same transaction. Returns,
+/// for each row, the invite and token to send.
+pub async fn commit_import(
+    pool: &PgPool,
+    rows: &[ValidatedImportRow],
+    password_hashes: &[String],
+    imported_by: Uuid,
+) -> sqlx::Result<Vec<Option<(UserInvite, String)>>> {
+    let mut tx = pool.begin().await?;
+    let mut ids_by_email: HashMap<String, Uuid> = HashMap::new();
+    let mut invites = Vec::with_capacity(rows.len());
+
+    for (validated, password_hash) in rows.iter().zip(password_hashes) {
+        let row = &validated.row;
+        let manager_id = match validated.manager {
+            ImportManager::Existing(id) => Some(id),
+            _ => None,
+        };
+
+        let id = sqlx::query_scalar::<_, Uuid>(
+            r#"
+            INSERT INTO users (username, email, password_hash, role_id, department, manager_id)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id
+            "#
+        )
+        .bind(&row.username)
+        .bind(&row.email)
+        .bind(password_hash)
+        .bind(validated.role_id)
+        .bind(non_empty(&row.department))
+        .bind(manager_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => sqlx::Error::Protocol(format!(
+                "User '{}' was created by someone else during the import",
+                row.username
+            )),
+            other => other,
+        })?;
+
+        let invite = if non_empty(&row.password).is_none() {
+            invite_service::mark_invited(&mut tx, id, imported_by).await?;
+            Some(invite_service::issue_invite(&mut tx, id, imported_by).await?)
+        } else {
+            None
+        };
+
+        ids_by_email.insert(row.email.to_lowercase(), id);
+        invites.push(invite);
+    }
+
+    for validated in rows {
+        if let ImportManager::InFile(manager) = &validated.manager {
+            sqlx::query("UPDATE users SET manager_id = $2 WHERE id = $1")
+                .bind(ids_by_email[&validated.row.email.to_lowercase()])
+                .bind(ids_by_email[manager])
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(invites)
+}
@@ -1,51 +1,213 @@
-use sqlx::PgPool;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Row};
 use uuid::Uuid;
 
-use crate::models::user::{User, UserWithRole, CreateUserPayload, UpdateUserPayload};
+use crate::models::user::{
+    ChangeUserStatusPayload, ListUsersQuery, UpdateUserPayload, User, UserPage,
+    UserStatusChange, UserWithRole,
+};
+use crate::models::user_attribute::UserAttributeDefinition;
+use crate::models::user_response::{FieldVisibility, UserResponse};
+use crate::services::user_attribute_service;
 
-pub async fn create_user(
-    pool: &PgPool,
-    payload: CreateUserPayload,
-) -> sqlx::Result<User> {
-    sqlx::query_as::<_, User>(
-        r#"
-        INSERT INTO users (username, email, password_hash)
-        VALUES ($1, $2, $3)
-        RETURNING
-            id,
-            username,
-            email,
-            password_hash,
-            role_id,
-            created_at,
-            updated_at
-        "#
-    )
-    .bind(payload.username)
-    .bind(payload.email)
-    .bind(payload.password_hash)
-    .fetch_one(pool)
-    .await
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// Statuses a user can be in. Only active users can sign in or be authorized.
+/// "invited" is set and cleared by the invite flow only.
+pub const USER_STATUSES: &[&str] = &["invited", "active", "suspended", "terminated"];
+
+/// Position after the last row of a page: its sort value and id.
+#[derive(Serialize, Deserialize)]
+struct UserCursor {
+    value: String,
+    id: Uuid,
 }
 
-pub async fn list_users(pool: &PgPool) -> sqlx::Result<Vec<UserWithRole>> {
-    sqlx::query_as::<_, UserWithRole>(
+fn encode_cursor(cursor: &UserCursor) -> String {
+    let json = serde_json::to_string(cursor).unwrap_or_default();
+    json.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_cursor(raw: &str) -> Option<UserCursor> {
+    if !raw.len().is_multiple_of(2) || !raw.is_ascii() {
+        return None;
+    }
+    let bytes: Option<Vec<u8>> = (0..raw.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&raw[i..i + 2], 16).ok())
+        .collect();
+    serde_json::from_slice(&bytes?).ok()
+}
+
+/// Sort column as SQL, and how its value is written into the cursor.
+fn sort_column(sort: &str) -> Option<(&'static str, &'static str)> {
+    match sort {
+        "username" => Some(("u.username", "TEXT")),
+        "email" => Some(("u.email", "TEXT")),
+        "created_at" => Some(("u.created_at", "TIMESTAMP")),
+        "role" => Some(("COALESCE(r.name, '')", "TEXT")),
+        _ => None,
+    }
+}
+
+fn push_user_filters(qb: &mut QueryBuilder<'_, Postgres>, query: &ListUsersQuery, search_email: bool) {
+    qb.push(" WHERE TRUE");
+    if let Some(role_id) = query.role_id {
+        qb.push(" AND u.role_id = ").push_bind(role_id);
+    }
+    if let Some(status) = &query.status {
+        qb.push(" AND user_status(u.id) = ").push_bind(status.clone());
+    }
+    if let Some(attributes) = &query.attributes {
+        qb.push(" AND u.attributes @> ").push_bind(attributes.clone());
+    }
+    if let Some(search) = query.search.as_deref().map(str::trim).filter(|s| !s.is_empty()) {
+        let escaped = search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+        qb.push(" AND (u.username ILIKE ").push_bind(pattern.clone());
+        if search_email {
+            qb.push(" OR u.email ILIKE ").push_bind(pattern);
+        }
+        qb.push(")");
+    }
+}
+
+/// Cursor-paginated user list. Invalid sort, order or cursor values come back
+/// as `sqlx::Error::Protocol`. Callers who can't see emails can't sort or
+/// search by them either.
+pub async fn list_users(
+    pool: &PgPool,
+    query: &ListUsersQuery,
+    visibility: &FieldVisibility,
+) -> sqlx::Result<UserPage> {
+    if let Some(status) = query.status.as_deref()
+        && !USER_STATUSES.contains(&status)
+    {
+        return Err(sqlx::Error::Protocol(format!("Unknown status '{}'", status)));
+    }
+    let sort = query.sort.as_deref().unwrap_or("username");
+    if sort == "email" && !visibility.private_fields {
+        return Err(sqlx::Error::Protocol("Cannot sort by 'email'".into()));
+    }
+    let (column, cursor_type) = sort_column(sort)
+        .ok_or_else(|| sqlx::Error::Protocol(format!("Cannot sort by '{}'", sort)))?;
+    let descending = match query.order.as_deref().unwrap_or("asc") {
+        "asc" => false,
+        "desc" => true,
+        other => return Err(sqlx::Error::Protocol(format!("Unknown sort order '{}'", other))),
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let cursor = query
+        .cursor
+        .as_deref()
+        .map(|raw| decode_cursor(raw).ok_or_else(|| sqlx::Error::Protocol("Invalid cursor".into())))
+        .transpose()?;
+
+    let mut count = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM users u LEFT JOIN roles r ON u.role_id = r.id");
+    push_user_filters(&mut count, query, visibility.private_fields);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+
+    let mut qb = QueryBuilder::<Postgres>::new(format!(
         r#"
         SELECT
             u.id,
             u.username,
             u.email,
-            u.password_hash,
             u.role_id,
             r.name as role_name,
+            u.display_name,
+            u.avatar_version,
+            user_status(u.id) AS status,
+            u.attributes,
             u.created_at,
-            u.updated_at
+            u.updated_at,
+            {}::TEXT AS sort_value
         FROM users u
         LEFT JOIN roles r ON u.role_id = r.id
-        "#
-    )
-    .fetch_all(pool)
-    .await
+        "#,
+        column
+    ));
+    push_user_filters(&mut qb, query, visibility.private_fields);
+    if let Some(cursor) = &cursor {
+        qb.push(format!(" AND ({}, u.id) {} (", column, if descending { "<" } else { ">" }))
+            .push_bind(cursor.value.clone())
+            .push(format!("::{}, ", cursor_type))
+            .push_bind(cursor.id)
+            .push(")");
+    }
+    let direction = if descending { "DESC" } else { "ASC" };
+    qb.push(format!(" ORDER BY {} {}, u.id {} LIMIT ", column, direction, direction))
+        .push_bind(limit + 1);
+
+    let mut rows: Vec<(UserWithRole, String)> = qb
+        .build()
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| Ok((UserWithRole::from_row(row)?, row.try_get::<String, _>("sort_value")?)))
+        .collect::<sqlx::Result<_>>()?;
+
+    let next_cursor = if rows.len() as i64 > limit {
+        rows.truncate(limit as usize);
+        rows.last().map(|(user, value)| encode_cursor(&UserCursor { value: value.clone(), id: user.id }))
+    } else {
+        None
+    };
+
+    Ok(UserPage {
+        items: rows
+            .into_iter()
+            .map(|(user, _)| UserResponse::from_user_with_role(user, visibility))
+            .collect(),
+        total,
+        next_cursor,
+    })
+}
+
+/// Every user matching the list filters, in list order, as CSV. One column per
+/// custom attribute follows the built-in ones. Emails the caller can't see are left empty.
+pub async fn export_users_csv(
+    pool: &PgPool,
+    query: &ListUsersQuery,
+    visibility: &FieldVisibility,
+    definitions: &[UserAttributeDefinition],
+) -> sqlx::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let csv_error = |e: csv::Error| sqlx::Error::Protocol(format!("CSV export failed: {}", e));
+
+    let mut header = vec!["id", "username", "email", "role", "status", "created_at"];
+    header.extend(definitions.iter().map(|d| d.name.as_str()));
+    writer.write_record(&header).map_err(csv_error)?;
+
+    let mut page_query = ListUsersQuery { cursor: None, limit: Some(MAX_PAGE_SIZE), ..query.clone() };
+    loop {
+        let page = list_users(pool, &page_query, visibility).await?;
+        for user in &page.items {
+            let attributes = user.attributes.clone().unwrap_or_default();
+            let mut record = vec![
+                user.id.to_string(),
+                user.username.clone(),
+                user.email.clone().unwrap_or_default(),
+                user.role_name.clone().unwrap_or_default(),
+                user.status.clone(),
+                user.created_at.to_string(),
+            ];
+            record.extend(
+                definitions
+                    .iter()
+                    .map(|d| attributes.get(&d.name).map(user_attribute_service::value_text).unwrap_or_default()),
+            );
+            writer.write_record(&record).map_err(csv_error)?;
+        }
+        match page.next_cursor {
+            Some(cursor) => page_query.cursor = Some(cursor),
+            None => break,
+        }
+    }
+
+    let bytes = writer.into_inner().map_err(|e| sqlx::Error::Protocol(format!("CSV export failed: {}", e)))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
 }
 
 pub async fn get_user(pool: &PgPool, id: Uuid) -> sqlx::Result<User> {
@@ -57,6 +219,7 @@ pub async fn get_user(pool: &PgPool, id: Uuid) -> sqlx::Result<User> {
             email,
             password_hash,
             role_id,
+            user_status(id) AS status,
             created_at,
             updated_at
         FROM users
@@ -75,9 +238,12 @@ pub async fn get_user_with_role(pool: &PgPool, id: Uuid) -> sqlx::Result<UserWit
             u.id,
             u.username,
             u.email,
-            u.password_hash,
             u.role_id,
             r.name as role_name,
+            u.display_name,
+            u.avatar_version,
+            user_status(u.id) AS status,
+            u.attributes,
             u.created_at,
             u.updated_at
         FROM users u
@@ -109,6 +275,7 @@ pub async fn update_user(
             email,
             password_hash,
             role_id,
+            user_status(id) AS status,
             created_at,
             updated_at
         "#
@@ -120,18 +287,121 @@ pub async fn update_user(
     .await
 }
 
-pub async fn delete_user(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
-    let result = sqlx::query(
+/// Hard-deletes the user and, through the cascades, their leave and payroll
+/// history. Only users terminated at least `retention_days` ago, or invited
+/// users who never activated, are removed; anyone else is left alone and `0`
+/// is returned. Sessions and policy bindings
+/// of the user go with them.
+pub async fn delete_user(pool: &PgPool, id: Uuid, retention_days: i32) -> sqlx::Result<u64> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM sessions
+        WHERE user_id = $1 AND user_status(user_id) IN ('invited', 'terminated')
+        "#
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+
+    let deleted = sqlx::query(
         r#"
         DELETE FROM users
         WHERE id = $1
+        AND (
+            user_status(id) = 'invited'
+            OR (
+                user_status(id) = 'terminated'
+                AND (
+                    SELECT MAX(effective_at) FROM user_status_changes
+                    WHERE user_id = $1 AND status = 'terminated' AND effective_at <= CURRENT_TIMESTAMP
+                ) <= CURRENT_TIMESTAMP - make_interval(days => $2)
+            )
+        )
         "#
     )
     .bind(id)
-    .execute(pool)
-    .await?;
+    .bind(retention_days)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
 
-    Ok(result.rows_affected())
+    if deleted > 0 {
+        sqlx::query("DELETE FROM policy_bindings WHERE subject_type = 'user' AND subject_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+/// Records a status change. When it is already in force and makes the user
+/// inactive, every session of the user is revoked in the same transaction.
+/// Sessions are the only credentials the API issues.
+pub async fn change_status(
+    pool: &PgPool,
+    user_id: Uuid,
+    payload: &ChangeUserStatusPayload,
+    changed_by: Uuid,
+) -> sqlx::Result<UserStatusChange> {
+    if !USER_STATUSES.contains(&payload.status.as_str()) {
+        return Err(sqlx::Error::Protocol(format!("Unknown status '{}'", payload.status)));
+    }
+    if payload.status == "invited" {
+        return Err(sqlx::Error::Protocol("Users can only be invited when they are created".into()));
+    }
+
+    let now = chrono::Utc::now().naive_utc();
+    let effective_at = payload.effective_at.unwrap_or(now);
+
+    let mut tx = pool.begin().await?;
+
+    let change = sqlx::query_as::<_, UserStatusChange>(
+        r#"
+        INSERT INTO user_status_changes (user_id, status, effective_at, reason, changed_by)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, user_id, status, effective_at, reason, changed_by, created_at
+        "#
+    )
+    .bind(user_id)
+    .bind(&payload.status)
+    .bind(effective_at)
+    .bind(&payload.reason)
+    .bind(changed_by)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => sqlx::Error::RowNotFound,
+        other => other,
+    })?;
+
+    if payload.status != "active" && effective_at <= now {
+        sqlx::query("DELETE FROM sessions WHERE user_id = $1")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+    Ok(change)
+}
+
+/// Every status change of the user, scheduled ones included, newest first.
+pub async fn list_status_history(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<UserStatusChange>> {
+    sqlx::query_as::<_, UserStatusChange>(
+        r#"
+        SELECT id, user_id, status, effective_at, reason, changed_by, created_at
+        FROM user_status_changes
+        WHERE user_id = $1
+        ORDER BY effective_at DESC, created_at DESC
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
 }
 
 pub async fn get_users_by_role_level_lte(
@@ -140,10 +410,12 @@ pub async fn get_users_by_role_level_lte(
 ) -> sqlx::Result<Vec<User>> {
     sqlx::query_as::<_, User>(
         r#"
-        SELECT u.*
+        SELECT u.id, u.username, u.email, u.password_hash, u.role_id,
+               user_status(u.id) AS status, u.created_at, u.updated_at
         FROM users u
         JOIN roles r ON u.role_id = r.id
         WHERE r.level <= $1
+        AND user_status(u.id) = 'active'
         "#
     )
     .bind(level)
@@ -1,10 +1,33 @@
+use std::sync::Arc;
+
 use sqlx::PgPool;
+use crate::services::attribute_service::{self, AttributeProviders};
+use crate::services::mail_service::{Mailer, OutboxMailer};
+use crate::services::self_profile_service;
+use crate::services::storage_service::{self, Storage};
 use crate::state::notification_hub::NotificationHub;
 
+/// Roughly seven years, the usual payroll record retention
+const DEFAULT_USER_RETENTION_DAYS: i32 = 2555;
+
 #[derive(Clone)]
 pub struct AppState {
     pub db: PgPool,
     pub notifications: NotificationHub,
+    /// When set, policy activation, archiving and binding changes need a second approver
+    pub policy_change_approval: bool,
+    /// Enrich the `AuthContext` of every authorization decision
+    pub attribute_providers: AttributeProviders,
+    /// Days a user must have been terminated before they can be hard-deleted
+    pub user_retention_days: i32,
+    /// Delivers invites and other mail
+    pub mailer: Arc<dyn Mailer>,
+    /// Frontend URL used in links sent by mail
+    pub app_base_url: String,
+    /// Fields users may change through `/api/me/profile`
+    pub self_editable_fields: Vec<String>,
+    /// Holds uploaded files such as avatars
+    pub storage: Arc<dyn Storage>,
 }
 
 impl AppState {
@@ -18,6 +41,39 @@ impl AppState {
             .expect("Failed to run migrations");
 
         let notifications = NotificationHub::new();
-        Self { db, notifications }
+
+        let policy_change_approval = std::env::var("POLICY_CHANGE_APPROVAL")
+            .map(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let attribute_providers = AttributeProviders::builtin(
+            attribute_service::org_timezone_from_env(),
+            attribute_service::trusted_proxies_from_env(),
+        );
+
+        let user_retention_days = std::env::var("USER_RETENTION_DAYS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_USER_RETENTION_DAYS);
+
+        let mailer: Arc<dyn Mailer> = Arc::new(OutboxMailer { pool: db.clone() });
+
+        let app_base_url = std::env::var("APP_BASE_URL").unwrap_or_else(|_| "http://localhost:5173".to_string());
+
+        let self_editable_fields = self_profile_service::editable_fields_from_env();
+
+        let storage: Arc<dyn Storage> = Arc::new(storage_service::local_storage_from_env());
+
+        Self {
+            db,
+            notifications,
+            policy_change_approval,
+            attribute_providers,
+            user_retention_days,
+            mailer,
+            app_base_url,
+            self_editable_fields,
+            storage,
+        }
     }
 }
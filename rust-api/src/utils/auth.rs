@@ -1,58 +1,202 @@
 use axum::{
     http::{StatusCode, HeaderMap, header},
 };
+use uuid::Uuid;
 use crate::{
     models::user::User,
-    models::user_role::AuthContext,
+    models::user_response::FieldVisibility,
+    models::row_filter::RowFilter,
+    models::user_role::{AuthContext, Decision},
+    services::attribute_service::{self, AttributeRequest, ResourceRef},
+    services::audit_service,
     services::auth_service,
     services::user_service,
     state::app_state::AppState,
+    utils::permissions::{self, Permission},
 };
 
-pub async fn authorize_action(
-    state: &AppState,
-    headers: &HeaderMap,
-    action: &str,
-    resource: &str,
-) -> Result<User, StatusCode> {
-    let cookie_header = headers.get(header::COOKIE)
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-    
-    let cookie_str = cookie_header.to_str().map_err(|_| StatusCode::BAD_REQUEST)?;
-    let token = cookie_str.split(';')
-        .find(|s| s.trim().starts_with("session_token="))
-        .map(|s| s.trim().trim_start_matches("session_token="))
-        .ok_or(StatusCode::UNAUTHORIZED)?;
-
-    let session = auth_service::validate_session(&state.db, token)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+/// Resolves the session cookie to the signed-in user.
+pub async fn current_user(state: &AppState, headers: &HeaderMap) -> Result<User, StatusCode> {
+    let user_id = get_user_id_from_headers(state, headers).await?;
 
-    let user = user_service::get_user(&state.db, session.user_id)
+    user_service::get_user(&state.db, user_id)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
 
-    let context = AuthContext {
-        department: None, // Could be enriched from user profile
+/// Bare context with only the current time. Use `build_context` when handling
+/// a request so the attribute providers can fill in the rest.
+pub fn request_context() -> AuthContext {
+    AuthContext {
+        department: None,
         location: None,
         time: chrono::Utc::now().to_rfc3339(),
         resource_owner_id: None,
+        attributes: Default::default(),
+    }
+}
+
+/// Context for a request, enriched by every provider registered in `AppState`.
+/// `resource` names the record the decision is about, if any. Fails with 503
+/// when a provider can't supply its attributes, rather than deciding without them.
+pub async fn build_context(
+    state: &AppState,
+    headers: &HeaderMap,
+    user: &User,
+    resource: Option<ResourceRef<'_>>,
+) -> Result<AuthContext, StatusCode> {
+    let mut context = request_context();
+    let request = AttributeRequest { headers, peer: attribute_service::peer_addr(), user, resource };
+    state
+        .attribute_providers
+        .enrich(&state.db, &request, &mut context)
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    Ok(context)
+}
+
+/// `base`, a context `build_context` made for this request without a record,
+/// extended with the attributes of one record. Cheaper than building a new
+/// context when a request decides about several records.
+pub async fn record_context(
+    state: &AppState,
+    headers: &HeaderMap,
+    user: &User,
+    base: &AuthContext,
+    resource: ResourceRef<'_>,
+) -> Result<AuthContext, StatusCode> {
+    let mut context = base.clone();
+    let request = AttributeRequest { headers, peer: attribute_service::peer_addr(), user, resource: Some(resource) };
+    state
+        .attribute_providers
+        .enrich_resource(&state.db, &request, &mut context)
+        .await
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
+    Ok(context)
+}
+
+/// Like `authorize_action`, but also returns the decision so callers can record
+/// how access was obtained (e.g. on behalf of a delegator). Pass `resource_id`
+/// when acting on one record so its attributes are available to the policies.
+pub async fn authorize_with_decision(
+    state: &AppState,
+    headers: &HeaderMap,
+    permission: Permission,
+    resource_id: Option<Uuid>,
+) -> Result<(User, Decision), StatusCode> {
+    let Permission { action, resource } = permission;
+
+    let user = current_user(state, headers).await?;
+    let target = resource_id.map(|id| ResourceRef { resource, id });
+    let context = build_context(state, headers, &user, target).await?;
+
+    let decision = match resource_id {
+        Some(id) => auth_service::authorize_record(&state.db, &user, action, resource, id, &context).await,
+        None => auth_service::authorize(&state.db, &user, action, resource, &context).await,
     };
+    let decision = decision
+        .map_err(|e| {
+            eprintln!("Authorization engine error: {:?}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
 
-    let decision = auth_service::authorize(&state.db, &user, action, resource, &context)
+    // Audit trail; decisions made inside a break-glass window carry its activation id
+    if let Err(e) = audit_service::record_decision(&state.db, user.id, action, resource, &decision).await {
+        eprintln!("Audit log error: {:?}", e);
+    }
+
+    if !decision.allowed {
+        eprintln!("Action FORBIDDEN: User {} tried to {} on {}", user.username, action, resource);
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok((user, decision))
+}
+
+/// For list endpoints: instead of all-or-nothing, returns the filter the service
+/// appends to its query. Forbidden only when the user may see no record at all.
+pub async fn authorize_rows(
+    state: &AppState,
+    headers: &HeaderMap,
+    permission: Permission,
+) -> Result<(User, RowFilter), StatusCode> {
+    let Permission { action, resource } = permission;
+
+    let user = current_user(state, headers).await?;
+    let context = build_context(state, headers, &user, None).await?;
+
+    let (decision, filter) = auth_service::authorize_rows(&state.db, &user, action, resource, &context)
         .await
         .map_err(|e| {
             eprintln!("Authorization engine error: {:?}", e);
             StatusCode::INTERNAL_SERVER_ERROR
         })?;
 
+    if let Err(e) = audit_service::record_decision(&state.db, user.id, action, resource, &decision).await {
+        eprintln!("Audit log error: {:?}", e);
+    }
+
     if !decision.allowed {
         eprintln!("Action FORBIDDEN: User {} tried to {} on {}", user.username, action, resource);
         return Err(StatusCode::FORBIDDEN);
     }
 
-    Ok(user)
+    Ok((user, filter))
+}
+
+/// Which private user fields `viewer` may see. A plain policy check: no 403 and
+/// no audit entry, and engine or attribute errors fall back to hiding the fields.
+pub async fn field_visibility(state: &AppState, headers: &HeaderMap, viewer: &User) -> FieldVisibility {
+    let Permission { action, resource } = permissions::READ_USER_PRIVATE;
+    let Ok(context) = build_context(state, headers, viewer, None).await else {
+        return FieldVisibility { viewer_id: viewer.id, private_fields: false };
+    };
+
+    let private_fields = match auth_service::authorize(&state.db, viewer, action, resource, &context).await {
+        Ok(decision) => decision.allowed,
+        Err(e) => {
+            eprintln!("Authorization engine error: {:?}", e);
+            false
+        }
+    };
+    FieldVisibility { viewer_id: viewer.id, private_fields }
+}
+
+pub async fn authorize_action(
+    state: &AppState,
+    headers: &HeaderMap,
+    permission: Permission,
+) -> Result<User, StatusCode> {
+    authorize_with_decision(state, headers, permission, None)
+        .await
+        .map(|(user, _)| user)
+}
+
+/// Lets users act on their own account without the permission; anyone else
+/// needs it. Returns the caller.
+pub async fn authorize_self_or(
+    state: &AppState,
+    headers: &HeaderMap,
+    user_id: Uuid,
+    permission: Permission,
+) -> Result<User, StatusCode> {
+    let caller = current_user(state, headers).await?;
+    if caller.id == user_id {
+        return Ok(caller);
+    }
+    authorize_action(state, headers, permission).await
+}
+
+/// `authorize_action` for a specific record.
+pub async fn authorize_resource(
+    state: &AppState,
+    headers: &HeaderMap,
+    permission: Permission,
+    resource_id: Uuid,
+) -> Result<User, StatusCode> {
+    authorize_with_decision(state, headers, permission, Some(resource_id))
+        .await
+        .map(|(user, _)| user)
 }
 
 /// Helper to extract user ID from session without authorization check
@@ -1,3 +1,5 @@
 // src/utils/mod.rs
 pub mod errors;
 pub mod auth;
+pub mod permissions;
+pub mod policy_lang;
@@ -0,0 +1,254 @@
+use std::sync::OnceLock;
+
+use serde::Serialize;
+
+/// Registry of every action and resource the PBAC engine knows about.
+/// Handlers reference the `Permission` constants below instead of string literals,
+/// and every route declares in the route table which permission guards it.
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct ActionDef {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct ResourceDef {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Permission {
+    pub action: &'static str,
+    pub resource: &'static str,
+}
+
+impl Permission {
+    pub const fn new(action: &'static str, resource: &'static str) -> Self {
+        Self { action, resource }
+    }
+}
+
+/// What a route requires of the caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Checked with `authorize_action`. Routes that only check it for records
+    /// owned by someone else count too.
+    Permission(Permission),
+    /// Public, only needs a signed-in user, or only acts on the caller's own records
+    Session,
+}
+
+pub const SESSION: Access = Access::Session;
+
+impl From<Permission> for Access {
+    fn from(permission: Permission) -> Self {
+        Access::Permission(permission)
+    }
+}
+
+/// One method and path the app serves, as declared in the route table.
+#[derive(Debug, Clone)]
+pub struct RouteDef {
+    pub method: String,
+    pub path: String,
+    pub access: Access,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ProtectedRoute {
+    pub method: String,
+    pub path: String,
+    #[serde(flatten)]
+    pub permission: Permission,
+}
+
+pub const WILDCARD: &str = "*";
+
+pub const ACTIONS: &[ActionDef] = &[
+    ActionDef { name: "create", description: "Create a new record" },
+    ActionDef { name: "read", description: "View records owned by other users" },
+    ActionDef { name: "update", description: "Modify a record or change its status" },
+    ActionDef { name: "delete", description: "Permanently remove a record" },
+    ActionDef { name: "edit", description: "Add or remove rules on a draft policy" },
+    ActionDef { name: "activate", description: "Move a draft policy to active" },
+    ActionDef { name: "archive", description: "Retire an active policy" },
+    ActionDef { name: "bind", description: "Attach or detach a policy to a role or user" },
+    ActionDef { name: "simulate", description: "Dry-run an authorization decision" },
+    ActionDef { name: "approve", description: "Approve or reject a change proposed by another user" },
+];
+
+pub const RESOURCES: &[ResourceDef] = &[
+    ResourceDef { name: "user", description: "Employee accounts" },
+    ResourceDef { name: "user_private", description: "Private user fields such as email" },
+    ResourceDef { name: "mail_outbox", description: "Mail stored by the local outbox mailer" },
+    ResourceDef { name: "user_attribute", description: "Definitions of custom user attributes" },
+    ResourceDef { name: "leave_request", description: "Leave requests submitted by employees" },
+    ResourceDef { name: "leave_type", description: "Kinds of leave and the rules for requesting them" },
+    ResourceDef { name: "leave_balance", description: "Leave balances and their ledgers" },
+    ResourceDef { name: "holiday_calendar", description: "Weekend days and public holidays per location" },
+    ResourceDef { name: "report", description: "Work reports submitted by employees" },
+    ResourceDef { name: "payslip", description: "Issued payslips" },
+    ResourceDef { name: "payslip_template", description: "Payslip layout templates" },
+    ResourceDef { name: "policy", description: "PBAC policies, rules and bindings" },
+    ResourceDef { name: "notification", description: "System notifications" },
+    ResourceDef { name: "auth", description: "The authorization engine itself" },
+    ResourceDef { name: "break_glass", description: "Emergency access grants and their activations" },
+    ResourceDef { name: "delegation", description: "Permissions temporarily delegated between users" },
+    ResourceDef { name: "record_share", description: "Grants giving one user access to a single record" },
+    ResourceDef { name: "access_review", description: "Periodic certification campaigns over policy bindings" },
+];
+
+// Users
+pub const CREATE_USER: Permission = Permission::new("create", "user");
+pub const READ_USER: Permission = Permission::new("read", "user");
+pub const READ_USER_PRIVATE: Permission = Permission::new("read", "user_private");
+pub const READ_MAIL_OUTBOX: Permission = Permission::new("read", "mail_outbox");
+pub const UPDATE_USER: Permission = Permission::new("update", "user");
+pub const DELETE_USER: Permission = Permission::new("delete", "user");
+
+// Custom user attribute definitions (values are covered by the user permissions)
+pub const CREATE_USER_ATTRIBUTE: Permission = Permission::new("create", "user_attribute");
+pub const UPDATE_USER_ATTRIBUTE: Permission = Permission::new("update", "user_attribute");
+pub const DELETE_USER_ATTRIBUTE: Permission = Permission::new("delete", "user_attribute");
+
+// Leave requests
+pub const READ_LEAVE_REQUEST: Permission = Permission::new("read", "leave_request");
+pub const UPDATE_LEAVE_REQUEST: Permission = Permission::new("update", "leave_request");
+
+// Leave types (reading them is covered by the leave request permission)
+pub const CREATE_LEAVE_TYPE: Permission = Permission::new("create", "leave_type");
+pub const UPDATE_LEAVE_TYPE: Permission = Permission::new("update", "leave_type");
+pub const DELETE_LEAVE_TYPE: Permission = Permission::new("delete", "leave_type");
+
+// Leave balances (employees always see their own)
+pub const READ_LEAVE_BALANCE: Permission = Permission::new("read", "leave_balance");
+pub const UPDATE_LEAVE_BALANCE: Permission = Permission::new("update", "leave_balance");
+
+// Holiday calendars (everyone may read them)
+pub const CREATE_HOLIDAY_CALENDAR: Permission = Permission::new("create", "holiday_calendar");
+pub const UPDATE_HOLIDAY_CALENDAR: Permission = Permission::new("update", "holiday_calendar");
+pub const DELETE_HOLIDAY_CALENDAR: Permission = Permission::new("delete", "holiday_calendar");
+
+// Reports
+pub const READ_REPORT: Permission = Permission::new("read", "report");
+pub const UPDATE_REPORT: Permission = Permission::new("update", "report");
+
+// Payslips
+pub const CREATE_PAYSLIP: Permission = Permission::new("create", "payslip");
+pub const READ_PAYSLIP: Permission = Permission::new("read", "payslip");
+
+// Payslip templates
+pub const CREATE_PAYSLIP_TEMPLATE: Permission = Permission::new("create", "payslip_template");
+pub const READ_PAYSLIP_TEMPLATE: Permission = Permission::new("read", "payslip_template");
+pub const UPDATE_PAYSLIP_TEMPLATE: Permission = Permission::new("update", "payslip_template");
+pub const DELETE_PAYSLIP_TEMPLATE: Permission = Permission::new("delete", "payslip_template");
+
+// Policies
+pub const READ_POLICY: Permission = Permission::new("read", "policy");
+pub const CREATE_POLICY: Permission = Permission::new("create", "policy");
+pub const EDIT_POLICY: Permission = Permission::new("edit", "policy");
+pub const ACTIVATE_POLICY: Permission = Permission::new("activate", "policy");
+pub const ARCHIVE_POLICY: Permission = Permission::new("archive", "policy");
+pub const DELETE_POLICY: Permission = Permission::new("delete", "policy");
+pub const BIND_POLICY: Permission = Permission::new("bind", "policy");
+pub const APPROVE_POLICY: Permission = Permission::new("approve", "policy");
+
+// Notifications
+pub const UPDATE_NOTIFICATION: Permission = Permission::new("update", "notification");
+
+// Break-glass emergency access
+pub const CREATE_BREAK_GLASS: Permission = Permission::new("create", "break_glass");
+pub const READ_BREAK_GLASS: Permission = Permission::new("read", "break_glass");
+pub const DELETE_BREAK_GLASS: Permission = Permission::new("delete", "break_glass");
+
+// Delegations
+pub const READ_DELEGATION: Permission = Permission::new("read", "delegation");
+pub const DELETE_DELEGATION: Permission = Permission::new("delete", "delegation");
+
+// Record shares (owners manage their own without these)
+pub const CREATE_RECORD_SHARE: Permission = Permission::new("create", "record_share");
+pub const READ_RECORD_SHARE: Permission = Permission::new("read", "record_share");
+pub const DELETE_RECORD_SHARE: Permission = Permission::new("delete", "record_share");
+
+// Access review campaigns
+pub const CREATE_ACCESS_REVIEW: Permission = Permission::new("create", "access_review");
+pub const READ_ACCESS_REVIEW: Permission = Permission::new("read", "access_review");
+pub const UPDATE_ACCESS_REVIEW: Permission = Permission::new("update", "access_review");
+
+// Authorization engine
+pub const SIMULATE_AUTH: Permission = Permission::new("simulate", "auth");
+
+pub fn is_known_action(name: &str) -> bool {
+    name == WILDCARD || ACTIONS.iter().any(|a| a.name == name)
+}
+
+pub fn is_known_resource(name: &str) -> bool {
+    name == WILDCARD || RESOURCES.iter().any(|r| r.name == name)
+}
+
+/// Every concrete (action, resource) pair the registry knows about.
+pub fn all_permissions() -> impl Iterator<Item = Permission> {
+    ACTIONS
+        .iter()
+        .flat_map(|a| RESOURCES.iter().map(move |r| Permission::new(a.name, r.name)))
+}
+
+/// Every route of the app with what it requires, from the route table.
+pub fn routes() -> &'static [RouteDef] {
+    static ROUTES: OnceLock<Vec<RouteDef>> = OnceLock::new();
+    ROUTES.get_or_init(|| crate::app::route_table().into_routes())
+}
+
+/// The routes guarded by a permission, with the permission each checks.
+pub fn protected_routes() -> Vec<ProtectedRoute> {
+    routes()
+        .iter()
+        .filter_map(|r| match r.access {
+            Access::Permission(permission) => Some(ProtectedRoute { method: r.method.clone(), path: r.path.clone(), permission }),
+            Access::Session => None,
+        })
+        .collect()
+}
+
+/// Startup check: every protected route must use a registered action and
+/// resource. Returns the list of problems found.
+pub fn verify_registry() -> Result<(), Vec<String>> {
+    let mut problems = Vec::new();
+
+    for r in routes() {
+        let Access::Permission(permission) = r.access else { continue };
+        if permission.action == WILDCARD || !is_known_action(permission.action) {
+            problems.push(format!("{} {} uses unregistered action '{}'", r.method, r.path, permission.action));
+        }
+        if permission.resource == WILDCARD || !is_known_resource(permission.resource) {
+            problems.push(format!("{} {} uses unregistered resource '{}'", r.method, r.path, permission.resource));
+        }
+    }
+
+    if problems.is_empty() { Ok(()) } else { Err(problems) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access(method: &str, path: &str) -> Option<Access> {
+        routes().iter().find(|r| r.method == method && r.path == path).map(|r| r.access)
+    }
+
+    #[test]
+    fn registry_is_consistent() {
+        assert_eq!(verify_registry(), Ok(()));
+    }
+
+    #[test]
+    fn nested_routes_carry_their_full_path() {
+        assert_eq!(access("POST", "/api/users"), Some(Access::Permission(CREATE_USER)));
+        assert_eq!(access("GET", "/api/users/{id}/leave-balances"), Some(Access::Permission(READ_LEAVE_BALANCE)));
+        assert_eq!(access("POST", "/api/management/changes/{id}/approve"), Some(Access::Permission(APPROVE_POLICY)));
+        assert_eq!(access("POST", "/api/auth/login"), Some(Access::Session));
+        assert_eq!(access("GET", "/ws/notifications"), Some(Access::Session));
+    }
+}
@@ -0,0 +1,634 @@
+//! Textual policy language. A policy is a list of statements:
+//!
+//! ```text
+//! // Ops staff may approve leave for their own team
+//! permit(action == "update", resource == leave_request)
+//!     when { context.department == "Ops" && resource.owner in principal.reports };
+//! forbid(action == *, resource == payslip_template);
+//! ```
+//!
+//! Conditions map onto `RuleConditions`:
+//! `resource.owner == principal`, `resource.owner in principal.reports`,
+//! `resource.department == "Name"` or `== principal.department`,
+//! `context.department == "Name"`, `context.location == "Name"`, and
+//! `context.<attribute> == "value"` for any provider attribute such as
+//! `context.request.ip` or `context.time.weekday`. Attribute names that
+//! aren't dotted identifiers are quoted: `context."cost-centre" == "42"`.
+
+use serde::Serialize;
+
+use crate::models::row_filter::{ContextConditions, RuleConditions};
+use crate::models::user_role::PolicyRule;
+use crate::utils::permissions;
+
+/// One statement, i.e. one row of `policy_rules`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleSource {
+    pub effect: String,
+    pub action: String,
+    pub resource: String,
+    pub conditions: Option<RuleConditions>,
+}
+
+impl RuleSource {
+    /// Converts a stored rule; fails if its conditions can't be expressed in the language.
+    pub fn from_rule(rule: &PolicyRule) -> Result<Self, String> {
+        let conditions = match &rule.conditions {
+            Some(value) if !value.is_null() => Some(RuleConditions::parse(value)?),
+            _ => None,
+        };
+        Ok(Self {
+            effect: rule.effect.clone(),
+            action: rule.action.clone(),
+            resource: rule.resource.clone(),
+            conditions: conditions.filter(|c| !c.is_empty()),
+        })
+    }
+
+    pub fn conditions_json(&self) -> Option<serde_json::Value> {
+        self.conditions
+            .as_ref()
+            .map(|c| serde_json::to_value(c).unwrap_or(serde_json::Value::Null))
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Str(String),
+    Star,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Comma,
+    Semi,
+    Dot,
+    EqEq,
+    AndAnd,
+    Eof,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Ident(name) => write!(f, "'{}'", name),
+            Token::Str(value) => write!(f, "\"{}\"", value),
+            Token::Star => write!(f, "'*'"),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+            Token::LBrace => write!(f, "'{{'"),
+            Token::RBrace => write!(f, "'}}'"),
+            Token::Comma => write!(f, "','"),
+            Token::Semi => write!(f, "';'"),
+            Token::Dot => write!(f, "'.'"),
+            Token::EqEq => write!(f, "'=='"),
+            Token::AndAnd => write!(f, "'&&'"),
+            Token::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+struct Spanned {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+fn error(message: impl Into<String>, line: usize, column: usize) -> ParseError {
+    ParseError { message: message.into(), line, column }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Spanned>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    let (mut line, mut column) = (1, 1);
+
+    while let Some(&c) = chars.peek() {
+        let (start_line, start_column) = (line, column);
+        let mut advance = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            let c = chars.next();
+            if c == Some('\n') {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+            c
+        };
+
+        let token = match c {
+            c if c.is_whitespace() => {
+                advance(&mut chars);
+                continue;
+            }
+            '/' => {
+                advance(&mut chars);
+                if chars.peek() != Some(&'/') {
+                    return Err(error("unexpected '/'; comments start with '//'", start_line, start_column));
+                }
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    advance(&mut chars);
+                }
+                continue;
+            }
+            '"' => {
+                advance(&mut chars);
+                let mut value = String::new();
+                loop {
+                    match advance(&mut chars) {
+                        Some('"') => break,
+                        Some('\\') => match advance(&mut chars) {
+                            Some(escaped @ ('"' | '\\')) => value.push(escaped),
+                            _ => return Err(error("invalid escape in string", start_line, start_column)),
+                        },
+                        Some('\n') | None => {
+                            return Err(error("unterminated string", start_line, start_column));
+                        }
+                        Some(other) => value.push(other),
+                    }
+                }
+                Token::Str(value)
+            }
+            '=' | '&' => {
+                advance(&mut chars);
+                if chars.peek() != Some(&c) {
+                    return Err(error(format!("expected '{}{}'", c, c), start_line, start_column));
+                }
+                advance(&mut chars);
+                if c == '=' { Token::EqEq } else { Token::AndAnd }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while chars.peek().is_some_and(|&c| c.is_ascii_alphanumeric() || c == '_') {
+                    name.extend(advance(&mut chars));
+                }
+                Token::Ident(name)
+            }
+            _ => {
+                advance(&mut chars);
+                match c {
+                    '*' => Token::Star,
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '{' => Token::LBrace,
+                    '}' => Token::RBrace,
+                    ',' => Token::Comma,
+                    ';' => Token::Semi,
+                    '.' => Token::Dot,
+                    other => return Err(error(format!("unexpected character '{}'", other), start_line, start_column)),
+                }
+            }
+        };
+        tokens.push(Spanned { token, line: start_line, column: start_column });
+    }
+
+    tokens.push(Spanned { token: Token::Eof, line, column });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Spanned {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> &Spanned {
+        let spanned = &self.tokens[self.pos];
+        if spanned.token != Token::Eof {
+            self.pos += 1;
+        }
+        spanned
+    }
+
+    fn error_here(&self, message: impl Into<String>) -> ParseError {
+        let spanned = self.peek();
+        error(message, spanned.line, spanned.column)
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), ParseError> {
+        if self.peek().token == expected {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.error_here(format!("expected {} but found {}", expected, self.peek().token)))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        match &self.peek().token {
+            Token::Ident(name) if name == keyword => {
+                self.next();
+                Ok(())
+            }
+            other => Err(self.error_here(format!("expected '{}' but found {}", keyword, other))),
+        }
+    }
+
+    /// `*`, a quoted name or a bare name, checked against the permission registry.
+    fn target(&mut self, kind: &str, known: fn(&str) -> bool) -> Result<String, ParseError> {
+        let spanned = self.peek();
+        let (line, column) = (spanned.line, spanned.column);
+        let value = match &spanned.token {
+            Token::Star => permissions::WILDCARD.to_string(),
+            Token::Str(name) | Token::Ident(name) => name.clone(),
+            other => return Err(self.error_here(format!("expected {} name or '*' but found {}", kind, other))),
+        };
+        self.next();
+        if !known(&value) {
+            return Err(error(format!("unknown {} '{}'", kind, value), line, column));
+        }
+        Ok(value)
+    }
+
+    /// A dotted path such as `resource.owner`. Segments after the first may be
+    /// quoted; the flag tells whether any was.
+    fn path(&mut self) -> Result<(String, bool, usize, usize), ParseError> {
+        let (line, column) = (self.peek().line, self.peek().column);
+        let mut parts = Vec::new();
+        let mut quoted = false;
+        loop {
+            match &self.peek().token {
+                Token::Ident(name) => parts.push(name.clone()),
+                Token::Str(name) if !parts.is_empty() => {
+                    parts.push(name.clone());
+                    quoted = true;
+                }
+                other => return Err(self.error_here(format!("expected an attribute but found {}", other))),
+            }
+            self.next();
+            if self.peek().token != Token::Dot {
+                break;
+            }
+            self.next();
+        }
+        Ok((parts.join("."), quoted, line, column))
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        match &self.peek().token {
+            Token::Str(value) => {
+                let value = value.clone();
+                self.next();
+                Ok(value)
+            }
+            other => Err(self.error_here(format!("expected a quoted string but found {}", other))),
+        }
+    }
+
+    fn condition(&mut self, conditions: &mut RuleConditions) -> Result<(), ParseError> {
+        let (attribute, quoted, line, column) = self.path()?;
+        let set = |slot: &mut Option<String>, value: String| {
+            if slot.is_some() {
+                return Err(error(format!("'{}' is constrained more than once", attribute), line, column));
+            }
+            *slot = Some(value);
+            Ok(())
+        };
+
+        match attribute.as_str() {
+            name if quoted && !name.starts_with("context.") => {
+                Err(error(format!("unknown attribute '{}'", attribute), line, column))
+            }
+            "resource.owner" => {
+                let owner = match &self.peek().token {
+                    Token::EqEq => {
+                        self.next();
+                        self.expect_keyword("principal")?;
+                        "self"
+                    }
+                    Token::Ident(name) if name == "in" => {
+                        self.next();
+                        let (target, _, line, column) = self.path()?;
+                        if target != "principal.reports" {
+                            return Err(error(format!("expected 'principal.reports' but found '{}'", target), line, column));
+                        }
+                        "reports"
+                    }
+                    other => return Err(self.error_here(format!("expected '==' or 'in' but found {}", other))),
+                };
+                set(&mut conditions.owner, owner.to_string())
+            }
+            "resource.department" => {
+                self.expect(Token::EqEq)?;
+                let value = if let Token::Ident(_) = self.peek().token {
+                    let (target, _, line, column) = self.path()?;
+                    if target != "principal.department" {
+                        return Err(error(format!("expected 'principal.department' but found '{}'", target), line, column));
+                    }
+                    "mine".to_string()
+                } else {
+                    let (line, column) = (self.peek().line, self.peek().column);
+                    let value = self.string()?;
+                    if value == "mine" || value.trim().is_empty() {
+                        return Err(error(format!("invalid department name \"{}\"", value), line, column));
+                    }
+                    value
+                };
+                set(&mut conditions.department, value)
+            }
+            "context.department" | "context.location" if !quoted => {
+                self.expect(Token::EqEq)?;
+                let value = self.string()?;
+                let context = conditions.context.get_or_insert_with(ContextConditions::default);
+                let slot = if attribute == "context.department" {
+                    &mut context.department
+                } else {
+                    &mut context.location
+                };
+                set(slot, value)
+            }
+            name if name.starts_with("context.") && name.len() > "context.".len() => {
+                self.expect(Token::EqEq)?;
+                let value = self.string()?;
+                let context = conditions.context.get_or_insert_with(ContextConditions::default);
+                let provided = name["context.".len()..].to_string();
+                if context.attributes.contains_key(&provided) {
+                    return Err(error(format!("'{}' is constrained more than once", attribute), line, column));
+                }
+                context.attributes.insert(provided, value);
+                Ok(())
+            }
+            _ => Err(error(format!("unknown attribute '{}'", attribute), line, column)),
+        }
+    }
+
+    fn statement(&mut self) -> Result<RuleSource, ParseError> {
+        let effect = match &self.peek().token {
+            Token::Ident(name) if name == "permit" => "allow",
+            Token::Ident(name) if name == "forbid" => "deny",
+            other => return Err(self.error_here(format!("expected 'permit' or 'forbid' but found {}", other))),
+        };
+        self.next();
+
+        self.expect(Token::LParen)?;
+        self.expect_keyword("action")?;
+        self.expect(Token::EqEq)?;
+        let action = self.target("action", permissions::is_known_action)?;
+        self.expect(Token::Comma)?;
+        self.expect_keyword("resource")?;
+        self.expect(Token::EqEq)?;
+        let resource = self.target("resource", permissions::is_known_resource)?;
+        self.expect(Token::RParen)?;
+
+        let mut conditions = None;
+        if matches!(&self.peek().token, Token::Ident(name) if name == "when") {
+            self.next();
+            self.expect(Token::LBrace)?;
+            let mut parsed = RuleConditions::default();
+            self.condition(&mut parsed)?;
+            while self.peek().token == Token::AndAnd {
+                self.next();
+                self.condition(&mut parsed)?;
+            }
+            self.expect(Token::RBrace)?;
+            conditions = Some(parsed);
+        }
+        self.expect(Token::Semi)?;
+
+        Ok(RuleSource {
+            effect: effect.to_string(),
+            action,
+            resource,
+            conditions,
+        })
+    }
+}
+
+/// Parses policy source into rules, in source order.
+pub fn parse(source: &str) -> Result<Vec<RuleSource>, ParseError> {
+    let mut parser = Parser { tokens: tokenize(source)?, pos: 0 };
+    let mut rules = Vec::new();
+    while parser.peek().token != Token::Eof {
+        rules.push(parser.statement()?);
+    }
+    Ok(rules)
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A provider attribute as written after `context.`: bare when every segment is
+/// an identifier and it can't be mistaken for a built-in condition, quoted otherwise.
+fn attribute_key(name: &str) -> String {
+    let identifier = |segment: &str| {
+        segment.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if name.split('.').all(identifier) && name != "department" && name != "location" {
+        name.to_string()
+    } else {
+        quote(name)
+    }
+}
+
+fn print_conditions(conditions: &RuleConditions) -> Vec<String> {
+    let mut parts = Vec::new();
+    match conditions.owner.as_deref() {
+        Some("self") => parts.push("resource.owner == principal".to_string()),
+        Some("reports") => parts.push("resource.owner in principal.reports".to_string()),
+        _ => {}
+    }
+    match conditions.department.as_deref() {
+        Some("mine") => parts.push("resource.department == principal.department".to_string()),
+        Some(name) => parts.push(format!("resource.department == {}", quote(name))),
+        None => {}
+    }
+    if let Some(context) = &conditions.context {
+        if let Some(department) = &context.department {
+            parts.push(format!("context.department == {}", quote(department)));
+        }
+        if let Some(location) = &context.location {
+            parts.push(format!("context.location == {}", quote(location)));
+        }
+        for (name, value) in &context.attributes {
+            parts.push(format!("context.{} == {}", attribute_key(name), quote(value)));
+        }
+    }
+    parts
+}
+
+/// Canonical source for a single rule; `parse` gives the same rule back.
+pub fn print_rule(rule: &RuleSource) -> String {
+    let keyword = if rule.effect == "deny" { "forbid" } else { "permit" };
+    let action = if rule.action == permissions::WILDCARD { rule.action.clone() } else { quote(&rule.action) };
+
+    let mut text = format!("{}(action == {}, resource == {})", keyword, action, rule.resource);
+    let conditions = rule.conditions.as_ref().map(print_conditions).unwrap_or_default();
+    if !conditions.is_empty() {
+        text.push_str(&format!(" when {{ {} }}", conditions.join(" && ")));
+    }
+    text.push(';');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_one(source: &str) -> RuleSource {
+        let mut rules = parse(source).unwrap();
+        assert_eq!(rules.len(), 1);
+        rules.remove(0)
+    }
+
+    fn parse_error(source: &str) -> ParseError {
+        parse(source).unwrap_err()
+    }
+
+    fn round_trips(rule: &RuleSource) {
+        let printed = print_rule(rule);
+        assert_eq!(&parse_one(&printed), rule, "printed as {}", printed);
+    }
+
+    #[test]
+    fn parses_the_documented_example() {
+        let rules = parse(
+            "// Ops staff may approve leave for their own team\n\
+             permit(action == \"update\", resource == leave_request)\n\
+                 when { context.department == \"Ops\" && resource.owner in principal.reports };\n\
+             forbid(action == *, resource == payslip_template);\n",
+        )
+        .unwrap();
+
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].effect, "allow");
+        assert_eq!(rules[0].action, "update");
+        let conditions = rules[0].conditions.as_ref().unwrap();
+        assert_eq!(conditions.owner.as_deref(), Some("reports"));
+        assert_eq!(conditions.context.as_ref().unwrap().department.as_deref(), Some("Ops"));
+        assert_eq!(rules[1].effect, "deny");
+        assert_eq!(rules[1].action, permissions::WILDCARD);
+        assert_eq!(rules[1].conditions, None);
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let err = parse_error("permit(action == read, resource == report)\n  when { resource.owner = principal };");
+        assert_eq!((err.line, err.column), (2, 25));
+        assert_eq!(err.message, "expected '=='");
+
+        let err = parse_error("permit(action == read resource == report);");
+        assert_eq!((err.line, err.column), (1, 23));
+        assert_eq!(err.message, "expected ',' but found 'resource'");
+
+        let err = parse_error("permit(action == read, resource == report)");
+        assert_eq!(err.message, "expected ';' but found end of input");
+
+        let err = parse_error("allow(action == read, resource == report);");
+        assert_eq!(err.message, "expected 'permit' or 'forbid' but found 'allow'");
+    }
+
+    #[test]
+    fn rejects_unknown_names_and_attributes() {
+        assert_eq!(parse_error("permit(action == fly, resource == report);").message, "unknown action 'fly'");
+        assert_eq!(parse_error("permit(action == read, resource == moon);").message, "unknown resource 'moon'");
+        assert_eq!(
+            parse_error("permit(action == read, resource == report) when { resource.colour == \"red\" };").message,
+            "unknown attribute 'resource.colour'"
+        );
+        assert_eq!(
+            parse_error("permit(action == read, resource == report) when { resource.\"owner\" == principal };").message,
+            "unknown attribute 'resource.owner'"
+        );
+    }
+
+    #[test]
+    fn rejects_conditions_set_twice() {
+        let err = parse_error(
+            "permit(action == read, resource == report) when { context.location == \"A\" && context.location == \"B\" };",
+        );
+        assert_eq!(err.message, "'context.location' is constrained more than once");
+    }
+
+    #[test]
+    fn rejects_malformed_strings() {
+        assert_eq!(
+            parse_error("permit(action == \"read, resource == report);").message,
+            "unterminated string"
+        );
+        assert_eq!(
+            parse_error("permit(action == \"re\\ad\", resource == report);").message,
+            "invalid escape in string"
+        );
+    }
+
+    #[test]
+    fn printed_rules_parse_back() {
+        let mut attributes = std::collections::BTreeMap::new();
+        attributes.insert("request.ip".to_string(), "10.0.0.1".to_string());
+        attributes.insert("time.weekday".to_string(), "Mon".to_string());
+
+        round_trips(&RuleSource {
+            effect: "allow".to_string(),
+            action: "update".to_string(),
+            resource: "leave_request".to_string(),
+            conditions: Some(RuleConditions {
+                owner: Some("self".to_string()),
+                department: Some("mine".to_string()),
+                context: Some(ContextConditions {
+                    department: Some("Ops \"North\"".to_string()),
+                    location: Some("C:\\Site".to_string()),
+                    attributes,
+                }),
+            }),
+        });
+        round_trips(&RuleSource {
+            effect: "deny".to_string(),
+            action: permissions::WILDCARD.to_string(),
+            resource: permissions::WILDCARD.to_string(),
+            conditions: None,
+        });
+        round_trips(&RuleSource {
+            effect: "allow".to_string(),
+            action: "read".to_string(),
+            resource: "report".to_string(),
+            conditions: Some(RuleConditions {
+                owner: Some("reports".to_string()),
+                department: Some("Finance".to_string()),
+                context: None,
+            }),
+        });
+    }
+
+    #[test]
+    fn quotes_attribute_keys_that_are_not_identifiers() {
+        let mut attributes = std::collections::BTreeMap::new();
+        for key in ["cost-centre", "2fa", "request.ip", "a..b", "has space", "department", "location", "with\"quote"] {
+            attributes.insert(key.to_string(), "x".to_string());
+        }
+        let rule = RuleSource {
+            effect: "allow".to_string(),
+            action: "read".to_string(),
+            resource: "report".to_string(),
+            conditions: Some(RuleConditions {
+                context: Some(ContextConditions { attributes, ..Default::default() }),
+                ..Default::default()
+            }),
+        };
+
+        let printed = print_rule(&rule);
+        assert!(printed.contains("context.\"cost-centre\" == \"x\""), "{}", printed);
+        assert!(printed.contains("context.request.ip == \"x\""), "{}", printed);
+        assert!(printed.contains("context.\"department\" == \"x\""), "{}", printed);
+        round_trips(&rule);
+    }
+}